@@ -27,6 +27,10 @@
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
 use crate::rte;
+use crate::rte::RteData;
+use crate::servl::supv;
+use crate::servl::supv::CheckpointId;
+use crate::servl::swtimer::TimerContext;
 use crate::servl::swtimer::TimerId;
 use crate::servl::swtimer::ToutTimer;
 use crate::mcal::gpio;
@@ -103,42 +107,35 @@ impl LedmData {
 //---------------------------------------------------------------------------------------------------------------------
 
 pub fn ledm_task() {
-    ToutTimer::handle_repeat(TimerId::LedmBlinkTimer);
-    ledm_update();
-    
+    #[allow(static_mut_refs)]
+    let rt_data: &mut RteData = unsafe { &mut rte::RTE_D };
+    supv::supv_checkpoint(CheckpointId::LedmTaskStart, rt_data);
+    ToutTimer::handle_repeat(TimerId::LedmBlinkTimer, rt_data);
+    ledm_update(rt_data);
+    supv::supv_checkpoint(CheckpointId::LedmTaskEnd, rt_data);
 }
 
 #[inline(always)]
-fn ledm_update() {
-    unsafe {
-        let mut idx: u32 = 0;
-        #[allow(static_mut_refs)]
-        while idx < rte::RTE_D.ledm_data.leds.len() as u32 {
-            if rte::RTE_D.ledm_data.leds[idx as usize].state == LedState::Off {
-                gpio::Pin::clr(rte::RTE_D.ledm_data.leds[idx as usize].pin);
-            } else if rte::RTE_D.ledm_data.leds[idx as usize].state == LedState::On {
-                gpio::Pin::set(rte::RTE_D.ledm_data.leds[idx as usize].pin);
-            }
-            idx += 1;
+fn ledm_update(rt_data: &mut RteData) {
+    for led in rt_data.ledm_data.leds.iter() {
+        if led.state == LedState::Off {
+            gpio::Pin::clr(led.pin);
+        } else if led.state == LedState::On {
+            gpio::Pin::set(led.pin);
         }
     }
 
     //in case of an error need to inform a failure manager here
 }
 
-pub fn ledm_blink_timer_callback() {
-    unsafe {
-        let mut idx: u32 = 0;
-        #[allow(static_mut_refs)]
-        while idx < rte::RTE_D.ledm_data.leds.len() as u32 {
-            if rte::RTE_D.ledm_data.leds[idx as usize].state == LedState::BlinkOff {
-                gpio::Pin::clr(rte::RTE_D.ledm_data.leds[idx as usize].pin);
-                rte::RTE_D.ledm_data.leds[idx as usize].state = LedState::BlinkOn;
-            } else if rte::RTE_D.ledm_data.leds[idx as usize].state == LedState::BlinkOn {
-                gpio::Pin::set(rte::RTE_D.ledm_data.leds[idx as usize].pin);
-                rte::RTE_D.ledm_data.leds[idx as usize].state = LedState::BlinkOff;
-            }
-            idx += 1;
+pub fn ledm_blink_timer_callback(_ctx: TimerContext, rt_data: &mut RteData) {
+    for led in rt_data.ledm_data.leds.iter_mut() {
+        if led.state == LedState::BlinkOff {
+            gpio::Pin::clr(led.pin);
+            led.state = LedState::BlinkOn;
+        } else if led.state == LedState::BlinkOn {
+            gpio::Pin::set(led.pin);
+            led.state = LedState::BlinkOff;
         }
     }
 
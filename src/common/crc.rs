@@ -0,0 +1,177 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : crc.rs
+//!
+//! # Short description
+//! Software crc implementation
+//!
+//! # Detailed description
+//! Pure rust crc calculation without hardware dependency. The default crc 32 uses the polynomial 0x04C11DB7
+//! with initial value 0xFFFFFFFF, no input / output reversal and no final xor (CRC-32/MPEG-2); this matches the
+//! reset configuration of the Mcu crc peripheral.
+//!
+//! The generic crc calculation (crc_calc / crc_update / crc_final) with CrcCfg is the bit identical software model
+//! of the Mcu crc peripheral (mcal::crc): the register has the width of the polynomial, the input bytes are shifted
+//! in msb first (after a bit reversal of each byte if reflect_in is set) and the result is bit reversed over the
+//! width if reflect_out is set. There is no final xor, because the peripheral has none.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///crc 32 polynomial
+pub const CRC32_POLY: u32 = 0x04C1_1DB7;
+///crc 32 initial value
+pub const CRC32_INIT: u32 = 0xFFFF_FFFF;
+
+///configuration of the crc 32 (CRC-32/MPEG-2) for the generic calculation
+#[allow(dead_code)]
+pub const CRC32_CFG: CrcCfg = CrcCfg {
+    width: CrcWidth::Bits32,
+    poly: CRC32_POLY,
+    init: CRC32_INIT,
+    reflect_in: false,
+    reflect_out: false,
+};
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///width of the crc polynomial; the values are the widths in bits
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CrcWidth {
+    Bits7 = 7,
+    Bits8 = 8,
+    Bits16 = 16,
+    Bits32 = 32,
+}
+
+impl CrcWidth {
+    ///mask of the crc register
+    pub const fn mask(self) -> u32 {
+        u32::MAX >> (32 - self as u32)
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+///crc configuration; poly and init are given without the bits above the width, the polynomial is given in normal
+///notation without the highest bit (e.g. 0x07 for x^8 + x^2 + x + 1)
+#[derive(PartialEq, Copy, Clone)]
+pub struct CrcCfg {
+    pub width: CrcWidth,
+    pub poly: u32,
+    pub init: u32,
+    pub reflect_in: bool,
+    pub reflect_out: bool,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///continue a crc 32 calculation with the given data; start with CRC32_INIT
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut crc: u32 = crc;
+    for byte in data.iter() {
+        crc ^= (*byte as u32) << 24;
+        let mut bit: u32 = 0;
+        while bit < 8 {
+            crc = if (crc & 0x8000_0000) != 0 {
+                (crc << 1) ^ CRC32_POLY
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+    }
+    crc
+}
+
+///calculate the crc 32 of the data
+#[allow(dead_code)]
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(CRC32_INIT, data)
+}
+
+///start a generic crc calculation; returns the crc register
+#[allow(dead_code)]
+pub fn crc_start(cfg: &CrcCfg) -> u32 {
+    cfg.init & cfg.width.mask()
+}
+
+///continue a generic crc calculation with the given data; the crc register is returned, the result is got by
+///crc_final
+#[allow(dead_code)]
+pub fn crc_update(cfg: &CrcCfg, crc: u32, data: &[u8]) -> u32 {
+    let width: u32 = cfg.width as u32;
+    let mask: u32 = cfg.width.mask();
+    let top: u32 = 1 << (width - 1);
+    let mut crc: u32 = crc;
+    for byte in data.iter() {
+        let byte: u8 = if cfg.reflect_in { byte.reverse_bits() } else { *byte };
+        let mut bit: u32 = 0;
+        while bit < 8 {
+            let data_bit: bool = (byte & (0x80 >> bit)) != 0;
+            let crc_bit: bool = (crc & top) != 0;
+            crc = (crc << 1) & mask;
+            if data_bit != crc_bit {
+                crc ^= cfg.poly & mask;
+            }
+            bit += 1;
+        }
+    }
+    crc
+}
+
+///get the result of a generic crc calculation from the crc register
+#[allow(dead_code)]
+pub fn crc_final(cfg: &CrcCfg, crc: u32) -> u32 {
+    if cfg.reflect_out {
+        crc.reverse_bits() >> (32 - cfg.width as u32)
+    } else {
+        crc
+    }
+}
+
+///calculate the generic crc of the data
+#[allow(dead_code)]
+pub fn crc_calc(cfg: &CrcCfg, data: &[u8]) -> u32 {
+    crc_final(cfg, crc_update(cfg, crc_start(cfg), data))
+}
@@ -26,6 +26,8 @@
 //---------------------------------------------------------------------------------------------------------------------
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
+pub mod crc;
+pub mod ringbuf;
 pub mod util;
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,246 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : ringbuf.rs
+//!
+//! # Short description
+//! Byte ring buffer with fixed capacity
+//!
+//! # Detailed description
+//! Simple fifo for data exchange between a driver task and the application, e.g. for the software transmit and
+//! receive buffers of communication drivers. The buffer is not protected against concurrent access, which is not
+//! needed for the cooperative and interrupt free scheduling.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+pub struct RingBuf<const N: usize> {
+    buf: [u8; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> RingBuf<N> {
+    pub const fn init() -> Self {
+        Self {
+            buf: [0; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    ///add a byte at the end; returns false if the buffer is full
+    pub fn push(&mut self, byte: u8) -> bool {
+        if self.len >= N {
+            return false;
+        }
+        self.buf[(self.head + self.len) % N] = byte;
+        self.len += 1;
+        true
+    }
+
+    ///take the oldest byte
+    pub fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let byte: u8 = self.buf[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(byte)
+    }
+
+    ///get the oldest byte without taking it
+    #[allow(dead_code)]
+    pub fn peek(&self) -> Option<u8> {
+        if self.len == 0 {
+            None
+        } else {
+            Some(self.buf[self.head])
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///number of bytes that can be added
+    #[allow(dead_code)]
+    pub fn free(&self) -> usize {
+        N - self.len
+    }
+
+    #[allow(dead_code)]
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn empty_and_full() {
+        let mut rb: RingBuf<4> = RingBuf::init();
+        assert!(rb.is_empty());
+        assert_eq!((rb.len(), rb.free()), (0, 4));
+        assert_eq!(rb.pop(), None);
+        assert_eq!(rb.peek(), None);
+        for byte in 1..=4 {
+            assert!(rb.push(byte));
+        }
+        assert_eq!((rb.len(), rb.free()), (4, 0));
+        //a full buffer keeps its content
+        assert!(!rb.push(5));
+        assert_eq!(rb.peek(), Some(1));
+        for byte in 1..=4 {
+            assert_eq!(rb.pop(), Some(byte));
+        }
+        assert!(rb.is_empty());
+        assert_eq!(rb.pop(), None);
+    }
+
+    #[test]
+    fn wrap_at_capacity() {
+        let mut rb: RingBuf<3> = RingBuf::init();
+        //move the head to every position and fill up over the end of the array
+        for round in 0..10u8 {
+            for i in 0..3 {
+                assert!(rb.push(round.wrapping_mul(3).wrapping_add(i)));
+            }
+            assert!(!rb.push(0xFF));
+            for i in 0..2 {
+                assert_eq!(rb.pop(), Some(round.wrapping_mul(3).wrapping_add(i)));
+            }
+            assert_eq!(rb.len(), 1);
+            assert_eq!(rb.pop(), Some(round.wrapping_mul(3).wrapping_add(2)));
+            assert!(rb.push(round));
+            assert_eq!(rb.pop(), Some(round));
+        }
+    }
+
+    #[test]
+    fn single_and_zero_capacity() {
+        let mut rb: RingBuf<1> = RingBuf::init();
+        for byte in 0..5 {
+            assert!(rb.push(byte));
+            assert!(!rb.push(0xFF));
+            assert_eq!(rb.pop(), Some(byte));
+            assert_eq!(rb.pop(), None);
+        }
+        let mut rb: RingBuf<0> = RingBuf::init();
+        assert!(!rb.push(1));
+        assert_eq!((rb.len(), rb.free()), (0, 0));
+        assert_eq!(rb.pop(), None);
+        assert_eq!(rb.peek(), None);
+    }
+
+    #[test]
+    fn clear_restarts_at_head() {
+        let mut rb: RingBuf<4> = RingBuf::init();
+        for byte in 0..3 {
+            assert!(rb.push(byte));
+        }
+        assert_eq!(rb.pop(), Some(0));
+        rb.clear();
+        assert!(rb.is_empty());
+        assert_eq!(rb.free(), 4);
+        for byte in 10..14 {
+            assert!(rb.push(byte));
+        }
+        assert!(!rb.push(14));
+        for byte in 10..14 {
+            assert_eq!(rb.pop(), Some(byte));
+        }
+    }
+
+    ///compare a random sequence of operations with a queue
+    fn check_against_queue<const N: usize>() {
+        let mut rb: RingBuf<N> = RingBuf::init();
+        let mut queue: VecDeque<u8> = VecDeque::new();
+        let mut seed: u32 = 0x1234_5678;
+        for step in 0..10_000u32 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let byte: u8 = step as u8;
+            //alternate phases with more pushes (mostly full) and more pops (mostly empty)
+            let push_weight: u32 = if (step / 1000) % 2 == 0 { 2 } else { 1 };
+            if (seed >> 16) % 3 < push_weight {
+                let pushed: bool = rb.push(byte);
+                assert_eq!(pushed, queue.len() < N);
+                if pushed {
+                    queue.push_back(byte);
+                }
+            } else {
+                assert_eq!(rb.pop(), queue.pop_front());
+            }
+            assert_eq!(rb.len(), queue.len());
+            assert_eq!(rb.free(), N - queue.len());
+            assert_eq!(rb.peek(), queue.front().copied());
+        }
+    }
+
+    #[test]
+    fn random_operations() {
+        check_against_queue::<1>();
+        check_against_queue::<2>();
+        check_against_queue::<7>();
+        check_against_queue::<16>();
+        check_against_queue::<64>();
+    }
+}
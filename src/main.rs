@@ -32,13 +32,14 @@
 //!
 //!
 
-#![no_main]
-#![no_std]
+#![cfg_attr(not(test), no_main)]
+#![cfg_attr(not(test), no_std)]
 
 //---------------------------------------------------------------------------------------------------------------------
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
 use core::arch::asm;
+#[cfg(not(test))]
 use core::panic::PanicInfo;
 
 mod appl;
@@ -47,7 +48,6 @@ mod mcal;
 mod rte;
 mod servl;
 
-use mcal::gpt::Timer6_7;
 use servl::sched;
 use crate::mcal::gpio;
 
@@ -103,6 +103,8 @@ fn app_set_led_on() {
 }
 
 //need to define a panic handler; will get here in case of panic; ends in infinity loop
+//the host test build links std, which brings its own handler
+#[cfg(not(test))]
 #[panic_handler]
 fn panic(_panic: &PanicInfo<'_>) -> ! {
     loop {}
@@ -112,8 +114,34 @@ fn panic(_panic: &PanicInfo<'_>) -> ! {
 fn application_main() -> ! {
     let _x = 42;
 
+    //all clock dependent timings are derived from the clock configuration, so there is no operation without it
+    if mcal::rcc::init().is_err() {
+        #[allow(clippy::empty_loop)]
+        loop {}
+    }
+    mcal::pwr::init();
+    //without rtc clock the calendar is not available; the other modules do not depend on it
+    let _ = mcal::rtc::init();
     mcal::gpio::init();
-    Timer6_7::inst_6().init();
+    mcal::dma::init();
+    mcal::crc::init();
+    mcal::spi::init();
+    mcal::i2c::init();
+    //without bus the can stays in init mode and can not transmit
+    let _ = mcal::can::init();
+    mcal::pwm::init();
+    mcal::icu::init();
+    mcal::dac::init();
+    mcal::comp::init();
+    mcal::tsc::init();
+    mcal::cec::init();
+
+    #[allow(static_mut_refs)]
+    let rt_data: &mut rte::RteData = unsafe { &mut rte::RTE_D };
+    mcal::usart::init(rt_data);
+    mcal::adc::init(rt_data);
+    servl::nvm::nvm_init(rt_data);
+    servl::fm::fm_init(rt_data);
 
     crate::servl::sched::s_init();
 
@@ -0,0 +1,511 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : adc.rs
+//!
+//! # Short description
+//! Adc module implementation
+//!
+//! # Detailed description
+//! The adc converts the channels of the ADC_SEQUENCE table; the Mcu converts the selected channels always in
+//! ascending channel order, so the order of the table has no effect. The sequence may contain the 16 external
+//! channels, the temperature sensor and the internal reference voltage VREFINT. All channels use the same sampling
+//! time ADC_SAMPLE_TIME.
+//! The adc is clocked with PCLK / 2 (PCLK / 4 if PCLK / 2 exceeds the maximum adc clock of 14 MHz) and is
+//! calibrated by init. The conversions are triggered by software in
+//! discontinuous mode: adc_task reads the result of the finished conversion and starts the conversion of the next
+//! channel, so one channel is converted per call and no result can be overwritten if the task is delayed.
+//! If ADC_DMA is configured, the complete sequence is converted per trigger (scan) and a circular dma channel writes
+//! the results to a buffer in the runtime data; adc_task copies the results when the sequence is finished and
+//! starts the next sequence.
+//! The latest raw value of each channel is kept in the runtime data. The raw values are converted to millivolt with
+//! the factory calibration value of VREFINT, which was measured with VDDA = 3.3 V; so VREFINT must be part of the
+//! sequence.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::dma;
+use crate::mcal::dma::DmaCfg;
+use crate::mcal::dma::DmaChannel;
+use crate::mcal::dma::DmaDir;
+use crate::mcal::dma::DmaPriority;
+use crate::mcal::dma::DmaRequest;
+use crate::mcal::dma::DmaSize;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Pin;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::rcc::PCLK_HZ;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+use crate::rte::RteData;
+use core::ptr;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const ADC_ISR_ADRDY: u32 = 1 << 0;
+const ADC_ISR_EOC: u32 = 1 << 2;
+const ADC_ISR_EOS: u32 = 1 << 3;
+const ADC_ISR_OVR: u32 = 1 << 4;
+
+const ADC_CR_ADEN: u32 = 1 << 0;
+const ADC_CR_ADSTART: u32 = 1 << 2;
+const ADC_CR_ADCAL: u32 = 1 << 31;
+
+const ADC_CFGR1_DMAEN: u32 = 1 << 0;
+const ADC_CFGR1_DMACFG: u32 = 1 << 1;
+const ADC_CFGR1_DISCEN: u32 = 1 << 16;
+
+const ADC_CFGR2_CKMODE_PCLK_DIV2: u32 = 1 << 30;
+const ADC_CFGR2_CKMODE_PCLK_DIV4: u32 = 2 << 30;
+///maximum adc clock
+const ADC_CLK_MAX_HZ: u32 = 14_000_000;
+///clock mode with the smallest divider that keeps the adc clock in its limit
+const ADC_CFGR2_CKMODE: u32 = if PCLK_HZ / 2 <= ADC_CLK_MAX_HZ {
+    ADC_CFGR2_CKMODE_PCLK_DIV2
+} else {
+    ADC_CFGR2_CKMODE_PCLK_DIV4
+};
+const _: () = assert!(PCLK_HZ / 4 <= ADC_CLK_MAX_HZ);
+
+const ADC_CCR_VREFEN: u32 = 1 << 22;
+const ADC_CCR_TSEN: u32 = 1 << 23;
+
+const RCC_APB2ENR_ADCEN: u32 = 1 << 9;
+
+///address of the factory calibration value of VREFINT, measured with VDDA = VREFINT_CAL_VDDA_MV
+const VREFINT_CAL_ADDR: u32 = 0x1FFF_F7BA;
+const VREFINT_CAL_VDDA_MV: u32 = 3300;
+
+///maximum raw value of a 12 bit conversion
+const ADC_MAX_RAW: u32 = 4095;
+
+///number of polls of the calibration and ready flags at init
+const ADC_INIT_POLL_CNT: u32 = 10_000;
+
+///number of adc channels including the internal channels
+pub const ADC_CHANNEL_NUM: usize = 18;
+
+///converted channels
+const ADC_SEQUENCE: [AdcChannel; 3] = [AdcChannel::Ch0, AdcChannel::TempSensor, AdcChannel::VrefInt];
+
+///sampling time of all channels; the temperature sensor needs at least 4 us
+const ADC_SAMPLE_TIME: SampleTime = SampleTime::Cycles239_5;
+
+///channel select mask of the sequence
+const ADC_CHSELR: u32 = adc_calc_chselr(&ADC_SEQUENCE);
+///number of converted channels
+const ADC_SEQ_LEN: usize = ADC_CHSELR.count_ones() as usize;
+
+///dma channel of the sequence scan; None for single conversions triggered by adc_task
+const ADC_DMA: Option<DmaChannel> = Some(DmaChannel::Dma1Ch1);
+
+///dma configuration of the sequence scan
+const ADC_DMA_CFG: DmaCfg = DmaCfg {
+    dir: DmaDir::PeriphToMem,
+    circular: true,
+    periph_size: DmaSize::Bits16,
+    mem_size: DmaSize::Bits16,
+    periph_inc: false,
+    mem_inc: true,
+    priority: DmaPriority::Low,
+};
+
+//the conversion to millivolt needs the VREFINT value
+const _: () = assert!((ADC_CHSELR & (1 << AdcChannel::VrefInt as u32)) != 0);
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum AdcChannel {
+    Ch0 = 0,
+    Ch1 = 1,
+    Ch2 = 2,
+    Ch3 = 3,
+    Ch4 = 4,
+    Ch5 = 5,
+    Ch6 = 6,
+    Ch7 = 7,
+    Ch8 = 8,
+    Ch9 = 9,
+    Ch10 = 10,
+    Ch11 = 11,
+    Ch12 = 12,
+    Ch13 = 13,
+    Ch14 = 14,
+    Ch15 = 15,
+    TempSensor = 16,
+    VrefInt = 17,
+}
+
+impl AdcChannel {
+    ///get the pin of an external channel
+    const fn pin(self) -> Option<Pin> {
+        match self {
+            AdcChannel::Ch0 => Some(Pin::A0),
+            AdcChannel::Ch1 => Some(Pin::A1),
+            AdcChannel::Ch2 => Some(Pin::A2),
+            AdcChannel::Ch3 => Some(Pin::A3),
+            AdcChannel::Ch4 => Some(Pin::A4),
+            AdcChannel::Ch5 => Some(Pin::A5),
+            AdcChannel::Ch6 => Some(Pin::A6),
+            AdcChannel::Ch7 => Some(Pin::A7),
+            AdcChannel::Ch8 => Some(Pin::B0),
+            AdcChannel::Ch9 => Some(Pin::B1),
+            AdcChannel::Ch10 => Some(Pin::C0),
+            AdcChannel::Ch11 => Some(Pin::C1),
+            AdcChannel::Ch12 => Some(Pin::C2),
+            AdcChannel::Ch13 => Some(Pin::C3),
+            AdcChannel::Ch14 => Some(Pin::C4),
+            AdcChannel::Ch15 => Some(Pin::C5),
+            AdcChannel::TempSensor | AdcChannel::VrefInt => None,
+        }
+    }
+}
+
+///sampling time in adc clock cycles; the values match the SMP bits of SMPR
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SampleTime {
+    Cycles1_5 = 0,
+    Cycles7_5 = 1,
+    Cycles13_5 = 2,
+    Cycles28_5 = 3,
+    Cycles41_5 = 4,
+    Cycles55_5 = 5,
+    Cycles71_5 = 6,
+    Cycles239_5 = 7,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum AdcError {
+    Calibration,
+    NotReady,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for adc module registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Adc {
+    pub isr: u32,
+    pub ier: u32,
+    pub cr: u32,
+    pub cfgr1: u32,
+    pub cfgr2: u32,
+    pub smpr: u32,
+    pub reserved0: [u32; 2],
+    pub tr: u32,
+    pub reserved1: u32,
+    pub chselr: u32,
+    pub reserved2: [u32; 5],
+    pub dr: u32,
+    pub reserved3: [u32; 177],
+    pub ccr: u32,
+}
+
+impl Adc {
+    /// method to the instance of Adc
+    pub fn inst() -> &'static mut Adc {
+        unsafe { &mut *(0x4001_2400 as *mut Adc) }
+    }
+
+    ///calibrate the disabled adc; polls until the calibration is finished
+    pub fn calibrate(&mut self) -> Result<(), AdcError> {
+        set_reg_bitmsk(&mut self.cr, ADC_CR_ADCAL);
+        let mut cnt: u32 = 0;
+        while (get_reg(&self.cr) & ADC_CR_ADCAL) != 0 {
+            cnt += 1;
+            if cnt >= ADC_INIT_POLL_CNT {
+                return Err(AdcError::Calibration);
+            }
+        }
+        Ok(())
+    }
+
+    ///enable the adc; polls until the adc is ready
+    pub fn enable(&mut self) -> Result<(), AdcError> {
+        set_reg(&mut self.isr, ADC_ISR_ADRDY);
+        set_reg_bitmsk(&mut self.cr, ADC_CR_ADEN);
+        let mut cnt: u32 = 0;
+        while (get_reg(&self.isr) & ADC_ISR_ADRDY) == 0 {
+            cnt += 1;
+            if cnt >= ADC_INIT_POLL_CNT {
+                return Err(AdcError::NotReady);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn start(&mut self) {
+        set_reg_bitmsk(&mut self.cr, ADC_CR_ADSTART);
+    }
+
+    ///get the result of a finished conversion and whether it was the last conversion of the sequence
+    pub fn try_read(&mut self) -> Option<(u16, bool)> {
+        let isr: u32 = get_reg(&self.isr);
+        if (isr & ADC_ISR_EOC) == 0 {
+            return None;
+        }
+        //reading the data register clears EOC
+        let data: u16 = get_reg(&self.dr) as u16;
+        let eos: bool = (isr & ADC_ISR_EOS) != 0;
+        if eos {
+            set_reg(&mut self.isr, ADC_ISR_EOS);
+        }
+        Some((data, eos))
+    }
+
+    ///check and clear the end of sequence flag
+    pub fn check_end_of_sequence(&mut self) -> bool {
+        if (get_reg(&self.isr) & ADC_ISR_EOS) != 0 {
+            set_reg(&mut self.isr, ADC_ISR_EOS);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///check and clear the overrun flag
+    pub fn check_overrun(&mut self) -> bool {
+        if (get_reg(&self.isr) & ADC_ISR_OVR) != 0 {
+            set_reg(&mut self.isr, ADC_ISR_OVR);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+pub struct AdcData {
+    raw: [u16; ADC_CHANNEL_NUM],
+    valid: [bool; ADC_CHANNEL_NUM],
+    dma_buf: [u16; ADC_SEQ_LEN],
+    channel: usize,
+    active: bool,
+    seq_cnt: u32,
+    ovr_cnt: u16,
+    init_error: Option<AdcError>,
+}
+
+impl AdcData {
+    pub const fn init() -> Self {
+        Self {
+            raw: [0; ADC_CHANNEL_NUM],
+            valid: [false; ADC_CHANNEL_NUM],
+            dma_buf: [0; ADC_SEQ_LEN],
+            channel: adc_next_channel(ADC_CHANNEL_NUM),
+            active: false,
+            seq_cnt: 0,
+            ovr_cnt: 0,
+            init_error: None,
+        }
+    }
+
+    ///get the latest raw value of the channel; None if the channel was not converted yet
+    #[allow(dead_code)]
+    pub fn get_raw(&self, channel: AdcChannel) -> Option<u16> {
+        if self.valid[channel as usize] {
+            Some(self.raw[channel as usize])
+        } else {
+            None
+        }
+    }
+
+    ///get the supply voltage VDDA in millivolt calculated from the VREFINT value
+    #[allow(dead_code)]
+    pub fn get_vdda_mv(&self) -> Option<u32> {
+        let vrefint: u16 = self.get_raw(AdcChannel::VrefInt)?;
+        if vrefint == 0 {
+            return None;
+        }
+        Some(VREFINT_CAL_VDDA_MV * adc_get_vrefint_cal() as u32 / vrefint as u32)
+    }
+
+    ///get the voltage of the channel in millivolt
+    #[allow(dead_code)]
+    pub fn get_mv(&self, channel: AdcChannel) -> Option<u32> {
+        let raw: u16 = self.get_raw(channel)?;
+        Some(raw as u32 * self.get_vdda_mv()? / ADC_MAX_RAW)
+    }
+
+    ///get the number of completely converted sequences
+    #[allow(dead_code)]
+    pub fn get_seq_cnt(&self) -> u32 {
+        self.seq_cnt
+    }
+
+    #[allow(dead_code)]
+    pub fn get_ovr_cnt(&self) -> u16 {
+        self.ovr_cnt
+    }
+
+    #[allow(dead_code)]
+    pub fn get_init_error(&self) -> Option<AdcError> {
+        self.init_error
+    }
+}
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///calculate the channel select mask of the sequence
+const fn adc_calc_chselr(seq: &[AdcChannel]) -> u32 {
+    let mut mask: u32 = 0;
+    let mut idx: usize = 0;
+    while idx < seq.len() {
+        mask |= 1 << (seq[idx] as u32);
+        idx += 1;
+    }
+    mask
+}
+
+///get the channel of the sequence that is converted after the channel; the sequence restarts after the last channel
+const fn adc_next_channel(channel: usize) -> usize {
+    let mut next: usize = channel + 1;
+    while next < ADC_CHANNEL_NUM {
+        if (ADC_CHSELR & (1 << next)) != 0 {
+            return next;
+        }
+        next += 1;
+    }
+    //first channel of the sequence
+    ADC_CHSELR.trailing_zeros() as usize
+}
+
+///read the factory calibration value of VREFINT
+fn adc_get_vrefint_cal() -> u16 {
+    unsafe { ptr::read_volatile(VREFINT_CAL_ADDR as *const u16) }
+}
+
+///init and calibrate the adc; configures the pins of the external channels as analog input
+pub fn init(rt_data: &mut RteData) {
+    set_reg_bitmsk(&mut Rcc::inst().apb2enr, RCC_APB2ENR_ADCEN);
+
+    let mut idx: usize = 0;
+    while idx < ADC_SEQUENCE.len() {
+        if let Some(pin) = ADC_SEQUENCE[idx].pin() {
+            pin.cfg_mode(Mode::Analog);
+        }
+        idx += 1;
+    }
+
+    let inst: &mut Adc = Adc::inst();
+    //the clock mode can only be changed while the adc is disabled
+    set_reg(&mut inst.cfgr2, ADC_CFGR2_CKMODE);
+    let result: Result<(), AdcError> = inst.calibrate().and_then(|_| inst.enable());
+    if let Err(e) = result {
+        rt_data.adc_data.init_error = Some(e);
+        return;
+    }
+
+    match ADC_DMA {
+        Some(dma_ch) => {
+            set_reg(&mut inst.cfgr1, ADC_CFGR1_DMAEN | ADC_CFGR1_DMACFG);
+            dma::dma_start(
+                dma_ch,
+                DmaRequest::Adc,
+                &ADC_DMA_CFG,
+                &inst.dr as *const u32 as u32,
+                rt_data.adc_data.dma_buf.as_mut_ptr() as u32,
+                ADC_SEQ_LEN as u16,
+            );
+        }
+        None => set_reg(&mut inst.cfgr1, ADC_CFGR1_DISCEN),
+    }
+    set_reg(&mut inst.smpr, ADC_SAMPLE_TIME as u32);
+    set_reg(&mut inst.chselr, ADC_CHSELR);
+    let mut ccr: u32 = ADC_CCR_VREFEN;
+    if (ADC_CHSELR & (1 << AdcChannel::TempSensor as u32)) != 0 {
+        ccr |= ADC_CCR_TSEN;
+    }
+    set_reg(&mut inst.ccr, ccr);
+}
+
+///adc main function; reads the finished conversion or sequence and starts the next one; must be called
+///periodically by a task
+pub fn adc_task(rt_data: &mut RteData) {
+    let adc: &mut AdcData = &mut rt_data.adc_data;
+    if adc.init_error.is_some() {
+        return;
+    }
+    let inst: &mut Adc = Adc::inst();
+
+    if inst.check_overrun() {
+        adc.ovr_cnt = adc.ovr_cnt.saturating_add(1);
+    }
+
+    if adc.active && ADC_DMA.is_some() {
+        if !inst.check_end_of_sequence() {
+            return;
+        }
+        //the results are in ascending channel order
+        let mut channel: usize = adc_next_channel(ADC_CHANNEL_NUM);
+        let mut idx: usize = 0;
+        while idx < ADC_SEQ_LEN {
+            adc.raw[channel] = unsafe { ptr::read_volatile(&adc.dma_buf[idx]) };
+            adc.valid[channel] = true;
+            channel = adc_next_channel(channel);
+            idx += 1;
+        }
+        adc.seq_cnt = adc.seq_cnt.wrapping_add(1);
+        adc.active = false;
+    } else if adc.active {
+        match inst.try_read() {
+            Some((data, eos)) => {
+                adc.raw[adc.channel] = data;
+                adc.valid[adc.channel] = true;
+                if eos {
+                    adc.seq_cnt = adc.seq_cnt.wrapping_add(1);
+                }
+                adc.channel = adc_next_channel(adc.channel);
+                adc.active = false;
+            }
+            None => return,
+        }
+    }
+
+    inst.start();
+    adc.active = true;
+}
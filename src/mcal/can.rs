@@ -0,0 +1,549 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : can.rs
+//!
+//! # Short description
+//! Can module implementation for the bxCAN peripheral
+//!
+//! # Detailed description
+//! The bit timing is calculated at compile time from PCLK_HZ (the can is clocked by PCLK), CAN_BITRATE and
+//! CAN_SAMPLE_POINT_PERMILLE. The operating mode (normal, loopback, silent or silent loopback) is set by CAN_MODE.
+//! The acceptance filters are configured by the CAN_FILTER_CFG table; each entry configures one filter bank in mask
+//! or list mode with 16 or 32 bit scale and assigns it to a receive fifo. The register values of the filters are
+//! built with can_filter32 / can_filter16 from the can id.
+//! Transmission and reception work poll based: Can::transmit writes a frame to a free mailbox and
+//! Can::get_tx_status returns its state; Can::receive takes a frame from a receive fifo. The error counters, the last
+//! error code and the error states (warning, passive, bus-off) are read by Can::get_error_state. The can recovers
+//! from bus-off automatically.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Pin;
+use crate::mcal::gpio::Pupd;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::rcc::PCLK_HZ;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const CAN_MCR_INRQ: u32 = 1 << 0;
+const CAN_MCR_SLEEP: u32 = 1 << 1;
+const CAN_MCR_TXFP: u32 = 1 << 2;
+const CAN_MCR_ABOM: u32 = 1 << 6;
+
+const CAN_MSR_INAK: u32 = 1 << 0;
+
+const CAN_TSR_RQCP0: u32 = 1 << 0;
+const CAN_TSR_TXOK0: u32 = 1 << 1;
+const CAN_TSR_ALST0: u32 = 1 << 2;
+const CAN_TSR_TME0: u32 = 1 << 26;
+
+const CAN_RFR_FMP_MSK: u32 = 0x3;
+const CAN_RFR_FOVR: u32 = 1 << 4;
+const CAN_RFR_RFOM: u32 = 1 << 5;
+
+const CAN_ESR_EWGF: u32 = 1 << 0;
+const CAN_ESR_EPVF: u32 = 1 << 1;
+const CAN_ESR_BOFF: u32 = 1 << 2;
+
+const CAN_BTR_LBKM: u32 = 1 << 30;
+const CAN_BTR_SILM: u32 = 1 << 31;
+
+const CAN_TIR_TXRQ: u32 = 1 << 0;
+const CAN_IR_RTR: u32 = 1 << 1;
+const CAN_IR_IDE: u32 = 1 << 2;
+
+const CAN_FMR_FINIT: u32 = 1 << 0;
+
+const RCC_APB1ENR_CANEN: u32 = 1 << 25;
+
+///number of filter banks
+const CAN_FILTER_BANK_NUM: usize = 14;
+
+///number of polls of the mode acknowledge at a mode change
+const CAN_MODE_POLL_CNT: u32 = 10_000;
+
+///bit rate in bit/s
+const CAN_BITRATE: u32 = 500_000;
+///sample point in per mille of the bit time
+const CAN_SAMPLE_POINT_PERMILLE: u32 = 875;
+
+const CAN_MODE: CanMode = CanMode::Normal;
+
+const CAN_RX_PIN: Pin = Pin::A11;
+const CAN_TX_PIN: Pin = Pin::A12;
+const CAN_AF: Af = Af::AlternateFunc4;
+
+///BTR timing value (without mode bits)
+const CAN_BTR_TIMING: u32 = can_calc_btr(CAN_BITRATE, CAN_SAMPLE_POINT_PERMILLE);
+
+///filter configuration; a bank is used at most once
+const CAN_FILTER_CFG: [CanFilterCfg; 1] = [CanFilterCfg {
+    bank: 0,
+    filter: CanFilter::Mask32 {
+        id: can_filter32(CanId::Std(0), false),
+        mask: 0,
+    },
+    fifo: CanFifo::Fifo0,
+}];
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CanMode {
+    Normal,
+    ///transmitted frames are received internally, the tx pin is active
+    Loopback,
+    ///the can only listens, no dominant bits are sent
+    Silent,
+    ///loopback without bus access for self test
+    SilentLoopback,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CanId {
+    ///11 bit identifier
+    Std(u16),
+    ///29 bit identifier
+    Ext(u32),
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CanFifo {
+    Fifo0 = 0,
+    Fifo1 = 1,
+}
+
+///filter bank configuration; the values are built by can_filter32 / can_filter16
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CanFilter {
+    ///frames whose id matches id in all bits set in mask
+    Mask32 { id: u32, mask: u32 },
+    ///frames with one of the ids
+    List32 { id1: u32, id2: u32 },
+    ///two id / mask pairs
+    Mask16 { id1: u16, mask1: u16, id2: u16, mask2: u16 },
+    ///frames with one of the four ids
+    List16 { ids: [u16; 4] },
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CanError {
+    ModeChange,
+    NoMailbox,
+    Length,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CanTxStatus {
+    Pending,
+    Ok,
+    ArbitrationLost,
+    Error,
+}
+
+///last error code of ESR
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CanLastError {
+    NoError = 0,
+    Stuff = 1,
+    Form = 2,
+    Acknowledgment = 3,
+    BitRecessive = 4,
+    BitDominant = 5,
+    Crc = 6,
+    SetBySoftware = 7,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for a transmit mailbox or receive fifo
+#[repr(C)]
+pub struct CanMailbox {
+    pub ir: u32,
+    pub dtr: u32,
+    pub dlr: u32,
+    pub dhr: u32,
+}
+
+/// register structure for can module registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Can {
+    pub mcr: u32,
+    pub msr: u32,
+    pub tsr: u32,
+    pub rfr: [u32; 2],
+    pub ier: u32,
+    pub esr: u32,
+    pub btr: u32,
+    pub reserved0: [u32; 88],
+    pub tx: [CanMailbox; 3],
+    pub rx: [CanMailbox; 2],
+    pub reserved1: [u32; 12],
+    pub fmr: u32,
+    pub fm1r: u32,
+    pub reserved2: u32,
+    pub fs1r: u32,
+    pub reserved3: u32,
+    pub ffa1r: u32,
+    pub reserved4: u32,
+    pub fa1r: u32,
+    pub reserved5: [u32; 8],
+    pub fb: [[u32; 2]; 28],
+}
+
+impl Can {
+    /// method to the instance of Can
+    pub fn inst() -> &'static mut Can {
+        unsafe { &mut *(0x4000_6400 as *mut Can) }
+    }
+
+    ///request or leave the init mode; polls until the mode is acknowledged
+    pub fn set_init_mode(&mut self, init: bool) -> Result<(), CanError> {
+        if init {
+            set_reg_bitmsk(&mut self.mcr, CAN_MCR_INRQ);
+        } else {
+            clr_reg_bitmsk(&mut self.mcr, CAN_MCR_INRQ);
+        }
+        let mut cnt: u32 = 0;
+        while ((get_reg(&self.msr) & CAN_MSR_INAK) != 0) != init {
+            cnt += 1;
+            if cnt >= CAN_MODE_POLL_CNT {
+                return Err(CanError::ModeChange);
+            }
+        }
+        Ok(())
+    }
+
+    ///set the operating mode; the can must be in init mode
+    pub fn set_mode(&mut self, mode: CanMode) {
+        let bits: u32 = match mode {
+            CanMode::Normal => 0,
+            CanMode::Loopback => CAN_BTR_LBKM,
+            CanMode::Silent => CAN_BTR_SILM,
+            CanMode::SilentLoopback => CAN_BTR_LBKM | CAN_BTR_SILM,
+        };
+        set_reg(&mut self.btr, CAN_BTR_TIMING | bits);
+    }
+
+    ///write the frame to a free transmit mailbox and request the transmission; returns the mailbox number
+    #[allow(dead_code)]
+    pub fn transmit(&mut self, frame: &CanFrame) -> Result<usize, CanError> {
+        if frame.dlc > 8 {
+            return Err(CanError::Length);
+        }
+        let tsr: u32 = get_reg(&self.tsr);
+        let mut mb: usize = 0;
+        while mb < 3 && (tsr & (CAN_TSR_TME0 << mb)) == 0 {
+            mb += 1;
+        }
+        if mb == 3 {
+            return Err(CanError::NoMailbox);
+        }
+
+        let mut ir: u32 = match frame.id {
+            CanId::Std(id) => ((id as u32) & 0x7FF) << 21,
+            CanId::Ext(id) => ((id & 0x1FFF_FFFF) << 3) | CAN_IR_IDE,
+        };
+        if frame.rtr {
+            ir |= CAN_IR_RTR;
+        }
+        let d: &[u8; 8] = &frame.data;
+        let mailbox: &mut CanMailbox = &mut self.tx[mb];
+        set_reg(&mut mailbox.ir, ir);
+        set_reg(&mut mailbox.dtr, frame.dlc as u32);
+        set_reg(&mut mailbox.dlr, u32::from_le_bytes([d[0], d[1], d[2], d[3]]));
+        set_reg(&mut mailbox.dhr, u32::from_le_bytes([d[4], d[5], d[6], d[7]]));
+        set_reg_bitmsk(&mut mailbox.ir, CAN_TIR_TXRQ);
+        Ok(mb)
+    }
+
+    ///get the status of the transmission of the mailbox; the status is reported once when finished
+    #[allow(dead_code)]
+    pub fn get_tx_status(&mut self, mailbox: usize) -> CanTxStatus {
+        let tsr: u32 = get_reg(&self.tsr) >> (8 * mailbox);
+        if (tsr & CAN_TSR_RQCP0) == 0 {
+            return CanTxStatus::Pending;
+        }
+        //the status bits are cleared by writing RQCP
+        set_reg(&mut self.tsr, CAN_TSR_RQCP0 << (8 * mailbox));
+        if (tsr & CAN_TSR_TXOK0) != 0 {
+            CanTxStatus::Ok
+        } else if (tsr & CAN_TSR_ALST0) != 0 {
+            CanTxStatus::ArbitrationLost
+        } else {
+            //transmission error (TERR) or aborted request
+            CanTxStatus::Error
+        }
+    }
+
+    ///take a received frame from the fifo; returns the frame and the index of the matching filter
+    #[allow(dead_code)]
+    pub fn receive(&mut self, fifo: CanFifo) -> Option<(CanFrame, u8)> {
+        let idx: usize = fifo as usize;
+        if (get_reg(&self.rfr[idx]) & CAN_RFR_FMP_MSK) == 0 {
+            return None;
+        }
+        let mailbox: &CanMailbox = &self.rx[idx];
+        let ir: u32 = get_reg(&mailbox.ir);
+        let dtr: u32 = get_reg(&mailbox.dtr);
+        let dl: [u8; 4] = get_reg(&mailbox.dlr).to_le_bytes();
+        let dh: [u8; 4] = get_reg(&mailbox.dhr).to_le_bytes();
+        let frame: CanFrame = CanFrame {
+            id: if (ir & CAN_IR_IDE) != 0 {
+                CanId::Ext(ir >> 3)
+            } else {
+                CanId::Std((ir >> 21) as u16)
+            },
+            rtr: (ir & CAN_IR_RTR) != 0,
+            dlc: (dtr & 0xF) as u8,
+            data: [dl[0], dl[1], dl[2], dl[3], dh[0], dh[1], dh[2], dh[3]],
+        };
+        //release the fifo output mailbox
+        set_reg_bitmsk(&mut self.rfr[idx], CAN_RFR_RFOM);
+        Some((frame, (dtr >> 8) as u8))
+    }
+
+    ///check and clear the overrun flag of the fifo
+    #[allow(dead_code)]
+    pub fn check_overrun(&mut self, fifo: CanFifo) -> bool {
+        let idx: usize = fifo as usize;
+        if (get_reg(&self.rfr[idx]) & CAN_RFR_FOVR) != 0 {
+            set_reg(&mut self.rfr[idx], CAN_RFR_FOVR);
+            true
+        } else {
+            false
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn get_error_state(&self) -> CanErrorState {
+        let esr: u32 = get_reg(&self.esr);
+        CanErrorState {
+            tec: (esr >> 16) as u8,
+            rec: (esr >> 24) as u8,
+            last_error: match (esr >> 4) & 0x7 {
+                0 => CanLastError::NoError,
+                1 => CanLastError::Stuff,
+                2 => CanLastError::Form,
+                3 => CanLastError::Acknowledgment,
+                4 => CanLastError::BitRecessive,
+                5 => CanLastError::BitDominant,
+                6 => CanLastError::Crc,
+                _ => CanLastError::SetBySoftware,
+            },
+            warning: (esr & CAN_ESR_EWGF) != 0,
+            passive: (esr & CAN_ESR_EPVF) != 0,
+            bus_off: (esr & CAN_ESR_BOFF) != 0,
+        }
+    }
+
+    ///configure the filter bank; the filters must be in init mode
+    fn cfg_filter(&mut self, cfg: &CanFilterCfg) {
+        let bit: u32 = 1 << cfg.bank;
+        let (list, scale32, r1, r2): (bool, bool, u32, u32) = match cfg.filter {
+            CanFilter::Mask32 { id, mask } => (false, true, id, mask),
+            CanFilter::List32 { id1, id2 } => (true, true, id1, id2),
+            CanFilter::Mask16 { id1, mask1, id2, mask2 } => (
+                false,
+                false,
+                (id1 as u32) | ((mask1 as u32) << 16),
+                (id2 as u32) | ((mask2 as u32) << 16),
+            ),
+            CanFilter::List16 { ids } => (
+                true,
+                false,
+                (ids[0] as u32) | ((ids[1] as u32) << 16),
+                (ids[2] as u32) | ((ids[3] as u32) << 16),
+            ),
+        };
+        clr_reg_bitmsk(&mut self.fa1r, bit);
+        if list {
+            set_reg_bitmsk(&mut self.fm1r, bit);
+        } else {
+            clr_reg_bitmsk(&mut self.fm1r, bit);
+        }
+        if scale32 {
+            set_reg_bitmsk(&mut self.fs1r, bit);
+        } else {
+            clr_reg_bitmsk(&mut self.fs1r, bit);
+        }
+        if cfg.fifo == CanFifo::Fifo1 {
+            set_reg_bitmsk(&mut self.ffa1r, bit);
+        } else {
+            clr_reg_bitmsk(&mut self.ffa1r, bit);
+        }
+        set_reg(&mut self.fb[cfg.bank as usize][0], r1);
+        set_reg(&mut self.fb[cfg.bank as usize][1], r2);
+        set_reg_bitmsk(&mut self.fa1r, bit);
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct CanFilterCfg {
+    pub bank: u8,
+    pub filter: CanFilter,
+    pub fifo: CanFifo,
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub struct CanFrame {
+    pub id: CanId,
+    pub rtr: bool,
+    pub dlc: u8,
+    pub data: [u8; 8],
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub struct CanErrorState {
+    ///transmit error counter
+    pub tec: u8,
+    ///receive error counter
+    pub rec: u8,
+    pub last_error: CanLastError,
+    ///an error counter reached the warning limit 96
+    pub warning: bool,
+    ///an error counter exceeded 127
+    pub passive: bool,
+    ///the transmit error counter exceeded 255
+    pub bus_off: bool,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///calculate the BTR timing value; searches the highest number of time quanta per bit (8 - 25) for which the bit rate
+///is reached exactly with the can clock PCLK_HZ
+const fn can_calc_btr(bitrate: u32, sample_point_permille: u32) -> u32 {
+    let clk: u32 = PCLK_HZ;
+    let mut tq: u32 = 25;
+    while tq >= 8 {
+        if clk.is_multiple_of(bitrate * tq) {
+            let brp: u32 = clk / (bitrate * tq);
+            if brp >= 1 && brp <= 1024 {
+                //sync segment is one time quantum
+                let ts1: u32 = (tq * sample_point_permille + 500) / 1000 - 1;
+                let ts2: u32 = tq - 1 - ts1;
+                if ts1 >= 1 && ts1 <= 16 && ts2 >= 1 && ts2 <= 8 {
+                    let sjw: u32 = if ts2 < 4 { ts2 } else { 4 };
+                    return ((sjw - 1) << 24) | ((ts2 - 1) << 20) | ((ts1 - 1) << 16) | (brp - 1);
+                }
+            }
+        }
+        tq -= 1;
+    }
+    panic!("can bit rate not possible with PCLK_HZ");
+}
+
+///build the 32 bit filter value (id or mask) of the id
+pub const fn can_filter32(id: CanId, rtr: bool) -> u32 {
+    let rtr_bit: u32 = if rtr { CAN_IR_RTR } else { 0 };
+    match id {
+        CanId::Std(id) => (((id as u32) & 0x7FF) << 21) | rtr_bit,
+        CanId::Ext(id) => ((id & 0x1FFF_FFFF) << 3) | CAN_IR_IDE | rtr_bit,
+    }
+}
+
+///build the 16 bit filter value (id or mask) of the id; of an extended id only the upper 14 bits are used
+#[allow(dead_code)]
+pub const fn can_filter16(id: CanId, rtr: bool) -> u16 {
+    let rtr_bit: u16 = if rtr { 1 << 4 } else { 0 };
+    match id {
+        CanId::Std(id) => ((id & 0x7FF) << 5) | rtr_bit,
+        CanId::Ext(id) => {
+            ((((id >> 18) & 0x7FF) as u16) << 5) | rtr_bit | (1 << 3) | (((id >> 15) & 0x7) as u16)
+        }
+    }
+}
+
+///init the can with the configured bit timing, mode and filters and start the communication; the can takes part in
+///the communication after 11 recessive bits on the bus
+pub fn init() -> Result<(), CanError> {
+    set_reg_bitmsk(&mut Rcc::inst().apb1rstr, RCC_APB1ENR_CANEN);
+    clr_reg_bitmsk(&mut Rcc::inst().apb1rstr, RCC_APB1ENR_CANEN);
+    set_reg_bitmsk(&mut Rcc::inst().apb1enr, RCC_APB1ENR_CANEN);
+
+    CAN_RX_PIN.cfg_af(CAN_AF);
+    CAN_RX_PIN.cfg_pupd(Pupd::PullUp);
+    CAN_RX_PIN.cfg_mode(Mode::Alternate);
+    CAN_TX_PIN.cfg_af(CAN_AF);
+    CAN_TX_PIN.cfg_mode(Mode::Alternate);
+
+    let inst: &mut Can = Can::inst();
+    //leave sleep mode after reset; transmit in request order; automatic bus-off recovery
+    clr_reg_bitmsk(&mut inst.mcr, CAN_MCR_SLEEP);
+    inst.set_init_mode(true)?;
+    set_reg_bitmsk(&mut inst.mcr, CAN_MCR_TXFP | CAN_MCR_ABOM);
+    inst.set_mode(CAN_MODE);
+
+    set_reg_bitmsk(&mut inst.fmr, CAN_FMR_FINIT);
+    set_reg(&mut inst.fa1r, 0);
+    let mut idx: usize = 0;
+    while idx < CAN_FILTER_CFG.len() {
+        inst.cfg_filter(&CAN_FILTER_CFG[idx]);
+        idx += 1;
+    }
+    clr_reg_bitmsk(&mut inst.fmr, CAN_FMR_FINIT);
+
+    inst.set_init_mode(false)
+}
+
+//the filter banks must exist
+const _: () = {
+    let mut idx: usize = 0;
+    while idx < CAN_FILTER_CFG.len() {
+        assert!((CAN_FILTER_CFG[idx].bank as usize) < CAN_FILTER_BANK_NUM);
+        idx += 1;
+    }
+};
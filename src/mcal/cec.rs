@@ -0,0 +1,401 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : cec.rs
+//!
+//! # Short description
+//! Cec module implementation for the HDMI-CEC controller
+//!
+//! # Detailed description
+//! The CEC controller sends and receives frames on the single wire CEC bus; a frame is a header byte (initiator and
+//! destination logical address) followed by up to 15 data bytes (opcode and operands); a frame without data bytes
+//! is a polling message. The controller is clocked with HSI / 244 (32.8 kHz), so the HSI must be on; the pin is
+//! configured as alternate function open drain (the pull-up is on the bus).
+//!
+//! The own logical address is set by CEC_CFG and can be changed by cec_set_own_address; the controller acknowledges
+//! the frames addressed to it and receives the broadcast frames (destination 15).
+//!
+//! No interrupts are used, the flags are polled by cec_task, which must be called at least every few ms (a byte
+//! takes about 24 ms on the bus):
+//! - transmission: cec_send starts a frame; the task writes the next byte when the controller requests it; the job
+//!   state reports the result (missing acknowledge, arbitration lost, bit timing or underrun error); a lost
+//!   arbitration is not retried, the application can send the frame again
+//! - reception: the task collects the received bytes; a complete frame is kept until it is read by cec_receive
+//!   (a frame that is not read before the next one is complete is lost); a frame with a receive error is discarded
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Otype;
+use crate::mcal::gpio::Pin;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+use crate::rte::RteData;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const CEC_CR_CECEN: u32 = 1 << 0;
+const CEC_CR_TXSOM: u32 = 1 << 1;
+const CEC_CR_TXEOM: u32 = 1 << 2;
+
+const CEC_CFGR_OAR_POS: u32 = 16;
+
+const CEC_ISR_RXBR: u32 = 1 << 0;
+const CEC_ISR_RXEND: u32 = 1 << 1;
+const CEC_ISR_RXOVR: u32 = 1 << 2;
+const CEC_ISR_BRE: u32 = 1 << 3;
+const CEC_ISR_SBPE: u32 = 1 << 4;
+const CEC_ISR_LBPE: u32 = 1 << 5;
+const CEC_ISR_RXACKE: u32 = 1 << 6;
+const CEC_ISR_ARBLST: u32 = 1 << 7;
+const CEC_ISR_TXBR: u32 = 1 << 8;
+const CEC_ISR_TXEND: u32 = 1 << 9;
+const CEC_ISR_TXUDR: u32 = 1 << 10;
+const CEC_ISR_TXERR: u32 = 1 << 11;
+const CEC_ISR_TXACKE: u32 = 1 << 12;
+const CEC_ISR_RX_ERR_MSK: u32 = CEC_ISR_RXOVR | CEC_ISR_BRE | CEC_ISR_SBPE | CEC_ISR_LBPE | CEC_ISR_RXACKE;
+const CEC_ISR_ALL: u32 = 0x1FFF;
+
+const RCC_APB1ENR_CECEN: u32 = 1 << 30;
+
+///broadcast logical address
+pub const CEC_BROADCAST_ADDR: u8 = 0xF;
+///maximum number of data bytes (opcode and operands) of a frame
+pub const CEC_FRAME_DATA_MAX: usize = 15;
+
+///controller configuration
+const CEC_CFG: CecCfg = CecCfg {
+    own_addr: 4,
+    pin: Pin::B10,
+    af: Af::AlternateFunc0,
+};
+
+const _: () = assert!(CEC_CFG.own_addr < CEC_BROADCAST_ADDR);
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CecError {
+    Busy,
+    Length,
+    Address,
+    ///missing acknowledge of the destination
+    Nack,
+    ArbitrationLost,
+    ///bit timing error on the bus (transmission or reception)
+    BitTiming,
+    ///the next byte was not written in time
+    Underrun,
+    ///a received byte was not read in time
+    Overrun,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CecJobState {
+    Idle,
+    Send,
+    Done,
+    Error(CecError),
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct CecCfg {
+    ///own logical address
+    own_addr: u8,
+    pin: Pin,
+    af: Af,
+}
+
+///CEC frame without the header byte
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub struct CecFrame {
+    pub initiator: u8,
+    pub destination: u8,
+    ///number of data bytes; 0 for a polling message
+    pub len: usize,
+    pub data: [u8; CEC_FRAME_DATA_MAX],
+}
+
+impl CecFrame {
+    ///get the data bytes of the frame
+    #[allow(dead_code)]
+    pub fn get_data(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct CecData {
+    own_addr: u8,
+    tx_state: CecJobState,
+    ///header and data bytes of the sent frame
+    tx_buf: [u8; CEC_FRAME_DATA_MAX + 1],
+    tx_len: usize,
+    tx_idx: usize,
+    rx_buf: [u8; CEC_FRAME_DATA_MAX + 1],
+    rx_idx: usize,
+    rx_frame: Option<CecFrame>,
+    rx_lost_cnt: u16,
+    rx_err_cnt: u16,
+    rx_last_error: Option<CecError>,
+}
+
+impl CecData {
+    pub const fn init() -> Self {
+        Self {
+            own_addr: CEC_CFG.own_addr,
+            tx_state: CecJobState::Idle,
+            tx_buf: [0; CEC_FRAME_DATA_MAX + 1],
+            tx_len: 0,
+            tx_idx: 0,
+            rx_buf: [0; CEC_FRAME_DATA_MAX + 1],
+            rx_idx: 0,
+            rx_frame: None,
+            rx_lost_cnt: 0,
+            rx_err_cnt: 0,
+            rx_last_error: None,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn get_job_state(&self) -> CecJobState {
+        self.tx_state
+    }
+
+    #[allow(dead_code)]
+    pub fn get_own_address(&self) -> u8 {
+        self.own_addr
+    }
+
+    ///get the number of received frames that were lost because the previous frame was not read and the number of
+    ///discarded frames with receive error
+    #[allow(dead_code)]
+    pub fn get_rx_error_cnt(&self) -> (u16, u16) {
+        (self.rx_lost_cnt, self.rx_err_cnt)
+    }
+
+    #[allow(dead_code)]
+    pub fn get_rx_last_error(&self) -> Option<CecError> {
+        self.rx_last_error
+    }
+
+    ///handle the transmission flags
+    fn handle_tx(&mut self, inst: &mut Cec, isr: u32) {
+        if self.tx_state != CecJobState::Send {
+            return;
+        }
+        let error: Option<CecError> = if (isr & CEC_ISR_ARBLST) != 0 {
+            Some(CecError::ArbitrationLost)
+        } else if (isr & CEC_ISR_TXACKE) != 0 {
+            Some(CecError::Nack)
+        } else if (isr & CEC_ISR_TXUDR) != 0 {
+            Some(CecError::Underrun)
+        } else if (isr & CEC_ISR_TXERR) != 0 {
+            Some(CecError::BitTiming)
+        } else {
+            None
+        };
+        if let Some(e) = error {
+            self.tx_state = CecJobState::Error(e);
+        } else if (isr & CEC_ISR_TXEND) != 0 {
+            self.tx_state = CecJobState::Done;
+        } else if (isr & CEC_ISR_TXBR) != 0 && self.tx_idx < self.tx_len {
+            set_reg(&mut inst.isr, CEC_ISR_TXBR);
+            //the end of message must be set before the last byte is written
+            if self.tx_idx == self.tx_len - 1 {
+                set_reg_bitmsk(&mut inst.cr, CEC_CR_TXEOM);
+            }
+            set_reg(&mut inst.txdr, self.tx_buf[self.tx_idx] as u32);
+            self.tx_idx += 1;
+        }
+    }
+
+    ///handle the reception flags
+    fn handle_rx(&mut self, inst: &mut Cec, isr: u32) {
+        if (isr & CEC_ISR_RXBR) != 0 {
+            let byte: u8 = get_reg(&inst.rxdr) as u8;
+            //the flag is not cleared by reading the data register, but by writing 1
+            set_reg(&mut inst.isr, CEC_ISR_RXBR);
+            if self.rx_idx < self.rx_buf.len() {
+                self.rx_buf[self.rx_idx] = byte;
+                self.rx_idx += 1;
+            }
+        }
+        if (isr & CEC_ISR_RX_ERR_MSK) != 0 {
+            self.rx_last_error = Some(if (isr & CEC_ISR_RXOVR) != 0 {
+                CecError::Overrun
+            } else if (isr & CEC_ISR_RXACKE) != 0 {
+                CecError::Nack
+            } else {
+                CecError::BitTiming
+            });
+            self.rx_err_cnt = self.rx_err_cnt.saturating_add(1);
+            self.rx_idx = 0;
+        } else if (isr & CEC_ISR_RXEND) != 0 {
+            if self.rx_idx > 0 {
+                if self.rx_frame.is_some() {
+                    self.rx_lost_cnt = self.rx_lost_cnt.saturating_add(1);
+                }
+                let mut frame: CecFrame = CecFrame {
+                    initiator: self.rx_buf[0] >> 4,
+                    destination: self.rx_buf[0] & 0xF,
+                    len: self.rx_idx - 1,
+                    data: [0; CEC_FRAME_DATA_MAX],
+                };
+                frame.data[..frame.len].copy_from_slice(&self.rx_buf[1..self.rx_idx]);
+                self.rx_frame = Some(frame);
+            }
+            self.rx_idx = 0;
+        }
+    }
+}
+
+/// register structure for cec controller registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Cec {
+    pub cr: u32,
+    pub cfgr: u32,
+    pub txdr: u32,
+    pub rxdr: u32,
+    pub isr: u32,
+    pub ier: u32,
+}
+
+impl Cec {
+    /// method to the instance of Cec
+    pub fn inst() -> &'static mut Cec {
+        unsafe { &mut *(0x4000_7800 as *mut Cec) }
+    }
+
+    ///write the own address; the configuration can only be written while the controller is disabled
+    fn cfg_own_address(&mut self, addr: u8) {
+        clr_reg_bitmsk(&mut self.cr, CEC_CR_CECEN);
+        set_reg(&mut self.cfgr, (1 << addr) << CEC_CFGR_OAR_POS);
+        set_reg(&mut self.isr, CEC_ISR_ALL);
+        set_reg_bitmsk(&mut self.cr, CEC_CR_CECEN);
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///init the cec controller with the own address of the configuration
+pub fn init() {
+    set_reg_bitmsk(&mut Rcc::inst().apb1enr, RCC_APB1ENR_CECEN);
+    CEC_CFG.pin.cfg_otype(Otype::OpenDrain);
+    CEC_CFG.pin.cfg_af(CEC_CFG.af);
+    CEC_CFG.pin.cfg_mode(Mode::Alternate);
+    Cec::inst().cfg_own_address(CEC_CFG.own_addr);
+}
+
+///change the own logical address; fails while a frame is sent
+#[allow(dead_code)]
+pub fn cec_set_own_address(addr: u8, rt_data: &mut RteData) -> Result<(), CecError> {
+    let data: &mut CecData = &mut rt_data.cec_data;
+    if data.tx_state == CecJobState::Send {
+        return Err(CecError::Busy);
+    }
+    if addr >= CEC_BROADCAST_ADDR {
+        return Err(CecError::Address);
+    }
+    Cec::inst().cfg_own_address(addr);
+    data.own_addr = addr;
+    data.rx_idx = 0;
+    Ok(())
+}
+
+///start sending a frame with the data bytes (opcode and operands) to the destination; no data bytes send a polling
+///message; fails while a frame is sent
+#[allow(dead_code)]
+pub fn cec_send(destination: u8, data: &[u8], rt_data: &mut RteData) -> Result<(), CecError> {
+    let cec: &mut CecData = &mut rt_data.cec_data;
+    if cec.tx_state == CecJobState::Send {
+        return Err(CecError::Busy);
+    }
+    if data.len() > CEC_FRAME_DATA_MAX {
+        return Err(CecError::Length);
+    }
+    if destination > CEC_BROADCAST_ADDR {
+        return Err(CecError::Address);
+    }
+    cec.tx_buf[0] = (cec.own_addr << 4) | destination;
+    cec.tx_buf[1..=data.len()].copy_from_slice(data);
+    cec.tx_len = data.len() + 1;
+    cec.tx_idx = 1;
+    cec.tx_state = CecJobState::Send;
+
+    let inst: &mut Cec = Cec::inst();
+    set_reg(
+        &mut inst.isr,
+        CEC_ISR_TXBR | CEC_ISR_TXEND | CEC_ISR_TXUDR | CEC_ISR_TXERR | CEC_ISR_TXACKE | CEC_ISR_ARBLST,
+    );
+    set_reg(&mut inst.txdr, cec.tx_buf[0] as u32);
+    if data.is_empty() {
+        set_reg_bitmsk(&mut inst.cr, CEC_CR_TXSOM | CEC_CR_TXEOM);
+    } else {
+        set_reg_bitmsk(&mut inst.cr, CEC_CR_TXSOM);
+    }
+    Ok(())
+}
+
+///get the received frame; the frame is removed
+#[allow(dead_code)]
+pub fn cec_receive(rt_data: &mut RteData) -> Option<CecFrame> {
+    rt_data.cec_data.rx_frame.take()
+}
+
+///cec task; handles the transmission and reception flags
+pub fn cec_task(rt_data: &mut RteData) {
+    let inst: &mut Cec = Cec::inst();
+    let isr: u32 = get_reg(&inst.isr);
+    let data: &mut CecData = &mut rt_data.cec_data;
+    data.handle_tx(inst, isr);
+    data.handle_rx(inst, isr);
+    //the data flags are cleared by the handlers together with the data register access; the status flags are cleared
+    //by writing 1
+    set_reg(&mut inst.isr, isr & !(CEC_ISR_RXBR | CEC_ISR_TXBR));
+}
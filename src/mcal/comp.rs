@@ -0,0 +1,294 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : comp.rs
+//!
+//! # Short description
+//! Comp module implementation for the two analog comparators COMP1 / COMP2
+//!
+//! # Detailed description
+//! The comparators are configured by the COMP_CFG table (index is the CompId); a comparator without configuration
+//! is not used. Every comparator has:
+//! - non-inverting input: the Io pin (PA1 for COMP1, PA3 for COMP2), the dac channel 1 output (COMP1 only) or the
+//!   non-inverting input of COMP1 (COMP2 only, window mode with a common input)
+//! - inverting input: 1/4, 1/2, 3/4 or 1/1 of the internal reference voltage VREFINT, a dac channel output or the Io
+//!   pin (PA0 for COMP1, PA2 for COMP2)
+//! - hysteresis, power mode (speed vs. current) and output polarity
+//! - timer connection: the output is routed to a timer input (break, input capture or OCREF clear); the timer
+//!   channel must not be used otherwise (e.g. by the icu)
+//! - an optional output pin with its alternate function
+//!
+//! The output level is polled with comp_get_output; no interrupts are used (the EXTI lines of the comparators can
+//! wake the Mcu from Stop, see pwr). comp_lock makes the configuration read only until the next reset, e.g. for a
+//! comparator that triggers the break of a power stage.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Pin;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::modify_reg_bits;
+use crate::mcal::util::set_reg_bitmsk;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const COMP_CSR_EN: u32 = 1 << 0;
+const COMP_CSR_COMP1_INP_DAC: u32 = 1 << 1;
+const COMP_CSR_MODE_POS: u32 = 2;
+const COMP_CSR_INSEL_POS: u32 = 4;
+const COMP_CSR_WNDWEN: u32 = 1 << 23;
+const COMP_CSR_OUTSEL_POS: u32 = 8;
+const COMP_CSR_POL: u32 = 1 << 11;
+const COMP_CSR_HYST_POS: u32 = 12;
+const COMP_CSR_OUT: u32 = 1 << 14;
+const COMP_CSR_LOCK: u32 = 1 << 15;
+///bits of one comparator in CSR
+const COMP_CSR_CH_MSK: u32 = 0xFFFF;
+///bit offset of the COMP2 bits in CSR
+const COMP_CSR_COMP2_POS: u32 = 16;
+
+const RCC_APB2ENR_SYSCFGCOMPEN: u32 = 1 << 0;
+
+///number of comparators
+const COMP_NUM: usize = 2;
+
+///comparator configuration; index is the CompId
+const COMP_CFG: [Option<CompCfg>; COMP_NUM] = [
+    Some(CompCfg {
+        inp: CompInp::Io,
+        inm: CompInm::HalfVref,
+        hysteresis: CompHyst::Low,
+        speed: CompSpeed::Medium,
+        inverted: false,
+        output: CompOutput::None,
+        pin: None,
+    }),
+    None,
+];
+
+const _: () = comp_check_cfg(&COMP_CFG);
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CompId {
+    Comp1 = 0,
+    Comp2 = 1,
+}
+
+impl CompId {
+    ///get the pins of the non-inverting and the inverting Io input
+    const fn pins(self) -> (Pin, Pin) {
+        match self {
+            CompId::Comp1 => (Pin::A1, Pin::A0),
+            CompId::Comp2 => (Pin::A3, Pin::A2),
+        }
+    }
+}
+
+///non-inverting input
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CompInp {
+    Io,
+    ///dac channel 1 output; COMP1 only
+    Dac1,
+    ///non-inverting input of COMP1; COMP2 only
+    Comp1Inp,
+}
+
+///inverting input; the values are the INSEL values
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CompInm {
+    QuarterVref = 0,
+    HalfVref = 1,
+    ThreeQuarterVref = 2,
+    Vref = 3,
+    Dac1 = 4,
+    Dac2 = 5,
+    Io = 6,
+}
+
+///hysteresis; the values are the HYST values
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CompHyst {
+    None = 0,
+    Low = 1,
+    Medium = 2,
+    High = 3,
+}
+
+///power mode; a lower speed has a lower current consumption; the values are the MODE values
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CompSpeed {
+    High = 0,
+    Medium = 1,
+    Low = 2,
+    VeryLow = 3,
+}
+
+///timer connection of the output; the values are the OUTSEL values
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CompOutput {
+    None = 0,
+    Tim1Break = 1,
+    Tim1Ic1 = 2,
+    Tim1OcrefClr = 3,
+    Tim2Ic4 = 4,
+    Tim2OcrefClr = 5,
+    Tim3Ic1 = 6,
+    Tim3OcrefClr = 7,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum CompError {
+    NotConfigured,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct CompCfg {
+    inp: CompInp,
+    inm: CompInm,
+    hysteresis: CompHyst,
+    speed: CompSpeed,
+    ///inverted output polarity
+    inverted: bool,
+    output: CompOutput,
+    ///output pin with its alternate function
+    pin: Option<(Pin, Af)>,
+}
+
+impl CompCfg {
+    ///get the bits of CSR at the COMP1 position without the input selection
+    const fn csr(&self) -> u32 {
+        let mut csr: u32 = COMP_CSR_EN
+            | ((self.speed as u32) << COMP_CSR_MODE_POS)
+            | ((self.inm as u32) << COMP_CSR_INSEL_POS)
+            | ((self.output as u32) << COMP_CSR_OUTSEL_POS)
+            | ((self.hysteresis as u32) << COMP_CSR_HYST_POS);
+        if self.inverted {
+            csr |= COMP_CSR_POL;
+        }
+        csr
+    }
+}
+
+/// register structure for the comparator control and status register of the system configuration; need C
+/// representation for correct memory layout
+#[repr(C)]
+pub struct Comp {
+    pub csr: u32,
+}
+
+impl Comp {
+    /// method to the instance of Comp
+    pub fn inst() -> &'static mut Comp {
+        unsafe { &mut *(0x4001_001C as *mut Comp) }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///check the comparator configuration at compile time
+const fn comp_check_cfg(cfg: &[Option<CompCfg>; COMP_NUM]) {
+    if let Some(comp1) = cfg[CompId::Comp1 as usize] {
+        assert!(!matches!(comp1.inp, CompInp::Comp1Inp));
+    }
+    if let Some(comp2) = cfg[CompId::Comp2 as usize] {
+        assert!(!matches!(comp2.inp, CompInp::Dac1));
+        //the common input is the input of COMP1
+        assert!(!matches!(comp2.inp, CompInp::Comp1Inp) || cfg[CompId::Comp1 as usize].is_some());
+    }
+}
+
+///get the bit offset of the comparator in CSR
+const fn comp_csr_pos(id: CompId) -> u32 {
+    COMP_CSR_COMP2_POS * id as u32
+}
+
+///init the configured comparators with their pins and enable them
+pub fn init() {
+    set_reg_bitmsk(&mut Rcc::inst().apb2enr, RCC_APB2ENR_SYSCFGCOMPEN);
+
+    let ids: [CompId; COMP_NUM] = [CompId::Comp1, CompId::Comp2];
+    for id in ids {
+        if let Some(cfg) = COMP_CFG[id as usize] {
+            let (inp_pin, inm_pin) = id.pins();
+            let mut csr: u32 = cfg.csr() << comp_csr_pos(id);
+            match cfg.inp {
+                CompInp::Io => inp_pin.cfg_mode(Mode::Analog),
+                CompInp::Dac1 => csr |= COMP_CSR_COMP1_INP_DAC,
+                CompInp::Comp1Inp => csr |= COMP_CSR_WNDWEN,
+            }
+            if cfg.inm == CompInm::Io {
+                inm_pin.cfg_mode(Mode::Analog);
+            }
+            if let Some((pin, af)) = cfg.pin {
+                pin.cfg_af(af);
+                pin.cfg_mode(Mode::Alternate);
+            }
+            modify_reg_bits(&mut Comp::inst().csr, COMP_CSR_CH_MSK << comp_csr_pos(id), csr);
+        }
+    }
+}
+
+///get the output level of the comparator (after the polarity)
+#[allow(dead_code)]
+pub fn comp_get_output(id: CompId) -> Result<bool, CompError> {
+    COMP_CFG[id as usize].ok_or(CompError::NotConfigured)?;
+    Ok((get_reg(&Comp::inst().csr) & (COMP_CSR_OUT << comp_csr_pos(id))) != 0)
+}
+
+///lock the configuration of the comparator until the next reset
+#[allow(dead_code)]
+pub fn comp_lock(id: CompId) -> Result<(), CompError> {
+    COMP_CFG[id as usize].ok_or(CompError::NotConfigured)?;
+    set_reg_bitmsk(&mut Comp::inst().csr, COMP_CSR_LOCK << comp_csr_pos(id));
+    Ok(())
+}
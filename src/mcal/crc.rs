@@ -0,0 +1,299 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : crc.rs
+//!
+//! # Short description
+//! Crc module implementation for the Mcu crc calculation unit
+//!
+//! # Detailed description
+//! The crc unit calculates a crc with a polynomial of 7, 8, 16 or 32 bit, an initial value and an optional bit
+//! reversal of the input bytes and the output. The configuration is the CrcCfg of common::crc, so the software
+//! calculation common::crc::crc_calc gives the same result for every configuration and can be used if the crc unit
+//! is not available (e.g. in a context that may interrupt a running calculation).
+//!
+//! The data is written byte by byte into the data register, so the input reversal is done by byte. A calculation is
+//! done at once with crc_calc or in parts with crc_start, crc_update and crc_get; the unit is not shared, so only
+//! one calculation may run at a time.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::common::crc::CrcCfg;
+use crate::common::crc::CrcWidth;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+use core::ptr;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const CRC_CR_RESET: u32 = 1 << 0;
+const CRC_CR_POLYSIZE_POS: u32 = 3;
+const CRC_CR_REV_IN_BYTE: u32 = 1 << 5;
+const CRC_CR_REV_OUT: u32 = 1 << 7;
+
+const RCC_AHBENR_CRCEN: u32 = 1 << 6;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for crc calculation unit registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Crc {
+    pub dr: u32,
+    pub idr: u32,
+    pub cr: u32,
+    pub reserved0: u32,
+    pub init: u32,
+    pub pol: u32,
+}
+
+impl Crc {
+    /// method to the instance of Crc
+    pub fn inst() -> &'static mut Crc {
+        unsafe { &mut *(0x4002_3000 as *mut Crc) }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///get the POLYSIZE field value of the width
+const fn crc_polysize(width: CrcWidth) -> u32 {
+    match width {
+        CrcWidth::Bits32 => 0,
+        CrcWidth::Bits16 => 1,
+        CrcWidth::Bits8 => 2,
+        CrcWidth::Bits7 => 3,
+    }
+}
+
+///get the control register value of the configuration
+const fn crc_cr_value(cfg: &CrcCfg) -> u32 {
+    let mut cr: u32 = crc_polysize(cfg.width) << CRC_CR_POLYSIZE_POS;
+    if cfg.reflect_in {
+        cr |= CRC_CR_REV_IN_BYTE;
+    }
+    if cfg.reflect_out {
+        cr |= CRC_CR_REV_OUT;
+    }
+    cr
+}
+
+///enable the clock of the crc unit
+pub fn init() {
+    set_reg_bitmsk(&mut Rcc::inst().ahbenr, RCC_AHBENR_CRCEN);
+}
+
+///configure the crc unit and start a new calculation with the initial value
+#[allow(dead_code)]
+pub fn crc_start(cfg: &CrcCfg) {
+    let inst: &mut Crc = Crc::inst();
+    set_reg(&mut inst.cr, crc_cr_value(cfg));
+    set_reg(&mut inst.pol, cfg.poly & cfg.width.mask());
+    set_reg(&mut inst.init, cfg.init & cfg.width.mask());
+    //the reset loads the initial value
+    set_reg_bitmsk(&mut inst.cr, CRC_CR_RESET);
+}
+
+///continue the started calculation with the given data
+#[allow(dead_code)]
+pub fn crc_update(data: &[u8]) {
+    let dr: *mut u8 = &mut Crc::inst().dr as *mut u32 as *mut u8;
+    for byte in data.iter() {
+        unsafe {
+            ptr::write_volatile(dr, *byte);
+        }
+    }
+}
+
+///get the result of the started calculation; the calculation may be continued afterwards
+#[allow(dead_code)]
+pub fn crc_get(cfg: &CrcCfg) -> u32 {
+    get_reg(&Crc::inst().dr) & cfg.width.mask()
+}
+
+///calculate the crc of the data
+#[allow(dead_code)]
+pub fn crc_calc(cfg: &CrcCfg, data: &[u8]) -> u32 {
+    crc_start(cfg);
+    crc_update(data);
+    crc_get(cfg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::crc;
+    use crate::common::crc::CRC32_CFG;
+
+    const CHECK_DATA: &[u8] = b"123456789";
+    const CRC32_POLY_RESET: u32 = 0x04C1_1DB7;
+
+    ///register level model of the crc unit; the crc register is kept left aligned in 32 bits, as the unit computes
+    ///the smaller polynomial sizes in the upper bits of its 32 bit register
+    struct CrcUnitModel {
+        cr: u32,
+        pol: u32,
+        init: u32,
+        crc: u32,
+    }
+
+    impl CrcUnitModel {
+        fn new() -> Self {
+            //reset values of the registers
+            CrcUnitModel { cr: 0, pol: CRC32_POLY_RESET, init: 0xFFFF_FFFF, crc: 0xFFFF_FFFF }
+        }
+
+        fn width(&self) -> u32 {
+            match (self.cr >> CRC_CR_POLYSIZE_POS) & 0x3 {
+                0 => 32,
+                1 => 16,
+                2 => 8,
+                _ => 7,
+            }
+        }
+
+        fn write_cr(&mut self, cr: u32) {
+            self.cr = cr & !CRC_CR_RESET;
+            if (cr & CRC_CR_RESET) != 0 {
+                self.crc = self.init << (32 - self.width());
+            }
+        }
+
+        fn write_dr8(&mut self, byte: u8) {
+            let byte: u8 = if (self.cr & CRC_CR_REV_IN_BYTE) != 0 { byte.reverse_bits() } else { byte };
+            let pol: u32 = self.pol << (32 - self.width());
+            self.crc ^= (byte as u32) << 24;
+            for _ in 0..8 {
+                self.crc = if (self.crc & 0x8000_0000) != 0 { (self.crc << 1) ^ pol } else { self.crc << 1 };
+            }
+        }
+
+        fn read_dr(&self) -> u32 {
+            let crc: u32 = self.crc >> (32 - self.width());
+            if (self.cr & CRC_CR_REV_OUT) != 0 {
+                crc.reverse_bits() >> (32 - self.width())
+            } else {
+                crc
+            }
+        }
+    }
+
+    ///run the calculation on the model with the register values of crc_start
+    fn model_calc(cfg: &CrcCfg, data: &[u8]) -> u32 {
+        let mut unit: CrcUnitModel = CrcUnitModel::new();
+        unit.write_cr(crc_cr_value(cfg));
+        unit.pol = cfg.poly & cfg.width.mask();
+        unit.init = cfg.init & cfg.width.mask();
+        unit.write_cr(unit.cr | CRC_CR_RESET);
+        for byte in data.iter() {
+            unit.write_dr8(*byte);
+        }
+        unit.read_dr() & cfg.width.mask()
+    }
+
+    fn cfg(width: CrcWidth, poly: u32, init: u32, reflect_in: bool, reflect_out: bool) -> CrcCfg {
+        CrcCfg { width, poly, init, reflect_in, reflect_out }
+    }
+
+    #[test]
+    fn check_values_crc32() {
+        //CRC-32/MPEG-2 is the reset configuration of the unit
+        assert_eq!(model_calc(&CRC32_CFG, CHECK_DATA), 0x0376_E6E7);
+        assert_eq!(crc::crc_calc(&CRC32_CFG, CHECK_DATA), 0x0376_E6E7);
+        assert_eq!(crc::crc32(CHECK_DATA), 0x0376_E6E7);
+        //CRC-32 (ISO-HDLC) is the reflected variant with a final xor done by the caller
+        let iso_hdlc: CrcCfg = cfg(CrcWidth::Bits32, 0x04C1_1DB7, 0xFFFF_FFFF, true, true);
+        assert_eq!(model_calc(&iso_hdlc, CHECK_DATA) ^ 0xFFFF_FFFF, 0xCBF4_3926);
+        assert_eq!(crc::crc_calc(&iso_hdlc, CHECK_DATA) ^ 0xFFFF_FFFF, 0xCBF4_3926);
+    }
+
+    #[test]
+    fn check_values_small_widths() {
+        //CRC-16/CCITT-FALSE, CRC-16/ARC, CRC-8/SMBUS, CRC-8/MAXIM-DOW, CRC-7/MMC, CRC-7/ROHC
+        let vectors: [(CrcCfg, u32); 6] = [
+            (cfg(CrcWidth::Bits16, 0x1021, 0xFFFF, false, false), 0x29B1),
+            (cfg(CrcWidth::Bits16, 0x8005, 0x0000, true, true), 0xBB3D),
+            (cfg(CrcWidth::Bits8, 0x07, 0x00, false, false), 0xF4),
+            (cfg(CrcWidth::Bits8, 0x31, 0x00, true, true), 0xA1),
+            (cfg(CrcWidth::Bits7, 0x09, 0x00, false, false), 0x75),
+            (cfg(CrcWidth::Bits7, 0x4F, 0x7F, true, true), 0x53),
+        ];
+        for (cfg, check) in vectors.iter() {
+            assert_eq!(model_calc(cfg, CHECK_DATA), *check);
+            assert_eq!(crc::crc_calc(cfg, CHECK_DATA), *check);
+        }
+    }
+
+    #[test]
+    fn model_matches_software_for_all_settings() {
+        let widths: [(CrcWidth, u32); 4] = [
+            (CrcWidth::Bits7, 0x09),
+            (CrcWidth::Bits8, 0x07),
+            (CrcWidth::Bits16, 0x1021),
+            (CrcWidth::Bits32, 0x04C1_1DB7),
+        ];
+        let data: [u8; 64] = core::array::from_fn(|i| (i as u8).wrapping_mul(37).wrapping_add(11));
+        for (width, poly) in widths.iter() {
+            for init in [0, 0xFFFF_FFFF, 0x1234_5678] {
+                for reflect_in in [false, true] {
+                    for reflect_out in [false, true] {
+                        let cfg: CrcCfg = cfg(*width, *poly, init, reflect_in, reflect_out);
+                        for len in [0, 1, 9, 64] {
+                            assert_eq!(model_calc(&cfg, &data[..len]), crc::crc_calc(&cfg, &data[..len]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn software_update_continues_calculation() {
+        let cfg: CrcCfg = cfg(CrcWidth::Bits16, 0x1021, 0xFFFF, true, true);
+        let crc_reg: u32 = crc::crc_update(&cfg, crc::crc_start(&cfg), &CHECK_DATA[..4]);
+        let crc_reg: u32 = crc::crc_update(&cfg, crc_reg, &CHECK_DATA[4..]);
+        assert_eq!(crc::crc_final(&cfg, crc_reg), crc::crc_calc(&cfg, CHECK_DATA));
+        assert_eq!(crc::crc32_update(crc::crc32(&CHECK_DATA[..4]), &CHECK_DATA[4..]), crc::crc32(CHECK_DATA));
+    }
+}
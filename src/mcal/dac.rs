@@ -0,0 +1,323 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : dac.rs
+//!
+//! # Short description
+//! Dac module implementation for the two 12 bit digital to analog converter channels
+//!
+//! # Detailed description
+//! The channels are configured by the DAC_CFG table (index is the DacChannel); a channel without configuration is
+//! not used and its pin is free (PA5 of channel 2 is the led). Every channel has:
+//! - trigger: None transfers a written value to the output at once; Software transfers it by dac_trigger; Tim6Trgo
+//!   transfers it by the trigger output of TIM6. If TIM6 is the time base of the scheduler, the scheduler restarts
+//!   the counter by an update generation at the begin of every timeslot, so the trigger occurs once per timeslot.
+//!   Otherwise TIM6 is configured by init with the trigger frequency DAC_TIM6_TRIGGER_HZ
+//! - wave: Noise adds a pseudo random value of the given number of bits, Triangle adds a triangle with the amplitude
+//!   2^bits - 1 to the written value; the wave steps with every trigger, so a trigger is needed
+//! - buffer: output buffer to drive a low impedance load; without buffer the output reaches 0 V and VDDA
+//!
+//! Both channels can be written and triggered at once with dac_set_dual / dac_trigger_dual.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpt::Timer6_7;
+use crate::mcal::gpt::TIMER_CLK_HZ;
+use crate::mcal::gpio::Pin;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const DAC_CR_EN: u32 = 1 << 0;
+const DAC_CR_BOFF: u32 = 1 << 1;
+const DAC_CR_TEN: u32 = 1 << 2;
+const DAC_CR_TSEL_POS: u32 = 3;
+const DAC_CR_WAVE_NOISE: u32 = 1 << 6;
+const DAC_CR_WAVE_TRIANGLE: u32 = 2 << 6;
+const DAC_CR_MAMP_POS: u32 = 8;
+///bit offset of the channel 2 bits in CR
+const DAC_CR_CH2_POS: u32 = 16;
+
+const DAC_TSEL_TIM6_TRGO: u32 = 0;
+const DAC_TSEL_SWTRIG: u32 = 7;
+
+const RCC_APB1ENR_DACEN: u32 = 1 << 29;
+
+///maximum output value
+pub const DAC_MAX_VALUE: u16 = 4095;
+
+///number of channels
+const DAC_CHANNEL_NUM: usize = 2;
+
+///channel configuration; index is the DacChannel
+const DAC_CFG: [Option<DacCfg>; DAC_CHANNEL_NUM] = [
+    Some(DacCfg {
+        trigger: DacTrigger::None,
+        wave: DacWave::None,
+        buffer: true,
+    }),
+    None,
+];
+
+///trigger frequency of TIM6; None if TIM6 is the time base of the scheduler (checked by the scheduler)
+pub const DAC_TIM6_TRIGGER_HZ: Option<u32> = None;
+///prescaler and auto reload value of TIM6 for the trigger frequency
+const DAC_TIM6_PSC_ARR: (u16, u16) = match DAC_TIM6_TRIGGER_HZ {
+    Some(freq_hz) => dac_calc_tim6(freq_hz),
+    None => (0, 0),
+};
+
+const _: () = dac_check_cfg(&DAC_CFG);
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DacChannel {
+    Ch1 = 0,
+    Ch2 = 1,
+}
+
+impl DacChannel {
+    const fn pin(self) -> Pin {
+        match self {
+            DacChannel::Ch1 => Pin::A4,
+            DacChannel::Ch2 => Pin::A5,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DacTrigger {
+    None,
+    Software,
+    Tim6Trgo,
+}
+
+///wave generation; bits is the number of bits of the wave (1..=12)
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DacWave {
+    None,
+    Noise { bits: u8 },
+    Triangle { bits: u8 },
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DacError {
+    NotConfigured,
+    Value,
+    Trigger,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct DacCfg {
+    trigger: DacTrigger,
+    wave: DacWave,
+    buffer: bool,
+}
+
+impl DacCfg {
+    ///get the channel 1 bits of CR
+    const fn cr(&self) -> u32 {
+        let mut cr: u32 = DAC_CR_EN;
+        if !self.buffer {
+            cr |= DAC_CR_BOFF;
+        }
+        match self.trigger {
+            DacTrigger::None => {}
+            DacTrigger::Software => cr |= DAC_CR_TEN | (DAC_TSEL_SWTRIG << DAC_CR_TSEL_POS),
+            DacTrigger::Tim6Trgo => cr |= DAC_CR_TEN | (DAC_TSEL_TIM6_TRGO << DAC_CR_TSEL_POS),
+        }
+        match self.wave {
+            DacWave::None => {}
+            DacWave::Noise { bits } => cr |= DAC_CR_WAVE_NOISE | ((bits as u32 - 1) << DAC_CR_MAMP_POS),
+            DacWave::Triangle { bits } => cr |= DAC_CR_WAVE_TRIANGLE | ((bits as u32 - 1) << DAC_CR_MAMP_POS),
+        }
+        cr
+    }
+}
+
+/// register structure for dac registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Dac {
+    pub cr: u32,
+    pub swtrigr: u32,
+    pub dhr12r1: u32,
+    pub dhr12l1: u32,
+    pub dhr8r1: u32,
+    pub dhr12r2: u32,
+    pub dhr12l2: u32,
+    pub dhr8r2: u32,
+    pub dhr12rd: u32,
+    pub dhr12ld: u32,
+    pub dhr8rd: u32,
+    pub dor1: u32,
+    pub dor2: u32,
+    pub sr: u32,
+}
+
+impl Dac {
+    /// method to the instance of Dac
+    pub fn inst() -> &'static mut Dac {
+        unsafe { &mut *(0x4000_7400 as *mut Dac) }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///check the channel configuration at compile time
+const fn dac_check_cfg(cfg: &[Option<DacCfg>; DAC_CHANNEL_NUM]) {
+    let mut idx: usize = 0;
+    while idx < DAC_CHANNEL_NUM {
+        if let Some(ch) = cfg[idx] {
+            match ch.wave {
+                DacWave::None => {}
+                DacWave::Noise { bits } | DacWave::Triangle { bits } => {
+                    assert!(bits >= 1 && bits <= 12);
+                    //the wave steps with the trigger
+                    assert!(!matches!(ch.trigger, DacTrigger::None));
+                }
+            }
+        }
+        idx += 1;
+    }
+}
+
+///calculate prescaler and auto reload value of TIM6 for the trigger frequency
+const fn dac_calc_tim6(freq_hz: u32) -> (u16, u16) {
+    assert!(freq_hz > 0 && freq_hz <= TIMER_CLK_HZ / 2);
+    let ticks: u32 = TIMER_CLK_HZ / freq_hz;
+    let psc: u32 = (ticks - 1) / 0x1_0000;
+    let arr: u32 = ticks / (psc + 1) - 1;
+    assert!(psc <= 0xFFFF);
+    (psc as u16, arr as u16)
+}
+
+///init the dac; configures and enables the configured channels with their pins as analog; starts TIM6 with the
+///trigger frequency if configured
+pub fn init() {
+    set_reg_bitmsk(&mut Rcc::inst().apb1enr, RCC_APB1ENR_DACEN);
+
+    let mut cr: u32 = 0;
+    let channels: [DacChannel; DAC_CHANNEL_NUM] = [DacChannel::Ch1, DacChannel::Ch2];
+    for channel in channels {
+        if let Some(cfg) = DAC_CFG[channel as usize] {
+            channel.pin().cfg_mode(Mode::Analog);
+            cr |= cfg.cr() << (DAC_CR_CH2_POS * channel as u32);
+        }
+    }
+    set_reg(&mut Dac::inst().cr, cr);
+
+    if DAC_TIM6_TRIGGER_HZ.is_some() {
+        Timer6_7::inst_6().init_trigger(DAC_TIM6_PSC_ARR.0, DAC_TIM6_PSC_ARR.1);
+    }
+}
+
+///get the configuration of the channel
+fn dac_get_cfg(channel: DacChannel) -> Result<DacCfg, DacError> {
+    DAC_CFG[channel as usize].ok_or(DacError::NotConfigured)
+}
+
+///set the 12 bit output value of the channel; with wave generation the value is the offset of the wave
+#[allow(dead_code)]
+pub fn dac_set(channel: DacChannel, value: u16) -> Result<(), DacError> {
+    dac_get_cfg(channel)?;
+    if value > DAC_MAX_VALUE {
+        return Err(DacError::Value);
+    }
+    let inst: &mut Dac = Dac::inst();
+    match channel {
+        DacChannel::Ch1 => set_reg(&mut inst.dhr12r1, value as u32),
+        DacChannel::Ch2 => set_reg(&mut inst.dhr12r2, value as u32),
+    }
+    Ok(())
+}
+
+///set the 12 bit output values of both channels at once
+#[allow(dead_code)]
+pub fn dac_set_dual(value1: u16, value2: u16) -> Result<(), DacError> {
+    dac_get_cfg(DacChannel::Ch1)?;
+    dac_get_cfg(DacChannel::Ch2)?;
+    if value1 > DAC_MAX_VALUE || value2 > DAC_MAX_VALUE {
+        return Err(DacError::Value);
+    }
+    set_reg(&mut Dac::inst().dhr12rd, ((value2 as u32) << 16) | value1 as u32);
+    Ok(())
+}
+
+///trigger the channel by software; the channel must be configured with the Software trigger
+#[allow(dead_code)]
+pub fn dac_trigger(channel: DacChannel) -> Result<(), DacError> {
+    if dac_get_cfg(channel)?.trigger != DacTrigger::Software {
+        return Err(DacError::Trigger);
+    }
+    set_reg(&mut Dac::inst().swtrigr, 1 << (channel as u32));
+    Ok(())
+}
+
+///trigger both channels at once by software; both channels must be configured with the Software trigger
+#[allow(dead_code)]
+pub fn dac_trigger_dual() -> Result<(), DacError> {
+    if dac_get_cfg(DacChannel::Ch1)?.trigger != DacTrigger::Software
+        || dac_get_cfg(DacChannel::Ch2)?.trigger != DacTrigger::Software
+    {
+        return Err(DacError::Trigger);
+    }
+    set_reg(&mut Dac::inst().swtrigr, 0x3);
+    Ok(())
+}
+
+///get the actual output value of the channel including the wave
+#[allow(dead_code)]
+pub fn dac_get_output(channel: DacChannel) -> u16 {
+    let inst: &Dac = Dac::inst();
+    match channel {
+        DacChannel::Ch1 => get_reg(&inst.dor1) as u16,
+        DacChannel::Ch2 => get_reg(&inst.dor2) as u16,
+    }
+}
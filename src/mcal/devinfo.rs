@@ -0,0 +1,184 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : devinfo.rs
+//!
+//! # Short description
+//! Devinfo module implementation to read the identity of the Mcu
+//!
+//! # Detailed description
+//! Reads the factory programmed device data:
+//! - 96 bit unique device id: wafer coordinates, wafer number and lot number; unique for every Mcu, so it can be
+//!   used as serial number
+//! - flash size in kByte
+//! - DBGMCU IDCODE: device id of the Mcu line and silicon revision
+//!
+//! The values are read only and need no peripheral clock.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::util::get_reg;
+use core::ptr;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///address of the unique device id
+const DEVINFO_UID_ADDR: u32 = 0x1FFF_F7AC;
+///address of the flash size in kByte
+const DEVINFO_FLASH_SIZE_ADDR: u32 = 0x1FFF_F7CC;
+///address of the DBGMCU IDCODE register
+const DBGMCU_IDCODE_ADDR: u32 = 0x4001_5800;
+
+const DBGMCU_IDCODE_DEV_ID_MSK: u32 = 0xFFF;
+const DBGMCU_IDCODE_REV_ID_POS: u32 = 16;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///device id of the Mcu line
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DevId {
+    ///STM32F030x4/x6, STM32F03x
+    F03x,
+    ///STM32F04x, STM32F070x6
+    F04x,
+    ///STM32F030x8, STM32F05x
+    F05x,
+    ///STM32F070xB, STM32F07x
+    F07x,
+    ///STM32F030xC, STM32F09x
+    F09x,
+    Unknown(u16),
+}
+
+impl DevId {
+    const fn from_u16(value: u16) -> Self {
+        match value {
+            0x444 => DevId::F03x,
+            0x445 => DevId::F04x,
+            0x440 => DevId::F05x,
+            0x448 => DevId::F07x,
+            0x442 => DevId::F09x,
+            _ => DevId::Unknown(value),
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+///96 bit unique device id
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub struct DevUid {
+    pub words: [u32; 3],
+}
+
+impl DevUid {
+    ///get the x coordinate on the wafer
+    #[allow(dead_code)]
+    pub fn get_x(&self) -> u16 {
+        self.words[0] as u16
+    }
+
+    ///get the y coordinate on the wafer
+    #[allow(dead_code)]
+    pub fn get_y(&self) -> u16 {
+        (self.words[0] >> 16) as u16
+    }
+
+    #[allow(dead_code)]
+    pub fn get_wafer(&self) -> u8 {
+        self.words[1] as u8
+    }
+
+    ///get the lot number; ascii characters
+    #[allow(dead_code)]
+    pub fn get_lot(&self) -> [u8; 7] {
+        let mut lot: [u8; 7] = [0; 7];
+        lot[0..3].copy_from_slice(&self.words[1].to_le_bytes()[1..4]);
+        lot[3..7].copy_from_slice(&self.words[2].to_le_bytes());
+        lot
+    }
+
+    ///get the id as bytes in memory order (little endian words)
+    #[allow(dead_code)]
+    pub fn get_bytes(&self) -> [u8; 12] {
+        let mut bytes: [u8; 12] = [0; 12];
+        for (idx, word) in self.words.iter().enumerate() {
+            bytes[4 * idx..4 * idx + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+///content of the DBGMCU IDCODE register
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub struct DevIdcode {
+    pub dev_id: DevId,
+    ///silicon revision (e.g. 0x1000 for revision 1.0)
+    pub rev_id: u16,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///read the unique device id
+#[allow(dead_code)]
+pub fn devinfo_get_uid() -> DevUid {
+    let uid: *const u32 = DEVINFO_UID_ADDR as *const u32;
+    DevUid {
+        words: unsafe { [ptr::read_volatile(uid), ptr::read_volatile(uid.add(1)), ptr::read_volatile(uid.add(2))] },
+    }
+}
+
+///read the flash size in kByte
+#[allow(dead_code)]
+pub fn devinfo_get_flash_size_kb() -> u16 {
+    unsafe { ptr::read_volatile(DEVINFO_FLASH_SIZE_ADDR as *const u16) }
+}
+
+///read the device id and the silicon revision
+#[allow(dead_code)]
+pub fn devinfo_get_idcode() -> DevIdcode {
+    let idcode: u32 = get_reg(unsafe { &*(DBGMCU_IDCODE_ADDR as *const u32) });
+    DevIdcode {
+        dev_id: DevId::from_u16((idcode & DBGMCU_IDCODE_DEV_ID_MSK) as u16),
+        rev_id: (idcode >> DBGMCU_IDCODE_REV_ID_POS) as u16,
+    }
+}
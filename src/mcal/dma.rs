@@ -0,0 +1,315 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : dma.rs
+//!
+//! # Short description
+//! Dma module implementation for DMA1 (7 channels) and DMA2 (5 channels)
+//!
+//! # Detailed description
+//! A channel is started with dma_start for a configured transfer; the peripheral request that triggers the channel is
+//! selected by the channel selection register DMA_CSELR, so every request can be mapped to one of the channels that
+//! support it (see reference manual, dma request mapping). The transfer status is polled with dma_get_status; no
+//! interrupts are used.
+//! In normal mode the channel transfers len items and is finished; in circular mode the channel restarts
+//! automatically and dma_get_remaining gives the current position in the buffer.
+//! CAUTION: the memory buffer is accessed by the dma in the background; it must be static (e.g. part of the runtime
+//! data) and must be read volatile while the channel is active.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::rcc::Rcc;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::modify_reg_bits;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const DMA_CCR_EN: u32 = 1 << 0;
+const DMA_CCR_DIR: u32 = 1 << 4;
+const DMA_CCR_CIRC: u32 = 1 << 5;
+const DMA_CCR_PINC: u32 = 1 << 6;
+const DMA_CCR_MINC: u32 = 1 << 7;
+const DMA_CCR_PSIZE_POS: u32 = 8;
+const DMA_CCR_MSIZE_POS: u32 = 10;
+const DMA_CCR_PL_POS: u32 = 12;
+const DMA_CCR_MEM2MEM: u32 = 1 << 14;
+
+const DMA_ISR_TCIF: u32 = 1 << 1;
+const DMA_ISR_TEIF: u32 = 1 << 3;
+///all flags of a channel
+const DMA_IFCR_CH_MSK: u32 = 0xF;
+
+const RCC_AHBENR_DMA1EN: u32 = 1 << 0;
+const RCC_AHBENR_DMA2EN: u32 = 1 << 1;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DmaNo {
+    Dma1,
+    Dma2,
+}
+
+impl DmaNo {
+    const fn base(self) -> u32 {
+        match self {
+            DmaNo::Dma1 => 0x4002_0000,
+            DmaNo::Dma2 => 0x4002_0400,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DmaChannel {
+    Dma1Ch1,
+    Dma1Ch2,
+    Dma1Ch3,
+    Dma1Ch4,
+    Dma1Ch5,
+    Dma1Ch6,
+    Dma1Ch7,
+    Dma2Ch1,
+    Dma2Ch2,
+    Dma2Ch3,
+    Dma2Ch4,
+    Dma2Ch5,
+}
+
+impl DmaChannel {
+    ///get the dma and the index of the channel (0 for channel 1)
+    const fn get(self) -> (DmaNo, usize) {
+        match self {
+            DmaChannel::Dma1Ch1 => (DmaNo::Dma1, 0),
+            DmaChannel::Dma1Ch2 => (DmaNo::Dma1, 1),
+            DmaChannel::Dma1Ch3 => (DmaNo::Dma1, 2),
+            DmaChannel::Dma1Ch4 => (DmaNo::Dma1, 3),
+            DmaChannel::Dma1Ch5 => (DmaNo::Dma1, 4),
+            DmaChannel::Dma1Ch6 => (DmaNo::Dma1, 5),
+            DmaChannel::Dma1Ch7 => (DmaNo::Dma1, 6),
+            DmaChannel::Dma2Ch1 => (DmaNo::Dma2, 0),
+            DmaChannel::Dma2Ch2 => (DmaNo::Dma2, 1),
+            DmaChannel::Dma2Ch3 => (DmaNo::Dma2, 2),
+            DmaChannel::Dma2Ch4 => (DmaNo::Dma2, 3),
+            DmaChannel::Dma2Ch5 => (DmaNo::Dma2, 4),
+        }
+    }
+}
+
+///peripheral requests; the values match the selection of DMA_CSELR; the transmit and receive request of a
+///peripheral have the same selection value, they differ by the channel
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DmaRequest {
+    None = 0,
+    Adc = 1,
+    I2c = 2,
+    Spi = 3,
+    TimA = 4,
+    TimB = 5,
+    TimC = 6,
+    Dac = 7,
+    Usart1 = 8,
+    Usart2 = 9,
+    Usart3 = 10,
+    Usart4 = 11,
+    Usart5 = 12,
+    Usart6 = 13,
+    Usart7 = 14,
+    Usart8 = 15,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DmaDir {
+    PeriphToMem,
+    MemToPeriph,
+    MemToMem,
+}
+
+///item size; the values match the PSIZE / MSIZE bits of CCR
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DmaSize {
+    Bits8 = 0,
+    Bits16 = 1,
+    Bits32 = 2,
+}
+
+///channel priority; the values match the PL bits of CCR
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DmaPriority {
+    Low = 0,
+    Medium = 1,
+    High = 2,
+    VeryHigh = 3,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DmaStatus {
+    Disabled,
+    Busy,
+    Complete,
+    Error,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for the registers of a dma channel
+#[repr(C)]
+pub struct DmaChannelRegs {
+    pub ccr: u32,
+    pub cndtr: u32,
+    pub cpar: u32,
+    pub cmar: u32,
+    pub reserved: u32,
+}
+
+/// register structure for dma module registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Dma {
+    pub isr: u32,
+    pub ifcr: u32,
+    pub ch: [DmaChannelRegs; 7],
+    pub reserved0: [u32; 5],
+    pub cselr: u32,
+}
+
+impl Dma {
+    /// method to the instance of the given dma
+    pub fn inst(dma: DmaNo) -> &'static mut Dma {
+        unsafe { &mut *(dma.base() as *mut Dma) }
+    }
+}
+
+///transfer configuration of a channel
+#[derive(Copy, Clone)]
+pub struct DmaCfg {
+    pub dir: DmaDir,
+    pub circular: bool,
+    pub periph_size: DmaSize,
+    pub mem_size: DmaSize,
+    pub periph_inc: bool,
+    pub mem_inc: bool,
+    pub priority: DmaPriority,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///enable the clock of both dma
+pub fn init() {
+    set_reg_bitmsk(&mut Rcc::inst().ahbenr, RCC_AHBENR_DMA1EN | RCC_AHBENR_DMA2EN);
+}
+
+///start a transfer of len items between the peripheral (or source memory for MemToMem) address and the memory
+///address; a running transfer of the channel is stopped before
+pub fn dma_start(channel: DmaChannel, request: DmaRequest, cfg: &DmaCfg, periph_addr: u32, mem_addr: u32, len: u16) {
+    let (dma, idx) = channel.get();
+    let inst: &mut Dma = Dma::inst(dma);
+
+    clr_reg_bitmsk(&mut inst.ch[idx].ccr, DMA_CCR_EN);
+    set_reg(&mut inst.ifcr, DMA_IFCR_CH_MSK << (4 * idx));
+    modify_reg_bits(&mut inst.cselr, 0xF << (4 * idx), (request as u32) << (4 * idx));
+
+    let mut ccr: u32 = ((cfg.periph_size as u32) << DMA_CCR_PSIZE_POS)
+        | ((cfg.mem_size as u32) << DMA_CCR_MSIZE_POS)
+        | ((cfg.priority as u32) << DMA_CCR_PL_POS);
+    match cfg.dir {
+        DmaDir::PeriphToMem => {}
+        DmaDir::MemToPeriph => ccr |= DMA_CCR_DIR,
+        DmaDir::MemToMem => ccr |= DMA_CCR_MEM2MEM,
+    }
+    if cfg.circular {
+        ccr |= DMA_CCR_CIRC;
+    }
+    if cfg.periph_inc {
+        ccr |= DMA_CCR_PINC;
+    }
+    if cfg.mem_inc {
+        ccr |= DMA_CCR_MINC;
+    }
+
+    set_reg(&mut inst.ch[idx].cpar, periph_addr);
+    set_reg(&mut inst.ch[idx].cmar, mem_addr);
+    set_reg(&mut inst.ch[idx].cndtr, len as u32);
+    set_reg(&mut inst.ch[idx].ccr, ccr);
+    set_reg_bitmsk(&mut inst.ch[idx].ccr, DMA_CCR_EN);
+}
+
+///stop the transfer of the channel
+#[allow(dead_code)]
+pub fn dma_stop(channel: DmaChannel) {
+    let (dma, idx) = channel.get();
+    let inst: &mut Dma = Dma::inst(dma);
+    clr_reg_bitmsk(&mut inst.ch[idx].ccr, DMA_CCR_EN);
+    set_reg(&mut inst.ifcr, DMA_IFCR_CH_MSK << (4 * idx));
+}
+
+///get the status of the channel; Complete and Error are reported once, the flags are cleared; in circular mode
+///Complete is reported at each wrap around of the buffer
+pub fn dma_get_status(channel: DmaChannel) -> DmaStatus {
+    let (dma, idx) = channel.get();
+    let inst: &mut Dma = Dma::inst(dma);
+    let isr: u32 = get_reg(&inst.isr) >> (4 * idx);
+    if (isr & DMA_ISR_TEIF) != 0 {
+        //the channel is disabled by hardware on a transfer error
+        set_reg(&mut inst.ifcr, DMA_IFCR_CH_MSK << (4 * idx));
+        DmaStatus::Error
+    } else if (isr & DMA_ISR_TCIF) != 0 {
+        set_reg(&mut inst.ifcr, DMA_IFCR_CH_MSK << (4 * idx));
+        DmaStatus::Complete
+    } else if (get_reg(&inst.ch[idx].ccr) & DMA_CCR_EN) == 0 {
+        DmaStatus::Disabled
+    } else {
+        DmaStatus::Busy
+    }
+}
+
+///get the number of items that remain to be transferred; in circular mode the buffer position is len minus this
+pub fn dma_get_remaining(channel: DmaChannel) -> u16 {
+    let (dma, idx) = channel.get();
+    get_reg(&Dma::inst(dma).ch[idx].cndtr) as u16
+}
@@ -0,0 +1,424 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : flash.rs
+//!
+//! # Short description
+//! Flash module implementation for the Mcu embedded flash memory interface
+//!
+//! # Detailed description
+//! Provides unlock / lock, page erase and half-word programming of the embedded flash. The methods of Flash only
+//! start an operation and poll its state, so that a task can handle a flash job without blocking the scheduler.
+//! CAUTION: while an erase or program operation is active, every read access to the flash stalls the bus, so
+//! code executed from flash waits until the operation is finished.
+//!
+//! The blocking functions (flash_erase_page, flash_program_halfword, flash_program_option_bytes) start the
+//! operation and wait for its end in a routine located in ram (.code_ram), so no flash access is done while the
+//! flash is busy. The routine is written in assembler to be sure that it does not call code located in flash.
+//!
+//! The option bytes are erased and programmed as a whole; they are loaded by the Mcu after the next reset or by
+//! flash_launch_option_bytes (which also triggers a reset). Read protection level 2 is not supported, because it
+//! can not be revoked.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+#[cfg(not(test))]
+use core::arch::asm;
+use core::ptr;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///flash page size in bytes
+pub const FLASH_PAGE_SIZE: u32 = 2048;
+
+///minimum and maximum duration of a page erase (datasheet tERASE); the flash is stalled for this time
+pub const FLASH_PAGE_ERASE_MIN_US: u32 = 20_000;
+pub const FLASH_PAGE_ERASE_MAX_US: u32 = 40_000;
+///maximum duration of a half-word programming (datasheet tPROG); the flash is stalled for this time
+pub const FLASH_PROGRAM_HALFWORD_MAX_US: u32 = 70;
+
+///start address and size of the main flash memory
+pub const FLASH_MAIN_START: u32 = 0x0800_0000;
+pub const FLASH_MAIN_SIZE: u32 = 256 * 1024;
+
+///start address of the option bytes; every option byte is stored as byte and complement in a half-word
+const FLASH_OB_ADDR: u32 = 0x1FFF_F800;
+const FLASH_OB_RDP: u32 = FLASH_OB_ADDR;
+const FLASH_OB_USER: u32 = FLASH_OB_ADDR + 0x2;
+const FLASH_OB_DATA0: u32 = FLASH_OB_ADDR + 0x4;
+const FLASH_OB_DATA1: u32 = FLASH_OB_ADDR + 0x6;
+const FLASH_OB_WRP0: u32 = FLASH_OB_ADDR + 0x8;
+
+const FLASH_KEY1: u32 = 0x4567_0123;
+const FLASH_KEY2: u32 = 0xCDEF_89AB;
+
+const FLASH_SR_BSY: u32 = 1 << 0;
+const FLASH_SR_PGERR: u32 = 1 << 2;
+const FLASH_SR_WRPRTERR: u32 = 1 << 4;
+const FLASH_SR_EOP: u32 = 1 << 5;
+
+const FLASH_CR_PG: u32 = 1 << 0;
+const FLASH_CR_PER: u32 = 1 << 1;
+const FLASH_CR_OPTPG: u32 = 1 << 4;
+const FLASH_CR_OPTER: u32 = 1 << 5;
+const FLASH_CR_STRT: u32 = 1 << 6;
+const FLASH_CR_LOCK: u32 = 1 << 7;
+const FLASH_CR_OPTWRE: u32 = 1 << 9;
+const FLASH_CR_OBL_LAUNCH: u32 = 1 << 13;
+
+const FLASH_OBR_RDPRT_POS: u32 = 1;
+const FLASH_OBR_RDPRT_MSK: u32 = 0x3;
+const FLASH_OBR_USER_POS: u32 = 8;
+const FLASH_OBR_DATA0_POS: u32 = 16;
+const FLASH_OBR_DATA1_POS: u32 = 24;
+
+const FLASH_SR_FLAG_MSK: u32 = FLASH_SR_PGERR | FLASH_SR_WRPRTERR | FLASH_SR_EOP;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum FlashError {
+    Busy,
+    WriteProtect,
+    Programming,
+    Locked,
+    Address,
+}
+
+///read protection level of the option bytes; the values are the RDP option byte values
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum RdpLevel {
+    Level0 = 0xAA,
+    Level1 = 0xBB,
+}
+
+///trigger of a blocking operation
+#[derive(Copy, Clone)]
+enum FlashTrigger {
+    ///set the start bit with the address register set to the address
+    Start(u32),
+    ///write the half-word to the address
+    Halfword(u32, u16),
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+///content of the option bytes; wrp has one bit per 4 pages (8 kByte), a cleared bit enables the write protection
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub struct FlashOptionBytes {
+    pub rdp: RdpLevel,
+    pub user: u8,
+    pub data0: u8,
+    pub data1: u8,
+    pub wrp: [u8; 4],
+}
+
+/// register structure for flash interface registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Flash {
+    pub acr: u32,
+    pub keyr: u32,
+    pub optkeyr: u32,
+    pub sr: u32,
+    pub cr: u32,
+    pub ar: u32,
+    pub reserved0: u32,
+    pub obr: u32,
+    pub wrpr: u32,
+}
+
+impl Flash {
+    /// method to the instance of Flash
+    pub fn inst() -> &'static mut Flash {
+        unsafe { &mut *(0x4002_2000 as *mut Flash) }
+    }
+
+    ///unlock the flash control register; is only possible once after each lock
+    pub fn unlock(&mut self) {
+        if (get_reg(&self.cr) & FLASH_CR_LOCK) != 0 {
+            set_reg(&mut self.keyr, FLASH_KEY1);
+            set_reg(&mut self.keyr, FLASH_KEY2);
+        }
+    }
+
+    ///unlock the flash control register and the write access to the option bytes
+    pub fn unlock_option_bytes(&mut self) {
+        self.unlock();
+        if (get_reg(&self.cr) & FLASH_CR_OPTWRE) == 0 {
+            set_reg(&mut self.optkeyr, FLASH_KEY1);
+            set_reg(&mut self.optkeyr, FLASH_KEY2);
+        }
+    }
+
+    pub fn lock(&mut self) {
+        set_reg_bitmsk(&mut self.cr, FLASH_CR_LOCK);
+    }
+
+    pub fn is_busy(&self) -> bool {
+        (get_reg(&self.sr) & FLASH_SR_BSY) != 0
+    }
+
+    ///start the erase of the page containing the address
+    #[allow(dead_code)]
+    pub fn start_page_erase(&mut self, addr: u32) -> Result<(), FlashError> {
+        flash_check_addr(addr)?;
+        self.check_start()?;
+        set_reg_bitmsk(&mut self.cr, FLASH_CR_PER);
+        set_reg(&mut self.ar, addr);
+        set_reg_bitmsk(&mut self.cr, FLASH_CR_STRT);
+        Ok(())
+    }
+
+    ///start the programming of a half-word; the address must be half-word aligned
+    #[allow(dead_code)]
+    pub fn start_program_halfword(&mut self, addr: u32, value: u16) -> Result<(), FlashError> {
+        flash_check_addr(addr)?;
+        if (addr & 1) != 0 {
+            return Err(FlashError::Address);
+        }
+        self.check_start()?;
+        set_reg_bitmsk(&mut self.cr, FLASH_CR_PG);
+        unsafe {
+            ptr::write_volatile(addr as *mut u16, value);
+        }
+        Ok(())
+    }
+
+    ///poll the state of the started operation; returns Busy as long as the operation is active, afterwards the
+    ///result of the operation; the operation bits are cleared when the operation is finished
+    #[allow(dead_code)]
+    pub fn poll(&mut self) -> Result<(), FlashError> {
+        let sr: u32 = get_reg(&self.sr);
+        if (sr & FLASH_SR_BSY) != 0 {
+            return Err(FlashError::Busy);
+        }
+        clr_reg_bitmsk(&mut self.cr, FLASH_CR_PG | FLASH_CR_PER);
+        self.finish(sr)
+    }
+
+    ///clear the status flags of the finished operation and map them to the result
+    fn finish(&mut self, sr: u32) -> Result<(), FlashError> {
+        //status flags are cleared by writing 1
+        set_reg(&mut self.sr, sr & FLASH_SR_FLAG_MSK);
+        if (sr & FLASH_SR_WRPRTERR) != 0 {
+            Err(FlashError::WriteProtect)
+        } else if (sr & FLASH_SR_PGERR) != 0 {
+            Err(FlashError::Programming)
+        } else {
+            Ok(())
+        }
+    }
+
+    ///execute an operation with the operation bit op_bit: the trigger (start bit or half-word write) and the
+    ///wait for the end are done from ram
+    fn execute(&mut self, op_bit: u32, trigger: FlashTrigger) -> Result<(), FlashError> {
+        self.check_start()?;
+        set_reg_bitmsk(&mut self.cr, op_bit);
+        let sr: u32 = match trigger {
+            FlashTrigger::Start(addr) => {
+                set_reg(&mut self.ar, addr);
+                let cr: u32 = get_reg(&self.cr) | FLASH_CR_STRT;
+                flash_ram_trigger_wait(&mut self.cr as *mut u32 as u32, cr, false, &self.sr as *const u32 as u32)
+            }
+            FlashTrigger::Halfword(addr, value) => {
+                flash_ram_trigger_wait(addr, value as u32, true, &self.sr as *const u32 as u32)
+            }
+        };
+        clr_reg_bitmsk(&mut self.cr, op_bit);
+        self.finish(sr)
+    }
+
+    fn check_start(&self) -> Result<(), FlashError> {
+        if self.is_busy() {
+            Err(FlashError::Busy)
+        } else if (get_reg(&self.cr) & FLASH_CR_LOCK) != 0 {
+            Err(FlashError::Locked)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///check that the address is located in the main flash
+fn flash_check_addr(addr: u32) -> Result<(), FlashError> {
+    if (FLASH_MAIN_START..FLASH_MAIN_START + FLASH_MAIN_SIZE).contains(&addr) {
+        Ok(())
+    } else {
+        Err(FlashError::Address)
+    }
+}
+
+///write the trigger value to the trigger address (as half-word or word) and wait until the flash is not busy;
+///returns the status register; the routine is located in ram and does not access the flash
+#[cfg(not(test))]
+#[unsafe(link_section = ".code_ram")]
+#[inline(never)]
+fn flash_ram_trigger_wait(trigger_addr: u32, trigger_val: u32, halfword: bool, sr_addr: u32) -> u32 {
+    //register operands as usize for the full register width
+    let sr: usize;
+    unsafe {
+        asm!(
+            "cmp {hw}, #0",
+            "beq 3f",
+            "strh {val}, [{dst}]",
+            "b 4f",
+            "3:",
+            "str {val}, [{dst}]",
+            "4:",
+            "ldr {sr}, [{sra}]",
+            "tst {sr}, {bsy}",
+            "bne 4b",
+            hw = in(reg) halfword as usize,
+            val = in(reg) trigger_val as usize,
+            dst = in(reg) trigger_addr as usize,
+            sra = in(reg) sr_addr as usize,
+            bsy = in(reg) FLASH_SR_BSY as usize,
+            sr = out(reg) sr,
+            options(nostack),
+        );
+    }
+    sr as u32
+}
+
+///the host test build has no flash controller to trigger
+#[cfg(test)]
+fn flash_ram_trigger_wait(_trigger_addr: u32, _trigger_val: u32, _halfword: bool, _sr_addr: u32) -> u32 {
+    unreachable!();
+}
+
+///erase the page containing the address and wait for the end of the erase
+#[allow(dead_code)]
+pub fn flash_erase_page(addr: u32) -> Result<(), FlashError> {
+    flash_check_addr(addr)?;
+    let inst: &mut Flash = Flash::inst();
+    inst.unlock();
+    let result: Result<(), FlashError> = inst.execute(FLASH_CR_PER, FlashTrigger::Start(addr));
+    inst.lock();
+    result
+}
+
+///program the half-word and wait for the end of the programming; the address must be half-word aligned and the
+///half-word must be erased
+#[allow(dead_code)]
+pub fn flash_program_halfword(addr: u32, value: u16) -> Result<(), FlashError> {
+    flash_check_addr(addr)?;
+    if (addr & 1) != 0 {
+        return Err(FlashError::Address);
+    }
+    let inst: &mut Flash = Flash::inst();
+    inst.unlock();
+    let result: Result<(), FlashError> = inst.execute(FLASH_CR_PG, FlashTrigger::Halfword(addr, value));
+    inst.lock();
+    result
+}
+
+///read the option bytes loaded by the Mcu; a read protection level 2 is reported as Level1
+#[allow(dead_code)]
+pub fn flash_read_option_bytes() -> FlashOptionBytes {
+    let inst: &Flash = Flash::inst();
+    let obr: u32 = get_reg(&inst.obr);
+    let wrpr: u32 = get_reg(&inst.wrpr);
+    FlashOptionBytes {
+        rdp: if ((obr >> FLASH_OBR_RDPRT_POS) & FLASH_OBR_RDPRT_MSK) == 0 {
+            RdpLevel::Level0
+        } else {
+            RdpLevel::Level1
+        },
+        user: (obr >> FLASH_OBR_USER_POS) as u8,
+        data0: (obr >> FLASH_OBR_DATA0_POS) as u8,
+        data1: (obr >> FLASH_OBR_DATA1_POS) as u8,
+        wrp: wrpr.to_le_bytes(),
+    }
+}
+
+///erase and program the option bytes; the new option bytes are active after the next reset or after
+///flash_launch_option_bytes; CAUTION: a change of the read protection level from Level1 to Level0 erases the
+///main flash
+#[allow(dead_code)]
+pub fn flash_program_option_bytes(ob: &FlashOptionBytes) -> Result<(), FlashError> {
+    let inst: &mut Flash = Flash::inst();
+    inst.unlock_option_bytes();
+    let result: Result<(), FlashError> = if (get_reg(&inst.cr) & FLASH_CR_OPTWRE) == 0 {
+        Err(FlashError::Locked)
+    } else {
+        flash_write_option_bytes(inst, ob)
+    };
+    clr_reg_bitmsk(&mut inst.cr, FLASH_CR_OPTWRE);
+    inst.lock();
+    result
+}
+
+///erase the option bytes and program them half-word by half-word; the complements are generated by the Mcu
+fn flash_write_option_bytes(inst: &mut Flash, ob: &FlashOptionBytes) -> Result<(), FlashError> {
+    inst.execute(FLASH_CR_OPTER, FlashTrigger::Start(FLASH_OB_ADDR))?;
+    let values: [(u32, u8); 8] = [
+        (FLASH_OB_RDP, ob.rdp as u8),
+        (FLASH_OB_USER, ob.user),
+        (FLASH_OB_DATA0, ob.data0),
+        (FLASH_OB_DATA1, ob.data1),
+        (FLASH_OB_WRP0, ob.wrp[0]),
+        (FLASH_OB_WRP0 + 0x2, ob.wrp[1]),
+        (FLASH_OB_WRP0 + 0x4, ob.wrp[2]),
+        (FLASH_OB_WRP0 + 0x6, ob.wrp[3]),
+    ];
+    for (addr, value) in values {
+        inst.execute(FLASH_CR_OPTPG, FlashTrigger::Halfword(addr, value as u16))?;
+    }
+    Ok(())
+}
+
+///load the option bytes; the Mcu is reset by the load
+#[allow(dead_code)]
+pub fn flash_launch_option_bytes() -> ! {
+    let inst: &mut Flash = Flash::inst();
+    inst.unlock_option_bytes();
+    set_reg_bitmsk(&mut inst.cr, FLASH_CR_OBL_LAUNCH);
+    #[allow(clippy::empty_loop)]
+    loop {}
+}
@@ -153,6 +153,7 @@ enum GpioType {
 }
 
 #[allow(dead_code)]
+#[derive(Copy, Clone)]
 pub enum Mode {
     Input = 0,
     Output = 1,
@@ -161,12 +162,14 @@ pub enum Mode {
 }
 
 #[allow(dead_code)]
+#[derive(Copy, Clone)]
 pub enum Otype {
     PushPull = 0,
     OpenDrain = 1,
 }
 
 #[allow(dead_code)]
+#[derive(Copy, Clone)]
 pub enum Ospeed {
     Low = 0,
     Medium = 1,
@@ -175,6 +178,7 @@ pub enum Ospeed {
 }
 
 #[allow(dead_code)]
+#[derive(Copy, Clone)]
 pub enum Pupd {
     NoPullUpPullDown = 0,
     PullUp = 1,
@@ -182,6 +186,7 @@ pub enum Pupd {
 }
 
 #[allow(dead_code)]
+#[derive(Copy, Clone)]
 pub enum Af {
     AlternateFunc0 = 0,
     AlternateFunc1 = 1,
@@ -27,7 +27,7 @@
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
 use super::rcc::Rcc;
-use super::rcc::F_CPU_HZ;
+use super::rcc::TIM_CLK_HZ;
 use crate::mcal::util::clr_reg_bitmsk;
 use crate::mcal::util::get_reg;
 use crate::mcal::util::set_reg;
@@ -44,10 +44,96 @@ use crate::mcal::util::set_reg_bitmsk;
 //---------------------------------------------------------------------------------------------------------------------
 // Constants
 //---------------------------------------------------------------------------------------------------------------------
+///counter clock of the timers without prescaler
+pub const TIMER_CLK_HZ: u32 = TIM_CLK_HZ;
+
+const TIM_CR1_CEN: u32 = 1 << 0;
+const TIM_CR1_ARPE: u32 = 1 << 7;
+const TIM_EGR_UG: u32 = 1 << 0;
 
 //---------------------------------------------------------------------------------------------------------------------
 // Enums
 //---------------------------------------------------------------------------------------------------------------------
+///timers with capture / compare channels
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum TimerNo {
+    Tim1,
+    Tim2,
+    Tim3,
+    Tim14,
+    Tim15,
+    Tim16,
+    Tim17,
+}
+
+impl TimerNo {
+    const fn base(self) -> u32 {
+        match self {
+            TimerNo::Tim1 => 0x4001_2C00,
+            TimerNo::Tim2 => 0x4000_0000,
+            TimerNo::Tim3 => 0x4000_0400,
+            TimerNo::Tim14 => 0x4000_2000,
+            TimerNo::Tim15 => 0x4001_4000,
+            TimerNo::Tim16 => 0x4001_4400,
+            TimerNo::Tim17 => 0x4001_4800,
+        }
+    }
+
+    ///get the clock enable / reset bit and whether the timer is located on apb2 (otherwise apb1)
+    const fn rcc_bit(self) -> (bool, u32) {
+        match self {
+            TimerNo::Tim1 => (true, 1 << 11),
+            TimerNo::Tim2 => (false, 1 << 0),
+            TimerNo::Tim3 => (false, 1 << 1),
+            TimerNo::Tim14 => (false, 1 << 8),
+            TimerNo::Tim15 => (true, 1 << 16),
+            TimerNo::Tim16 => (true, 1 << 17),
+            TimerNo::Tim17 => (true, 1 << 18),
+        }
+    }
+
+    ///get the maximum counter value; TIM2 has a 32 bit counter
+    pub const fn max_cnt(self) -> u32 {
+        match self {
+            TimerNo::Tim2 => 0xFFFF_FFFF,
+            _ => 0xFFFF,
+        }
+    }
+
+    ///get the number of capture / compare channels
+    pub const fn channel_num(self) -> usize {
+        match self {
+            TimerNo::Tim1 | TimerNo::Tim2 | TimerNo::Tim3 => 4,
+            TimerNo::Tim15 => 2,
+            TimerNo::Tim14 | TimerNo::Tim16 | TimerNo::Tim17 => 1,
+        }
+    }
+
+    ///true if the timer has a break and dead-time register; the outputs of these timers need the main output enable
+    pub const fn has_bdtr(self) -> bool {
+        matches!(self, TimerNo::Tim1 | TimerNo::Tim15 | TimerNo::Tim16 | TimerNo::Tim17)
+    }
+
+    ///true if the channel has a complementary output
+    pub const fn has_complementary(self, channel: TimChannel) -> bool {
+        match self {
+            TimerNo::Tim1 => !matches!(channel, TimChannel::Ch4),
+            TimerNo::Tim15 | TimerNo::Tim16 | TimerNo::Tim17 => matches!(channel, TimChannel::Ch1),
+            TimerNo::Tim2 | TimerNo::Tim3 | TimerNo::Tim14 => false,
+        }
+    }
+}
+
+///capture / compare channel of a timer
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum TimChannel {
+    Ch1 = 0,
+    Ch2 = 1,
+    Ch3 = 2,
+    Ch4 = 3,
+}
 
 //---------------------------------------------------------------------------------------------------------------------
 // Structs
@@ -79,7 +165,7 @@ impl Timer6_7 {
         unsafe { &mut *(0x4000_1400 as *mut Timer6_7) }
     }
 
-    ///configure timer to run at F_CPU_HZ; so 1 count takes a time of 1 / F_CPU_HZ
+    ///configure timer to run at TIMER_CLK_HZ; so 1 count takes a time of 1 / TIMER_CLK_HZ
     pub fn init(&mut self) {
         set_reg_bitmsk(&mut Rcc::inst().apb1rstr, 1 << 4);
         clr_reg_bitmsk(&mut Rcc::inst().apb1rstr, 1 << 4);
@@ -90,10 +176,28 @@ impl Timer6_7 {
 
         // set period to 1ms
         let time_ms: i32 = 1;
-        set_reg(&mut self.arr, ((F_CPU_HZ / 1000 - 1) * time_ms) as u32);
+        set_reg(&mut self.arr, ((TIMER_CLK_HZ as i32 / 1000 - 1) * time_ms) as u32);
         set_reg(&mut self.cr1, (1 << 7) | (1 << 2));
         set_reg(&mut self.psc, 0);
-        set_reg(&mut self.cr1, 1);
+        set_reg_bitmsk(&mut self.cr1, 1);
+    }
+
+    ///configure timer 6 with the prescaler and auto reload value and output the update as trigger (TRGO); used when
+    ///timer 6 is not the time base of the scheduler
+    #[allow(dead_code)]
+    pub fn init_trigger(&mut self, psc: u16, arr: u16) {
+        set_reg_bitmsk(&mut Rcc::inst().apb1rstr, 1 << 4);
+        clr_reg_bitmsk(&mut Rcc::inst().apb1rstr, 1 << 4);
+
+        set_reg_bitmsk(&mut Rcc::inst().apb1enr, 1 << 4);
+
+        set_reg(&mut self.psc, psc as u32);
+        set_reg(&mut self.arr, arr as u32);
+        //MMS = update
+        set_reg(&mut self.cr2, 2 << 4);
+        set_reg(&mut self.cr1, (1 << 7) | (1 << 2));
+        set_reg(&mut self.egr, 1);
+        set_reg_bitmsk(&mut self.cr1, 1);
     }
 
     pub fn timer_elapsed(&mut self) -> bool {
@@ -109,8 +213,70 @@ impl Timer6_7 {
         get_reg(&self.cnt)
     }
 
+    ///reset the counter by an update generation; the update is the trigger output (TRGO) of the timer, e.g. for
+    ///the dac; the update flag is not set, because only overflows set it (URS)
     pub fn reset_cnt_value(&mut self) {
-        set_reg(&mut self.cnt, 0);
+        set_reg(&mut self.egr, 1);
+    }
+}
+
+/// register structure for the timers with capture / compare channels; need C representation for correct memory
+/// layout; registers that a timer does not have are reserved
+#[repr(C)]
+pub struct Timer {
+    pub cr1: u32,
+    pub cr2: u32,
+    pub smcr: u32,
+    pub dier: u32,
+    pub sr: u32,
+    pub egr: u32,
+    pub ccmr: [u32; 2],
+    pub ccer: u32,
+    pub cnt: u32,
+    pub psc: u32,
+    pub arr: u32,
+    pub rcr: u32,
+    pub ccr: [u32; 4],
+    pub bdtr: u32,
+    pub dcr: u32,
+    pub dmar: u32,
+}
+
+impl Timer {
+    /// method to the instance of the given timer
+    pub fn inst(timer: TimerNo) -> &'static mut Timer {
+        unsafe { &mut *(timer.base() as *mut Timer) }
+    }
+
+    ///reset the timer and enable its clock
+    pub fn enable_clock(timer: TimerNo) {
+        let (apb2, bit) = timer.rcc_bit();
+        if apb2 {
+            set_reg_bitmsk(&mut Rcc::inst().apb2rstr, bit);
+            clr_reg_bitmsk(&mut Rcc::inst().apb2rstr, bit);
+            set_reg_bitmsk(&mut Rcc::inst().apb2enr, bit);
+        } else {
+            set_reg_bitmsk(&mut Rcc::inst().apb1rstr, bit);
+            clr_reg_bitmsk(&mut Rcc::inst().apb1rstr, bit);
+            set_reg_bitmsk(&mut Rcc::inst().apb1enr, bit);
+        }
+    }
+
+    ///set prescaler and auto reload value; the new values are loaded by an update event, so the counter restarts
+    pub fn set_timebase(&mut self, psc: u16, arr: u32) {
+        set_reg(&mut self.psc, psc as u32);
+        set_reg(&mut self.arr, arr);
+        set_reg_bitmsk(&mut self.cr1, TIM_CR1_ARPE);
+        set_reg(&mut self.egr, TIM_EGR_UG);
+    }
+
+    pub fn start(&mut self) {
+        set_reg_bitmsk(&mut self.cr1, TIM_CR1_CEN);
+    }
+
+    #[allow(dead_code)]
+    pub fn stop(&mut self) {
+        clr_reg_bitmsk(&mut self.cr1, TIM_CR1_CEN);
     }
 }
 
@@ -0,0 +1,663 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : i2c.rs
+//!
+//! # Short description
+//! I2c module implementation for I2C1 and I2C2 in master mode
+//!
+//! # Detailed description
+//! The driver works poll based without interrupts. The busses are configured by the I2C_BUS_CFG table (index is the
+//! I2cNo); the TIMINGR value is calculated at compile time from the bus clock and the bus speed. The clock of I2C1 is
+//! switched to the system clock (SYSCLK_HZ), I2C2 is clocked by PCLK_HZ.
+//! A transaction is done as job: i2c_start_transfer starts a write, read or write-then-read transaction with a 7 bit
+//! slave address; i2c_task advances the job state machine by at most I2C_BYTES_PER_CALL bytes per call. A write-then-
+//! read transaction uses a repeated start between the two parts. The read data can be fetched with i2c_take_result
+//! after the job is done.
+//! Errors: a not acknowledged address or data byte gives Nack, a lost arbitration ArbitrationLost, a misplaced start
+//! or stop condition BusError. If the job does not progress for I2C_TIMEOUT_CALLS i2c_task calls, the job ends with
+//! Timeout and a bus recovery is done: the peripheral is disabled and SCL is toggled by gpio until a slave that holds
+//! SDA low releases it, then a stop condition is generated.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Otype;
+use crate::mcal::gpio::Pin;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::rcc::F_CPU_HZ;
+use crate::mcal::rcc::PCLK_HZ;
+use crate::mcal::rcc::SYSCLK_HZ;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+use crate::rte::RteData;
+use core::arch::asm;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const I2C_CR1_PE: u32 = 1 << 0;
+
+const I2C_CR2_RD_WRN: u32 = 1 << 10;
+const I2C_CR2_START: u32 = 1 << 13;
+const I2C_CR2_STOP: u32 = 1 << 14;
+const I2C_CR2_NBYTES_POS: u32 = 16;
+const I2C_CR2_AUTOEND: u32 = 1 << 25;
+
+const I2C_ISR_TXE: u32 = 1 << 0;
+const I2C_ISR_TXIS: u32 = 1 << 1;
+const I2C_ISR_RXNE: u32 = 1 << 2;
+const I2C_ISR_NACKF: u32 = 1 << 4;
+const I2C_ISR_STOPF: u32 = 1 << 5;
+const I2C_ISR_TC: u32 = 1 << 6;
+const I2C_ISR_BERR: u32 = 1 << 8;
+const I2C_ISR_ARLO: u32 = 1 << 9;
+const I2C_ISR_BUSY: u32 = 1 << 15;
+
+const I2C_ICR_ALL: u32 = 0x3F38;
+
+const RCC_CFGR3_I2C1SW: u32 = 1 << 4;
+
+///number of i2c busses
+const I2C_BUS_NUM: usize = 2;
+
+///maximum number of bytes of each part of a job
+pub const I2C_JOB_BUF_SIZE: usize = 32;
+
+///maximum number of bytes transferred per i2c_task call and bus
+const I2C_BYTES_PER_CALL: u32 = 4;
+
+///number of i2c_task calls without progress until a job ends with Timeout
+const I2C_TIMEOUT_CALLS: u16 = 20;
+
+///number of scl clocks generated by the bus recovery at most
+const I2C_RECOVERY_CLOCKS: u32 = 9;
+
+///bus configuration; index is the I2cNo; a bus with enabled false is not initialized
+const I2C_BUS_CFG: [I2cBusCfg; I2C_BUS_NUM] = [
+    I2cBusCfg {
+        enabled: true,
+        speed: I2cSpeed::Standard,
+        scl_pin: Pin::B8,
+        sda_pin: Pin::B9,
+        af: Af::AlternateFunc1,
+    },
+    I2cBusCfg {
+        enabled: false,
+        speed: I2cSpeed::Fast,
+        scl_pin: Pin::B10,
+        sda_pin: Pin::B11,
+        af: Af::AlternateFunc1,
+    },
+];
+
+///TIMINGR values of the busses; index is the I2cNo
+const I2C_TIMINGR: [u32; I2C_BUS_NUM] = [
+    i2c_calc_timingr(SYSCLK_HZ, I2C_BUS_CFG[0].speed),
+    i2c_calc_timingr(PCLK_HZ, I2C_BUS_CFG[1].speed),
+];
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum I2cNo {
+    I2c1 = 0,
+    I2c2 = 1,
+}
+
+impl I2cNo {
+    const fn base(self) -> u32 {
+        match self {
+            I2cNo::I2c1 => 0x4000_5400,
+            I2cNo::I2c2 => 0x4000_5800,
+        }
+    }
+
+    ///get the clock enable / reset bit of apb1
+    const fn rcc_bit(self) -> u32 {
+        match self {
+            I2cNo::I2c1 => 1 << 21,
+            I2cNo::I2c2 => 1 << 22,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum I2cSpeed {
+    ///100 kHz
+    Standard,
+    ///400 kHz
+    Fast,
+    ///1 MHz
+    FastPlus,
+}
+
+impl I2cSpeed {
+    ///get the bus frequency and the minimum scl low time, scl high time and data setup time in ns
+    const fn timing(self) -> (u32, u32, u32, u32) {
+        match self {
+            I2cSpeed::Standard => (100_000, 4700, 4000, 250),
+            I2cSpeed::Fast => (400_000, 1300, 600, 100),
+            I2cSpeed::FastPlus => (1_000_000, 500, 260, 50),
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum I2cError {
+    Busy,
+    Length,
+    NoData,
+    Nack,
+    ArbitrationLost,
+    BusError,
+    Timeout,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum I2cJobState {
+    Idle,
+    Write,
+    Read,
+    WaitStop,
+    Done,
+    Error(I2cError),
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for i2c module registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct I2c {
+    pub cr1: u32,
+    pub cr2: u32,
+    pub oar1: u32,
+    pub oar2: u32,
+    pub timingr: u32,
+    pub timeoutr: u32,
+    pub isr: u32,
+    pub icr: u32,
+    pub pecr: u32,
+    pub rxdr: u32,
+    pub txdr: u32,
+}
+
+impl I2c {
+    /// method to the instance of the given i2c
+    pub fn inst(i2c: I2cNo) -> &'static mut I2c {
+        unsafe { &mut *(i2c.base() as *mut I2c) }
+    }
+
+    pub fn enable(&mut self) {
+        set_reg_bitmsk(&mut self.cr1, I2C_CR1_PE);
+    }
+
+    ///disable the peripheral; this resets the communication state and the status flags
+    pub fn disable(&mut self) {
+        clr_reg_bitmsk(&mut self.cr1, I2C_CR1_PE);
+    }
+
+    ///start a transfer of nbytes bytes; autoend generates the stop condition after the last byte
+    pub fn start(&mut self, addr: u8, read: bool, nbytes: usize, autoend: bool) {
+        let mut cr2: u32 = ((addr as u32) << 1) | ((nbytes as u32) << I2C_CR2_NBYTES_POS) | I2C_CR2_START;
+        if read {
+            cr2 |= I2C_CR2_RD_WRN;
+        }
+        if autoend {
+            cr2 |= I2C_CR2_AUTOEND;
+        }
+        set_reg(&mut self.cr2, cr2);
+    }
+
+    pub fn stop(&mut self) {
+        set_reg_bitmsk(&mut self.cr2, I2C_CR2_STOP);
+    }
+
+    pub fn is_busy(&self) -> bool {
+        (get_reg(&self.isr) & I2C_ISR_BUSY) != 0
+    }
+}
+
+#[derive(Copy, Clone)]
+struct I2cBusCfg {
+    enabled: bool,
+    speed: I2cSpeed,
+    scl_pin: Pin,
+    sda_pin: Pin,
+    af: Af,
+}
+
+///job of a bus
+#[derive(Copy, Clone)]
+struct I2cJob {
+    state: I2cJobState,
+    addr: u8,
+    tx_buf: [u8; I2C_JOB_BUF_SIZE],
+    tx_len: usize,
+    tx_idx: usize,
+    rx_buf: [u8; I2C_JOB_BUF_SIZE],
+    rx_len: usize,
+    rx_idx: usize,
+    idle_calls: u16,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+pub struct I2cData {
+    jobs: [I2cJob; I2C_BUS_NUM],
+    recovery_cnt: [u16; I2C_BUS_NUM],
+}
+
+impl I2cData {
+    pub const fn init() -> Self {
+        Self {
+            jobs: [I2cJob {
+                state: I2cJobState::Idle,
+                addr: 0,
+                tx_buf: [0; I2C_JOB_BUF_SIZE],
+                tx_len: 0,
+                tx_idx: 0,
+                rx_buf: [0; I2C_JOB_BUF_SIZE],
+                rx_len: 0,
+                rx_idx: 0,
+                idle_calls: 0,
+            }; I2C_BUS_NUM],
+            recovery_cnt: [0; I2C_BUS_NUM],
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn get_job_state(&self, i2c: I2cNo) -> I2cJobState {
+        self.jobs[i2c as usize].state
+    }
+
+    ///get the number of bus recoveries of the bus
+    #[allow(dead_code)]
+    pub fn get_recovery_cnt(&self, i2c: I2cNo) -> u16 {
+        self.recovery_cnt[i2c as usize]
+    }
+}
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///number of ticks of tick_hz that last at least ns
+const fn i2c_ns_to_ticks(ns: u32, tick_hz: u32) -> u32 {
+    (ns as u64 * tick_hz as u64).div_ceil(1_000_000_000) as u32
+}
+
+///calculate the scl low and high count in ticks of tick_hz; the scl period is rounded up, so the bus frequency
+///does not exceed the speed mode, and is split in the ratio of the minimum times, but not below the minimum times
+const fn i2c_calc_scl(tick_hz: u32, speed: I2cSpeed) -> (u32, u32) {
+    let (freq, low_ns, high_ns, _) = speed.timing();
+    let low_min: u32 = i2c_ns_to_ticks(low_ns, tick_hz);
+    let high_min: u32 = i2c_ns_to_ticks(high_ns, tick_hz);
+    let period: u32 = tick_hz.div_ceil(freq);
+    //the i2c clock is too slow for the speed mode
+    assert!(period >= low_min + high_min);
+    let mut low: u32 = (period * low_ns).div_ceil(low_ns + high_ns);
+    if low < low_min {
+        low = low_min;
+    } else if period - low < high_min {
+        low = period - high_min;
+    }
+    (low, period - low)
+}
+
+///calculate the TIMINGR value for the bus speed with the i2c clock clk; the sda hold time is kept at zero
+const fn i2c_calc_timingr(clk: u32, speed: I2cSpeed) -> u32 {
+    let (_, _, _, setup_ns) = speed.timing();
+    //smallest prescaler for which the scl low and high count fit into the 8 bit SCLL and SCLH (count - 1)
+    let mut presc: u32 = 0;
+    loop {
+        let (low, high) = i2c_calc_scl(clk / (presc + 1), speed);
+        if low <= 256 && high <= 256 {
+            break;
+        }
+        presc += 1;
+        assert!(presc < 16);
+    }
+    let tick_hz: u32 = clk / (presc + 1);
+    let (low, high) = i2c_calc_scl(tick_hz, speed);
+    assert!(low >= 1 && low <= 256);
+    assert!(high >= 1 && high <= 256);
+    let setup: u32 = i2c_ns_to_ticks(setup_ns, tick_hz);
+    let scldel: u32 = if setup > 0 { setup - 1 } else { 0 };
+    assert!(scldel < 16);
+    (presc << 28) | (scldel << 20) | ((high - 1) << 8) | (low - 1)
+}
+
+///configure the pins of the bus for the peripheral
+fn i2c_cfg_pins(cfg: &I2cBusCfg) {
+    for pin in [cfg.scl_pin, cfg.sda_pin] {
+        pin.cfg_otype(Otype::OpenDrain);
+        pin.cfg_af(cfg.af);
+        pin.cfg_mode(Mode::Alternate);
+    }
+}
+
+///init all enabled busses
+pub fn init() {
+    //i2c1 is clocked by hsi after reset; switch to system clock, so that the timing matches SYSCLK_HZ
+    set_reg_bitmsk(&mut Rcc::inst().cfgr3, RCC_CFGR3_I2C1SW);
+
+    for i2c in [I2cNo::I2c1, I2cNo::I2c2] {
+        let cfg: &I2cBusCfg = &I2C_BUS_CFG[i2c as usize];
+        if !cfg.enabled {
+            continue;
+        }
+        set_reg_bitmsk(&mut Rcc::inst().apb1rstr, i2c.rcc_bit());
+        clr_reg_bitmsk(&mut Rcc::inst().apb1rstr, i2c.rcc_bit());
+        set_reg_bitmsk(&mut Rcc::inst().apb1enr, i2c.rcc_bit());
+
+        i2c_cfg_pins(cfg);
+        let inst: &mut I2c = I2c::inst(i2c);
+        inst.disable();
+        set_reg(&mut inst.timingr, I2C_TIMINGR[i2c as usize]);
+        inst.enable();
+    }
+}
+
+///busy wait for half a scl period of the standard mode
+fn i2c_recovery_delay() {
+    //a loop iteration takes about 4 cycles
+    let mut cnt: u32 = F_CPU_HZ as u32 / 200_000 / 4;
+    while cnt > 0 {
+        unsafe { asm!("nop") };
+        cnt -= 1;
+    }
+}
+
+///recover a bus that is blocked by a slave holding SDA low: SCL is clocked by gpio until SDA is released, then a
+///stop condition is generated and the peripheral is reinitialized
+pub fn i2c_bus_recovery(i2c: I2cNo, rt_data: &mut RteData) {
+    let cfg: &I2cBusCfg = &I2C_BUS_CFG[i2c as usize];
+    let inst: &mut I2c = I2c::inst(i2c);
+    inst.disable();
+
+    //open drain outputs; a set output releases the line
+    cfg.scl_pin.set();
+    cfg.sda_pin.set();
+    cfg.scl_pin.cfg_mode(Mode::Output);
+    cfg.sda_pin.cfg_mode(Mode::Output);
+    i2c_recovery_delay();
+
+    let mut clk: u32 = 0;
+    while clk < I2C_RECOVERY_CLOCKS && !cfg.sda_pin.get() {
+        cfg.scl_pin.clr();
+        i2c_recovery_delay();
+        cfg.scl_pin.set();
+        i2c_recovery_delay();
+        clk += 1;
+    }
+
+    //stop condition : sda rising while scl is high
+    cfg.scl_pin.clr();
+    i2c_recovery_delay();
+    cfg.sda_pin.clr();
+    i2c_recovery_delay();
+    cfg.scl_pin.set();
+    i2c_recovery_delay();
+    cfg.sda_pin.set();
+    i2c_recovery_delay();
+
+    i2c_cfg_pins(cfg);
+    inst.enable();
+    let cnt: &mut u16 = &mut rt_data.i2c_data.recovery_cnt[i2c as usize];
+    *cnt = cnt.saturating_add(1);
+}
+
+///start a transaction with the slave; tx bytes are written first, then rx_len bytes are read; a write-then-read
+///transaction uses a repeated start; fails if the bus has an active job
+#[allow(dead_code)]
+pub fn i2c_start_transfer(
+    i2c: I2cNo,
+    addr: u8,
+    tx: &[u8],
+    rx_len: usize,
+    rt_data: &mut RteData,
+) -> Result<(), I2cError> {
+    let job: &mut I2cJob = &mut rt_data.i2c_data.jobs[i2c as usize];
+    match job.state {
+        I2cJobState::Write | I2cJobState::Read | I2cJobState::WaitStop => return Err(I2cError::Busy),
+        I2cJobState::Idle | I2cJobState::Done | I2cJobState::Error(_) => {}
+    }
+    if (tx.is_empty() && rx_len == 0) || tx.len() > I2C_JOB_BUF_SIZE || rx_len > I2C_JOB_BUF_SIZE || addr > 0x7F {
+        return Err(I2cError::Length);
+    }
+    let inst: &mut I2c = I2c::inst(i2c);
+    if inst.is_busy() {
+        return Err(I2cError::Busy);
+    }
+
+    job.addr = addr;
+    job.tx_buf[..tx.len()].copy_from_slice(tx);
+    job.tx_len = tx.len();
+    job.tx_idx = 0;
+    job.rx_len = rx_len;
+    job.rx_idx = 0;
+    job.idle_calls = 0;
+
+    set_reg(&mut inst.icr, I2C_ICR_ALL);
+    if tx.is_empty() {
+        inst.start(addr, true, rx_len, true);
+        job.state = I2cJobState::Read;
+    } else {
+        inst.start(addr, false, tx.len(), rx_len == 0);
+        job.state = I2cJobState::Write;
+    }
+    Ok(())
+}
+
+///copy the read data of the finished job; returns the number of bytes; the job is set to Idle
+#[allow(dead_code)]
+pub fn i2c_take_result(i2c: I2cNo, rx: &mut [u8], rt_data: &mut RteData) -> Result<usize, I2cError> {
+    let job: &mut I2cJob = &mut rt_data.i2c_data.jobs[i2c as usize];
+    match job.state {
+        I2cJobState::Write | I2cJobState::Read | I2cJobState::WaitStop => Err(I2cError::Busy),
+        I2cJobState::Idle => Err(I2cError::NoData),
+        I2cJobState::Error(e) => {
+            job.state = I2cJobState::Idle;
+            Err(e)
+        }
+        I2cJobState::Done => {
+            let cnt: usize = job.rx_len.min(rx.len());
+            rx[..cnt].copy_from_slice(&job.rx_buf[..cnt]);
+            job.state = I2cJobState::Idle;
+            Ok(cnt)
+        }
+    }
+}
+
+///advance the job of the bus by at most I2C_BYTES_PER_CALL bytes; returns true if the job progressed
+fn i2c_advance_job(inst: &mut I2c, job: &mut I2cJob) -> bool {
+    let isr: u32 = get_reg(&inst.isr);
+
+    let error: Option<I2cError> = if (isr & I2C_ISR_ARLO) != 0 {
+        Some(I2cError::ArbitrationLost)
+    } else if (isr & I2C_ISR_BERR) != 0 {
+        Some(I2cError::BusError)
+    } else if (isr & I2C_ISR_NACKF) != 0 {
+        Some(I2cError::Nack)
+    } else {
+        None
+    };
+    if let Some(e) = error {
+        //without autoend the stop condition after a nack must be generated by software
+        if e == I2cError::Nack && (get_reg(&inst.cr2) & I2C_CR2_AUTOEND) == 0 {
+            inst.stop();
+        }
+        //flush the transmit data register
+        set_reg(&mut inst.isr, I2C_ISR_TXE);
+        set_reg(&mut inst.icr, I2C_ICR_ALL);
+        job.state = I2cJobState::Error(e);
+        return true;
+    }
+
+    let mut progress: bool = false;
+    let mut cnt: u32 = 0;
+    while cnt < I2C_BYTES_PER_CALL {
+        let isr: u32 = get_reg(&inst.isr);
+        match job.state {
+            I2cJobState::Write => {
+                if (isr & I2C_ISR_TXIS) != 0 && job.tx_idx < job.tx_len {
+                    set_reg(&mut inst.txdr, job.tx_buf[job.tx_idx] as u32);
+                    job.tx_idx += 1;
+                } else if (isr & I2C_ISR_TC) != 0 {
+                    //write part done without autoend; repeated start for the read part
+                    inst.start(job.addr, true, job.rx_len, true);
+                    job.state = I2cJobState::Read;
+                } else if (isr & I2C_ISR_STOPF) != 0 {
+                    job.state = I2cJobState::WaitStop;
+                } else {
+                    break;
+                }
+            }
+            I2cJobState::Read => {
+                if (isr & I2C_ISR_RXNE) != 0 && job.rx_idx < job.rx_len {
+                    job.rx_buf[job.rx_idx] = get_reg(&inst.rxdr) as u8;
+                    job.rx_idx += 1;
+                } else if (isr & I2C_ISR_STOPF) != 0 {
+                    job.state = I2cJobState::WaitStop;
+                } else {
+                    break;
+                }
+            }
+            I2cJobState::WaitStop => {
+                set_reg(&mut inst.icr, I2C_ICR_ALL);
+                job.state = I2cJobState::Done;
+            }
+            I2cJobState::Idle | I2cJobState::Done | I2cJobState::Error(_) => break,
+        }
+        progress = true;
+        cnt += 1;
+    }
+    progress
+}
+
+///i2c main function; advances the active jobs and handles the timeout; must be called periodically by a task
+pub fn i2c_task(rt_data: &mut RteData) {
+    for i2c in [I2cNo::I2c1, I2cNo::I2c2] {
+        let job: &mut I2cJob = &mut rt_data.i2c_data.jobs[i2c as usize];
+        match job.state {
+            I2cJobState::Write | I2cJobState::Read | I2cJobState::WaitStop => {}
+            I2cJobState::Idle | I2cJobState::Done | I2cJobState::Error(_) => continue,
+        }
+
+        if i2c_advance_job(I2c::inst(i2c), job) {
+            job.idle_calls = 0;
+        } else {
+            job.idle_calls += 1;
+            if job.idle_calls >= I2C_TIMEOUT_CALLS {
+                job.state = I2cJobState::Error(I2cError::Timeout);
+                i2c_bus_recovery(i2c, rt_data);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEEDS: [I2cSpeed; 3] = [I2cSpeed::Standard, I2cSpeed::Fast, I2cSpeed::FastPlus];
+
+    ///check the decoded TIMINGR against the speed mode for the i2c clock clk
+    fn check_timingr(clk: u32, speed: I2cSpeed) {
+        let timingr: u32 = i2c_calc_timingr(clk, speed);
+        let (freq, low_ns, high_ns, setup_ns) = speed.timing();
+        let presc: u64 = (timingr >> 28) as u64;
+        let scldel: u64 = ((timingr >> 20) & 0xF) as u64;
+        let sdadel: u64 = ((timingr >> 16) & 0xF) as u64;
+        let sclh: u64 = ((timingr >> 8) & 0xFF) as u64;
+        let scll: u64 = (timingr & 0xFF) as u64;
+        //reserved bits and sdadel stay zero, so no count has overflowed into the next field
+        assert_eq!(timingr & 0x0F00_0000, 0);
+        assert_eq!(sdadel, 0);
+        let tick_ns_num: u64 = (presc + 1) * 1_000_000_000;
+        assert!((scll + 1) * tick_ns_num >= low_ns as u64 * clk as u64);
+        assert!((sclh + 1) * tick_ns_num >= high_ns as u64 * clk as u64);
+        assert!((scldel + 1) * tick_ns_num >= setup_ns as u64 * clk as u64);
+        //the scl frequency does not exceed the speed mode
+        assert!((scll + sclh + 2) * (presc + 1) * freq as u64 >= clk as u64);
+    }
+
+    #[test]
+    fn timingr_at_8mhz() {
+        for speed in SPEEDS {
+            check_timingr(8_000_000, speed);
+        }
+    }
+
+    #[test]
+    fn timingr_at_48mhz() {
+        for speed in SPEEDS {
+            check_timingr(48_000_000, speed);
+        }
+    }
+
+    #[test]
+    fn timingr_low_count_above_8_bit_raises_prescaler() {
+        //48 MHz Standard has a period of 480 clocks, the low time share of 260 does not fit into SCLL
+        let timingr: u32 = i2c_calc_timingr(48_000_000, I2cSpeed::Standard);
+        assert_eq!(timingr >> 28, 1);
+        assert_eq!(timingr & 0xFFFF, (109 << 8) | 129);
+    }
+
+    #[test]
+    fn timingr_keeps_minimum_high_time() {
+        //8 MHz Fast+ has a period of 8 clocks, the ratio would give a high time of 250 ns below 260 ns
+        let timingr: u32 = i2c_calc_timingr(8_000_000, I2cSpeed::FastPlus);
+        assert_eq!(timingr >> 28, 0);
+        assert_eq!(timingr & 0xFFFF, (2 << 8) | 4);
+    }
+
+    #[test]
+    fn timingr_of_busses() {
+        check_timingr(SYSCLK_HZ, I2C_BUS_CFG[0].speed);
+        check_timingr(PCLK_HZ, I2C_BUS_CFG[1].speed);
+    }
+}
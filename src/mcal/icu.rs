@@ -0,0 +1,498 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : icu.rs
+//!
+//! # Short description
+//! Input capture module implementation to measure period, frequency and pulse width of external signals
+//!
+//! # Detailed description
+//! The capture timers are configured by the ICU_TIMER_CFG table with the tick frequency of the counter; the counter
+//! runs free over the full range. The inputs are configured by the ICU_CFG table (index is the IcuId) with timer
+//! channel, pin, alternate function, input filter and mode:
+//! - Edge: the selected edge(s) are captured; the 16 bit capture values are extended to 32 bit timestamps by counting
+//!   the counter overflows, the period is the difference of two consecutive timestamps (TIM2 has a 32 bit counter
+//!   and needs no extension)
+//! - PwmInput: the input is routed to the channel and the following channel (e.g. CH1 and CH2); the counter is
+//!   reset by the rising edge (slave reset mode), the channel captures the period and the following channel the
+//!   pulse width at the falling edge; only channel 1 of TIM1/2/3/15 can be used and the timer can not be shared with
+//!   an Edge input
+//!
+//! No interrupts are used, the flags are polled by icu_task; the task period must be less than half the overflow time
+//! of the counter to assign the overflows to the captures. An Edge input that has more than one edge between two
+//! polls loses captures and reports no period until the next two edges; so the Edge mode is intended for signals
+//! slower than the task period, faster signals should use the PwmInput mode that measures in hardware.
+//! An input without edge for its timeout has no valid measurement.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Pin;
+use crate::mcal::gpt::TimChannel;
+use crate::mcal::gpt::Timer;
+use crate::mcal::gpt::TimerNo;
+use crate::mcal::gpt::TIMER_CLK_HZ;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::modify_reg_bits;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+use crate::rte::RteData;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///call period of icu_task; must be the period of the calling task (checked in the scheduler)
+pub const ICU_TASK_PERIOD_US: u32 = 1000;
+
+const TIM_CR1_URS: u32 = 1 << 2;
+const TIM_SMCR_TS_TI1FP1: u32 = 0b101 << 4;
+const TIM_SMCR_SMS_RESET: u32 = 0b100;
+const TIM_SMCR_MSK: u32 = 0xFF;
+const TIM_SR_UIF: u32 = 1 << 0;
+const TIM_SR_CC1IF: u32 = 1 << 1;
+const TIM_SR_CC1OF: u32 = 1 << 9;
+
+///input capture selection of a channel in CCMR
+const TIM_CCMR_CCS_DIRECT: u32 = 0b01;
+const TIM_CCMR_CCS_INDIRECT: u32 = 0b10;
+const TIM_CCMR_ICF_POS: u32 = 4;
+const TIM_CCMR_CH_MSK: u32 = 0xFF;
+
+const TIM_CCER_CCE: u32 = 1 << 0;
+const TIM_CCER_CCP: u32 = 1 << 1;
+const TIM_CCER_CCNP: u32 = 1 << 3;
+const TIM_CCER_CH_MSK: u32 = 0xF;
+
+///timer configuration
+const ICU_TIMER_CFG: [IcuTimerCfg; 2] = [
+    IcuTimerCfg {
+        timer: TimerNo::Tim2,
+        tick_hz: 1_000_000,
+    },
+    IcuTimerCfg {
+        timer: TimerNo::Tim3,
+        tick_hz: 1_000_000,
+    },
+];
+
+///number of configured inputs
+const ICU_INPUT_NUM: usize = 2;
+
+///input configuration; index is the IcuId
+const ICU_CFG: [IcuCfg; ICU_INPUT_NUM] = [
+    IcuCfg {
+        timer: TimerNo::Tim2,
+        channel: TimChannel::Ch1,
+        pin: Pin::A15,
+        af: Af::AlternateFunc2,
+        filter: 3,
+        mode: IcuMode::Edge(IcuEdge::Rising),
+        timeout_us: 2_000_000,
+    },
+    IcuCfg {
+        timer: TimerNo::Tim3,
+        channel: TimChannel::Ch1,
+        pin: Pin::A6,
+        af: Af::AlternateFunc1,
+        filter: 3,
+        mode: IcuMode::PwmInput,
+        timeout_us: 100_000,
+    },
+];
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///configured inputs
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum IcuId {
+    Tacho = 0,
+    PwmIn = 1,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum IcuEdge {
+    Rising,
+    Falling,
+    Both,
+}
+
+impl IcuEdge {
+    ///get the polarity bits of CCER
+    const fn ccer(self) -> u32 {
+        match self {
+            IcuEdge::Rising => 0,
+            IcuEdge::Falling => TIM_CCER_CCP,
+            IcuEdge::Both => TIM_CCER_CCP | TIM_CCER_CCNP,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum IcuMode {
+    Edge(IcuEdge),
+    PwmInput,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct IcuTimerCfg {
+    timer: TimerNo,
+    ///counter frequency; must be a divider of the timer clock
+    tick_hz: u32,
+}
+
+#[derive(Copy, Clone)]
+struct IcuCfg {
+    timer: TimerNo,
+    channel: TimChannel,
+    pin: Pin,
+    af: Af,
+    ///input filter (ICxF bits); 0 is no filter
+    filter: u8,
+    mode: IcuMode,
+    ///time without edge after that the measurement is invalid
+    timeout_us: u32,
+}
+
+#[derive(Copy, Clone)]
+pub struct IcuData {
+    ///overflow counter of the timers as upper half of the timestamps
+    ovf: [u16; ICU_TIMER_CFG.len()],
+    timestamp: [u32; ICU_INPUT_NUM],
+    period: [u32; ICU_INPUT_NUM],
+    pulse: [u32; ICU_INPUT_NUM],
+    valid: [bool; ICU_INPUT_NUM],
+    ///number of captures since start or timeout; the first capture gives no period
+    capture_cnt: [u32; ICU_INPUT_NUM],
+    idle_us: [u32; ICU_INPUT_NUM],
+    ovr_cnt: [u16; ICU_INPUT_NUM],
+}
+
+impl IcuData {
+    pub const fn init() -> Self {
+        Self {
+            ovf: [0; ICU_TIMER_CFG.len()],
+            timestamp: [0; ICU_INPUT_NUM],
+            period: [0; ICU_INPUT_NUM],
+            pulse: [0; ICU_INPUT_NUM],
+            valid: [false; ICU_INPUT_NUM],
+            capture_cnt: [0; ICU_INPUT_NUM],
+            idle_us: [0; ICU_INPUT_NUM],
+            ovr_cnt: [0; ICU_INPUT_NUM],
+        }
+    }
+
+    ///get the timestamp in ticks of the latest captured edge; None if no edge was captured yet or in PwmInput mode
+    #[allow(dead_code)]
+    pub fn get_timestamp(&self, id: IcuId) -> Option<u32> {
+        if ICU_CFG[id as usize].mode == IcuMode::PwmInput || self.capture_cnt[id as usize] == 0 {
+            None
+        } else {
+            Some(self.timestamp[id as usize])
+        }
+    }
+
+    ///get the period in ticks of the timer; None if there is no valid measurement
+    #[allow(dead_code)]
+    pub fn get_period_ticks(&self, id: IcuId) -> Option<u32> {
+        if self.valid[id as usize] {
+            Some(self.period[id as usize])
+        } else {
+            None
+        }
+    }
+
+    ///get the pulse width in ticks of the timer; only available in PwmInput mode
+    #[allow(dead_code)]
+    pub fn get_pulse_ticks(&self, id: IcuId) -> Option<u32> {
+        if self.valid[id as usize] && ICU_CFG[id as usize].mode == IcuMode::PwmInput {
+            Some(self.pulse[id as usize])
+        } else {
+            None
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn get_period_us(&self, id: IcuId) -> Option<u32> {
+        let period: u32 = self.get_period_ticks(id)?;
+        Some(icu_ticks_to_us(id, period))
+    }
+
+    #[allow(dead_code)]
+    pub fn get_pulse_us(&self, id: IcuId) -> Option<u32> {
+        let pulse: u32 = self.get_pulse_ticks(id)?;
+        Some(icu_ticks_to_us(id, pulse))
+    }
+
+    ///get the frequency in mHz
+    #[allow(dead_code)]
+    pub fn get_frequency_mhz(&self, id: IcuId) -> Option<u32> {
+        let period: u32 = self.get_period_ticks(id)?;
+        if period == 0 {
+            return None;
+        }
+        let freq: u64 = icu_tick_hz(ICU_CFG[id as usize].timer) as u64 * 1000 / period as u64;
+        Some(freq.min(u32::MAX as u64) as u32)
+    }
+
+    ///get the duty cycle in per mille; only available in PwmInput mode
+    #[allow(dead_code)]
+    pub fn get_duty_permille(&self, id: IcuId) -> Option<u16> {
+        let pulse: u32 = self.get_pulse_ticks(id)?;
+        let period: u32 = self.get_period_ticks(id)?;
+        if period == 0 {
+            return None;
+        }
+        Some((pulse.min(period) as u64 * 1000 / period as u64) as u16)
+    }
+
+    ///get the number of lost captures; Edge mode with more than one edge between two polls
+    #[allow(dead_code)]
+    pub fn get_ovr_cnt(&self, id: IcuId) -> u16 {
+        self.ovr_cnt[id as usize]
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///get the tick frequency of the configured timer
+const fn icu_tick_hz(timer: TimerNo) -> u32 {
+    let mut idx: usize = 0;
+    while idx < ICU_TIMER_CFG.len() {
+        if ICU_TIMER_CFG[idx].timer as u32 == timer as u32 {
+            return ICU_TIMER_CFG[idx].tick_hz;
+        }
+        idx += 1;
+    }
+    0
+}
+
+///convert ticks of the timer of the input to us
+#[allow(dead_code)]
+pub fn icu_ticks_to_us(id: IcuId, ticks: u32) -> u32 {
+    (ticks as u64 * 1_000_000 / icu_tick_hz(ICU_CFG[id as usize].timer) as u64) as u32
+}
+
+///extend the capture value by the overflow counter; with a pending overflow a capture in the lower half of the
+///counter range was taken after the overflow
+fn icu_extend(timer: TimerNo, ovf: u16, ccr: u32, uif: bool) -> u32 {
+    if timer.max_cnt() == u32::MAX {
+        return ccr;
+    }
+    let high: u16 = if uif && ccr < 0x8000 {
+        ovf.wrapping_add(1)
+    } else {
+        ovf
+    };
+    ((high as u32) << 16) | ccr
+}
+
+///configure the channel as input capture with the input selection and polarity
+fn icu_cfg_channel(inst: &mut Timer, ch: u32, ccs: u32, filter: u8, ccer: u32) {
+    let ccmr_pos: u32 = 8 * (ch % 2);
+    modify_reg_bits(
+        &mut inst.ccmr[(ch / 2) as usize],
+        TIM_CCMR_CH_MSK << ccmr_pos,
+        (ccs | ((filter as u32) << TIM_CCMR_ICF_POS)) << ccmr_pos,
+    );
+    modify_reg_bits(
+        &mut inst.ccer,
+        TIM_CCER_CH_MSK << (4 * ch),
+        (ccer | TIM_CCER_CCE) << (4 * ch),
+    );
+}
+
+///init the configured timers and inputs and start the timers
+pub fn init() {
+    let mut idx: usize = 0;
+    while idx < ICU_TIMER_CFG.len() {
+        let cfg: &IcuTimerCfg = &ICU_TIMER_CFG[idx];
+        Timer::enable_clock(cfg.timer);
+        let inst: &mut Timer = Timer::inst(cfg.timer);
+        //only the counter overflow sets the update flag, not the reset of the PwmInput mode
+        set_reg_bitmsk(&mut inst.cr1, TIM_CR1_URS);
+        inst.set_timebase((TIMER_CLK_HZ / cfg.tick_hz - 1) as u16, cfg.timer.max_cnt());
+        idx += 1;
+    }
+
+    idx = 0;
+    while idx < ICU_INPUT_NUM {
+        let cfg: &IcuCfg = &ICU_CFG[idx];
+        cfg.pin.cfg_af(cfg.af);
+        cfg.pin.cfg_mode(Mode::Alternate);
+        let inst: &mut Timer = Timer::inst(cfg.timer);
+        let ch: u32 = cfg.channel as u32;
+        match cfg.mode {
+            IcuMode::Edge(edge) => {
+                icu_cfg_channel(inst, ch, TIM_CCMR_CCS_DIRECT, cfg.filter, edge.ccer());
+            }
+            IcuMode::PwmInput => {
+                icu_cfg_channel(inst, ch, TIM_CCMR_CCS_DIRECT, cfg.filter, IcuEdge::Rising.ccer());
+                icu_cfg_channel(inst, ch + 1, TIM_CCMR_CCS_INDIRECT, cfg.filter, IcuEdge::Falling.ccer());
+                modify_reg_bits(&mut inst.smcr, TIM_SMCR_MSK, TIM_SMCR_TS_TI1FP1 | TIM_SMCR_SMS_RESET);
+            }
+        }
+        idx += 1;
+    }
+
+    idx = 0;
+    while idx < ICU_TIMER_CFG.len() {
+        let inst: &mut Timer = Timer::inst(ICU_TIMER_CFG[idx].timer);
+        set_reg(&mut inst.sr, 0);
+        inst.start();
+        idx += 1;
+    }
+}
+
+///evaluate the flags of the timer status for the input
+fn icu_poll_input(icu: &mut IcuData, id: usize, inst: &mut Timer, sr: u32, ovf: u16) {
+    let cfg: &IcuCfg = &ICU_CFG[id];
+    let ch: u32 = cfg.channel as u32;
+    let uif: bool = (sr & TIM_SR_UIF) != 0;
+    icu.idle_us[id] = icu.idle_us[id].saturating_add(ICU_TASK_PERIOD_US);
+
+    match cfg.mode {
+        IcuMode::Edge(_) => {
+            if (sr & (TIM_SR_CC1IF << ch)) != 0 {
+                //reading the capture register clears the capture flag
+                let timestamp: u32 = icu_extend(cfg.timer, ovf, get_reg(&inst.ccr[ch as usize]), uif);
+                if (sr & (TIM_SR_CC1OF << ch)) != 0 {
+                    //a capture was overwritten, so the timestamp difference is not one period
+                    set_reg(&mut inst.sr, !(TIM_SR_CC1OF << ch));
+                    icu.ovr_cnt[id] = icu.ovr_cnt[id].wrapping_add(1);
+                    icu.valid[id] = false;
+                } else if icu.capture_cnt[id] != 0 {
+                    icu.period[id] = timestamp.wrapping_sub(icu.timestamp[id]);
+                    icu.valid[id] = true;
+                }
+                icu.timestamp[id] = timestamp;
+                icu.capture_cnt[id] = icu.capture_cnt[id].wrapping_add(1).max(1);
+                icu.idle_us[id] = 0;
+            }
+        }
+        IcuMode::PwmInput => {
+            if uif {
+                //no rising edge for the full counter range; the next capture is not a period
+                icu.capture_cnt[id] = 0;
+                icu.valid[id] = false;
+            }
+            if (sr & (TIM_SR_CC1IF << ch)) != 0 {
+                let period: u32 = get_reg(&inst.ccr[ch as usize]);
+                let pulse: u32 = get_reg(&inst.ccr[ch as usize + 1]);
+                //overcaptures are expected if the signal is faster than the task, the registers have the latest values
+                set_reg(&mut inst.sr, !((TIM_SR_CC1OF << ch) | (TIM_SR_CC1OF << (ch + 1))));
+                if !uif && icu.capture_cnt[id] != 0 {
+                    icu.period[id] = period;
+                    icu.pulse[id] = pulse;
+                    icu.valid[id] = true;
+                }
+                if !uif {
+                    icu.capture_cnt[id] = icu.capture_cnt[id].wrapping_add(1).max(1);
+                }
+                icu.idle_us[id] = 0;
+            }
+        }
+    }
+
+    if icu.idle_us[id] > cfg.timeout_us {
+        icu.capture_cnt[id] = 0;
+        icu.valid[id] = false;
+    }
+}
+
+///poll the capture and overflow flags of the timers; must be called every ICU_TASK_PERIOD_US
+pub fn icu_task(rt_data: &mut RteData) {
+    let icu: &mut IcuData = &mut rt_data.icu_data;
+    let mut t_idx: usize = 0;
+    while t_idx < ICU_TIMER_CFG.len() {
+        let timer: TimerNo = ICU_TIMER_CFG[t_idx].timer;
+        let inst: &mut Timer = Timer::inst(timer);
+        let sr: u32 = get_reg(&inst.sr);
+
+        let mut idx: usize = 0;
+        while idx < ICU_INPUT_NUM {
+            if ICU_CFG[idx].timer == timer {
+                icu_poll_input(icu, idx, inst, sr, icu.ovf[t_idx]);
+            }
+            idx += 1;
+        }
+
+        if (sr & TIM_SR_UIF) != 0 {
+            //the flags are cleared by writing 0, so only the update flag is cleared
+            set_reg(&mut inst.sr, !TIM_SR_UIF);
+            icu.ovf[t_idx] = icu.ovf[t_idx].wrapping_add(1);
+        }
+        t_idx += 1;
+    }
+}
+
+//the configuration must be possible
+const _: () = {
+    let mut idx: usize = 0;
+    while idx < ICU_TIMER_CFG.len() {
+        let cfg: &IcuTimerCfg = &ICU_TIMER_CFG[idx];
+        assert!(cfg.tick_hz != 0 && TIMER_CLK_HZ.is_multiple_of(cfg.tick_hz));
+        assert!(TIMER_CLK_HZ / cfg.tick_hz <= 0x1_0000);
+        //the task must poll at least twice per counter overflow
+        assert!((cfg.timer.max_cnt() as u64 + 1) * 1_000_000 / cfg.tick_hz as u64 > 2 * ICU_TASK_PERIOD_US as u64);
+        idx += 1;
+    }
+    idx = 0;
+    while idx < ICU_INPUT_NUM {
+        let cfg: &IcuCfg = &ICU_CFG[idx];
+        assert!(icu_tick_hz(cfg.timer) != 0);
+        assert!((cfg.channel as usize) < cfg.timer.channel_num());
+        assert!(cfg.filter <= 0xF);
+        if let IcuMode::PwmInput = cfg.mode {
+            assert!(matches!(cfg.timer, TimerNo::Tim1 | TimerNo::Tim2 | TimerNo::Tim3 | TimerNo::Tim15));
+            assert!(matches!(cfg.channel, TimChannel::Ch1));
+            //the counter reset would corrupt the timestamps of other inputs on the timer
+            let mut other: usize = 0;
+            while other < ICU_INPUT_NUM {
+                assert!(other == idx || ICU_CFG[other].timer as u32 != cfg.timer as u32);
+                other += 1;
+            }
+        }
+        idx += 1;
+    }
+};
@@ -32,6 +32,7 @@
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
 use crate::application_main;
+use crate::mcal::rcc;
 use core::ptr;
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -238,6 +239,9 @@ pub unsafe extern "C" fn application_reset_handler() -> ! {
         unsafe static _stack_size: usize;
     }
 
+    //the reset flags are read before the ram is initialized; the cause is kept in the no-init ram
+    rcc::rcc_store_reset_cause();
+
     //setup data section
     let count: usize = &_data_size as *const usize as usize;
     let mut load_addr: *const u8 = ptr::addr_of!(_data_loadaddr);
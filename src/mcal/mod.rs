@@ -26,11 +26,30 @@
 //---------------------------------------------------------------------------------------------------------------------
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
+pub mod adc;
+pub mod can;
+pub mod cec;
+pub mod comp;
+pub mod crc;
+pub mod dac;
+pub mod devinfo;
+pub mod dma;
+pub mod flash;
 pub mod gpio;
 pub mod gpt;
+pub mod i2c;
+pub mod icu;
 pub mod isr;
+pub mod pwm;
+pub mod pwr;
 pub mod rcc;
+pub mod rtc;
+pub mod spi;
+pub mod systick;
+pub mod tsc;
+pub mod usart;
 pub mod util;
+pub mod wwdg;
 
 //---------------------------------------------------------------------------------------------------------------------
 // Types
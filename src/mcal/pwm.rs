@@ -0,0 +1,311 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : pwm.rs
+//!
+//! # Short description
+//! Pwm module implementation for the timers with capture / compare channels
+//!
+//! # Detailed description
+//! The pwm timers are configured by the PWM_TIMER_CFG table with frequency in Hz and dead-time in ns; the outputs by
+//! the PWM_OUTPUT_CFG table (index is the PwmOutput) with timer channel, pin and alternate function of the output and
+//! optionally of the complementary output. All channels of a timer have the same frequency. The duty cycle is given
+//! in per mille of the period; the outputs use pwm mode 1 (output active while the counter is below the compare
+//! value) with preload, so a new duty cycle or frequency is taken over at the next period.
+//! Complementary outputs and dead-time are available on the timers with break and dead-time register (TIM1, TIM15,
+//! TIM16, TIM17); the dead-time is inserted between the output and the complementary output at each edge. An active
+//! low output inverts both outputs, so the complementary output stays the inverse of the output (no shoot-through of
+//! a half-bridge).
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Pin;
+use crate::mcal::gpt::TimChannel;
+use crate::mcal::gpt::Timer;
+use crate::mcal::gpt::TimerNo;
+use crate::mcal::gpt::TIMER_CLK_HZ;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::modify_reg_bits;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///pwm mode 1 and output compare preload of a channel in CCMR
+const TIM_CCMR_OC_PWM1_PRELOAD: u32 = (0b110 << 4) | (1 << 3);
+const TIM_CCMR_CH_MSK: u32 = 0xFF;
+
+const TIM_CCER_CCE: u32 = 1 << 0;
+const TIM_CCER_CCP: u32 = 1 << 1;
+const TIM_CCER_CCNE: u32 = 1 << 2;
+const TIM_CCER_CCNP: u32 = 1 << 3;
+
+const TIM_BDTR_MOE: u32 = 1 << 15;
+const TIM_BDTR_DTG_MSK: u32 = 0xFF;
+
+///maximum duty cycle in per mille
+const PWM_DUTY_MAX: u16 = 1000;
+
+///timer configuration
+const PWM_TIMER_CFG: [PwmTimerCfg; 1] = [PwmTimerCfg {
+    timer: TimerNo::Tim1,
+    freq_hz: 20_000,
+    deadtime_ns: 500,
+}];
+
+///number of configured outputs
+const PWM_OUTPUT_NUM: usize = 1;
+
+///output configuration; index is the PwmOutput
+const PWM_OUTPUT_CFG: [PwmOutputCfg; PWM_OUTPUT_NUM] = [PwmOutputCfg {
+    timer: TimerNo::Tim1,
+    channel: TimChannel::Ch1,
+    pin: Pin::A8,
+    af: Af::AlternateFunc2,
+    comp_pin: Some((Pin::A7, Af::AlternateFunc2)),
+    active_low: false,
+    duty_permille: 0,
+}];
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///configured pwm outputs
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum PwmOutput {
+    Bridge = 0,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum PwmError {
+    Frequency,
+    Duty,
+    Deadtime,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct PwmTimerCfg {
+    timer: TimerNo,
+    freq_hz: u32,
+    ///dead-time of the complementary outputs; 0 for timers without dead-time
+    deadtime_ns: u32,
+}
+
+#[derive(Copy, Clone)]
+struct PwmOutputCfg {
+    timer: TimerNo,
+    channel: TimChannel,
+    pin: Pin,
+    af: Af,
+    ///pin and alternate function of the complementary output
+    comp_pin: Option<(Pin, Af)>,
+    ///inverts the output and the complementary output
+    active_low: bool,
+    ///duty cycle after init
+    duty_permille: u16,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///calculate prescaler and auto reload value for the frequency; the smallest prescaler is used for the highest
+///duty cycle resolution
+const fn pwm_calc_timebase(timer: TimerNo, freq_hz: u32) -> Result<(u16, u32), PwmError> {
+    if freq_hz == 0 || freq_hz > TIMER_CLK_HZ / 2 {
+        return Err(PwmError::Frequency);
+    }
+    let ticks: u64 = (TIMER_CLK_HZ / freq_hz) as u64;
+    let psc: u64 = ticks.div_ceil(timer.max_cnt() as u64 + 1) - 1;
+    if psc > 0xFFFF {
+        return Err(PwmError::Frequency);
+    }
+    let arr: u64 = TIMER_CLK_HZ as u64 / ((psc + 1) * freq_hz as u64) - 1;
+    Ok((psc as u16, arr as u32))
+}
+
+///calculate the DTG bits of BDTR for the dead-time; the dead-time clock is the timer clock
+const fn pwm_calc_dtg(deadtime_ns: u32) -> Result<u32, PwmError> {
+    let ticks: u64 = (deadtime_ns as u64 * TIMER_CLK_HZ as u64).div_ceil(1_000_000_000);
+    if ticks <= 127 {
+        Ok(ticks as u32)
+    } else if ticks <= 254 {
+        Ok(0x80 | (ticks.div_ceil(2) - 64) as u32)
+    } else if ticks <= 504 {
+        Ok(0xC0 | (ticks.div_ceil(8) - 32) as u32)
+    } else if ticks <= 1008 {
+        Ok(0xE0 | (ticks.div_ceil(16) - 32) as u32)
+    } else {
+        Err(PwmError::Deadtime)
+    }
+}
+
+///calculate the compare value of the duty cycle for the auto reload value
+const fn pwm_calc_ccr(arr: u32, duty_permille: u16) -> u32 {
+    ((arr as u64 + 1) * duty_permille as u64 / PWM_DUTY_MAX as u64) as u32
+}
+
+///configure the channel of the output for pwm mode 1 and enable the output and complementary output
+fn pwm_cfg_output(cfg: &PwmOutputCfg) {
+    cfg.pin.cfg_af(cfg.af);
+    cfg.pin.cfg_mode(Mode::Alternate);
+
+    let inst: &mut Timer = Timer::inst(cfg.timer);
+    let ch: u32 = cfg.channel as u32;
+    let ccmr_pos: u32 = 8 * (ch % 2);
+    modify_reg_bits(
+        &mut inst.ccmr[(ch / 2) as usize],
+        TIM_CCMR_CH_MSK << ccmr_pos,
+        TIM_CCMR_OC_PWM1_PRELOAD << ccmr_pos,
+    );
+    set_reg(
+        &mut inst.ccr[ch as usize],
+        pwm_calc_ccr(get_reg(&inst.arr), cfg.duty_permille),
+    );
+
+    let mut ccer: u32 = TIM_CCER_CCE;
+    if cfg.active_low {
+        ccer |= TIM_CCER_CCP;
+    }
+    if let Some((pin, af)) = cfg.comp_pin {
+        pin.cfg_af(af);
+        pin.cfg_mode(Mode::Alternate);
+        ccer |= TIM_CCER_CCNE;
+        if cfg.active_low {
+            ccer |= TIM_CCER_CCNP;
+        }
+    }
+    modify_reg_bits(&mut inst.ccer, 0xF << (4 * ch), ccer << (4 * ch));
+}
+
+///init the configured timers and outputs and start the timers
+pub fn init() {
+    let mut idx: usize = 0;
+    while idx < PWM_TIMER_CFG.len() {
+        let cfg: &PwmTimerCfg = &PWM_TIMER_CFG[idx];
+        Timer::enable_clock(cfg.timer);
+        let inst: &mut Timer = Timer::inst(cfg.timer);
+        //the configuration is checked at compile time
+        if let Ok((psc, arr)) = pwm_calc_timebase(cfg.timer, cfg.freq_hz) {
+            inst.set_timebase(psc, arr);
+        }
+        if cfg.timer.has_bdtr() {
+            let dtg: u32 = pwm_calc_dtg(cfg.deadtime_ns).unwrap_or(0);
+            set_reg(&mut inst.bdtr, dtg | TIM_BDTR_MOE);
+        }
+        idx += 1;
+    }
+
+    idx = 0;
+    while idx < PWM_OUTPUT_NUM {
+        pwm_cfg_output(&PWM_OUTPUT_CFG[idx]);
+        idx += 1;
+    }
+
+    idx = 0;
+    while idx < PWM_TIMER_CFG.len() {
+        Timer::inst(PWM_TIMER_CFG[idx].timer).start();
+        idx += 1;
+    }
+}
+
+///set the duty cycle of the output in per mille
+#[allow(dead_code)]
+pub fn pwm_set_duty(output: PwmOutput, duty_permille: u16) -> Result<(), PwmError> {
+    if duty_permille > PWM_DUTY_MAX {
+        return Err(PwmError::Duty);
+    }
+    let cfg: &PwmOutputCfg = &PWM_OUTPUT_CFG[output as usize];
+    let inst: &mut Timer = Timer::inst(cfg.timer);
+    let ccr: u32 = pwm_calc_ccr(get_reg(&inst.arr), duty_permille);
+    set_reg(&mut inst.ccr[cfg.channel as usize], ccr);
+    Ok(())
+}
+
+///set the frequency of the timer; the duty cycles of all channels are kept
+#[allow(dead_code)]
+pub fn pwm_set_frequency(timer: TimerNo, freq_hz: u32) -> Result<(), PwmError> {
+    let (psc, arr) = pwm_calc_timebase(timer, freq_hz)?;
+    let inst: &mut Timer = Timer::inst(timer);
+    let old_period: u64 = get_reg(&inst.arr) as u64 + 1;
+    let mut ch: usize = 0;
+    while ch < timer.channel_num() {
+        let ccr: u64 = get_reg(&inst.ccr[ch]) as u64;
+        set_reg(&mut inst.ccr[ch], (ccr * (arr as u64 + 1) / old_period) as u32);
+        ch += 1;
+    }
+    //prescaler and auto reload are preloaded, so the change is done at the next update event
+    set_reg(&mut inst.psc, psc as u32);
+    set_reg(&mut inst.arr, arr);
+    Ok(())
+}
+
+///set the dead-time of the complementary outputs of the timer
+#[allow(dead_code)]
+pub fn pwm_set_deadtime(timer: TimerNo, deadtime_ns: u32) -> Result<(), PwmError> {
+    if !timer.has_bdtr() {
+        return Err(PwmError::Deadtime);
+    }
+    let dtg: u32 = pwm_calc_dtg(deadtime_ns)?;
+    modify_reg_bits(&mut Timer::inst(timer).bdtr, TIM_BDTR_DTG_MSK, dtg);
+    set_reg_bitmsk(&mut Timer::inst(timer).bdtr, TIM_BDTR_MOE);
+    Ok(())
+}
+
+//the configuration must be possible
+const _: () = {
+    let mut idx: usize = 0;
+    while idx < PWM_TIMER_CFG.len() {
+        let cfg: &PwmTimerCfg = &PWM_TIMER_CFG[idx];
+        assert!(pwm_calc_timebase(cfg.timer, cfg.freq_hz).is_ok());
+        assert!(cfg.deadtime_ns == 0 || (cfg.timer.has_bdtr() && pwm_calc_dtg(cfg.deadtime_ns).is_ok()));
+        idx += 1;
+    }
+    idx = 0;
+    while idx < PWM_OUTPUT_NUM {
+        let cfg: &PwmOutputCfg = &PWM_OUTPUT_CFG[idx];
+        assert!((cfg.channel as usize) < cfg.timer.channel_num());
+        assert!(cfg.comp_pin.is_none() || cfg.timer.has_complementary(cfg.channel));
+        assert!(cfg.duty_permille <= PWM_DUTY_MAX);
+        idx += 1;
+    }
+};
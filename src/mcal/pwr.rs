@@ -0,0 +1,307 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : pwr.rs
+//!
+//! # Short description
+//! Pwr module implementation for the low power modes Sleep, Stop and Standby
+//!
+//! # Detailed description
+//! The low power modes are entered with WFE / WFI; no interrupts are used:
+//! - Sleep: only the core is stopped; SEVONPEND is set, so a peripheral interrupt that becomes pending wakes the core,
+//!   also if the interrupt is not enabled in the NVIC (the interrupt has to be enabled in the peripheral). Only the
+//!   change to pending gives the wakeup event, and without ISRs the pending bits are never cleared by the core; so
+//!   pwr_enter_sleep clears the pending bits of the given wakeup interrupts before the WFE (a wakeup interrupt that
+//!   is still requested by its peripheral becomes pending again at once and ends the Sleep immediately)
+//! - Stop: all clocks are stopped, ram and registers are kept; the core wakes by an EXTI event line configured with
+//!   pwr_cfg_exti_wakeup (e.g. rtc alarm). The regulator can be switched to low power mode in Stop (lower current,
+//!   longer wakeup time). After the wakeup the system clock is the HSI, so the clock tree is restored by
+//!   rcc::init before pwr_enter_stop returns; the rtc shadow registers are not updated in Stop, so they are
+//!   synchronized by rtc::rtc_sync_shadow afterwards.
+//! - Standby: the core domain is switched off, ram and registers are lost; the Mcu wakes by a reset from the NRST pin,
+//!   a rising edge of an enabled wakeup pin (pwr_cfg_wakeup_pin) or the rtc. After the wakeup pwr_is_standby_wakeup
+//!   reports the Standby.
+//!
+//! The backup domain (rtc and backup registers) is write protected after reset; pwr_set_backup_access enables the
+//! write access.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::rcc;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::rcc::RccError;
+use crate::mcal::rtc;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+#[cfg(not(test))]
+use core::arch::asm;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const PWR_CR_LPDS: u32 = 1 << 0;
+const PWR_CR_PDDS: u32 = 1 << 1;
+const PWR_CR_CWUF: u32 = 1 << 2;
+const PWR_CR_CSBF: u32 = 1 << 3;
+const PWR_CR_DBP: u32 = 1 << 8;
+
+const PWR_CSR_SBF: u32 = 1 << 1;
+
+///interrupt clear pending register of the NVIC; bit n is the interrupt number n
+const NVIC_ICPR_ADDR: u32 = 0xE000_E280;
+
+///system control register of the core
+const SCB_SCR_ADDR: u32 = 0xE000_ED10;
+const SCB_SCR_SLEEPDEEP: u32 = 1 << 2;
+const SCB_SCR_SEVONPEND: u32 = 1 << 4;
+
+const RCC_APB1ENR_PWREN: u32 = 1 << 28;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///regulator mode in Stop
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum PwrRegulator {
+    Main,
+    LowPower,
+}
+
+///wakeup pins for Standby; the values match the EWUP bits of PWR_CSR
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum WakeupPin {
+    Wkup1PA0 = 8,
+    Wkup2PC13 = 9,
+    Wkup3PE6 = 10,
+    Wkup4PA2 = 11,
+    Wkup5PC5 = 12,
+    Wkup6PB5 = 13,
+    Wkup7PB15 = 14,
+    Wkup8PF2 = 15,
+}
+
+///internal EXTI lines that can wake from Stop; the values are the line numbers
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum ExtiLine {
+    Pvd = 16,
+    RtcAlarm = 17,
+    RtcTamper = 19,
+    RtcWakeup = 20,
+    Comp1 = 21,
+    Comp2 = 22,
+    I2c1 = 23,
+    Usart1 = 25,
+    Usart2 = 26,
+    Cec = 27,
+    Usart3 = 28,
+}
+
+impl ExtiLine {
+    ///true if the line has an edge detection; the other lines are direct lines of the peripheral
+    const fn is_configurable(self) -> bool {
+        (self as u32) < 23
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for power control registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Pwr {
+    pub cr: u32,
+    pub csr: u32,
+}
+
+impl Pwr {
+    /// method to the instance of Pwr
+    pub fn inst() -> &'static mut Pwr {
+        unsafe { &mut *(0x4000_7000 as *mut Pwr) }
+    }
+}
+
+/// register structure for the extended interrupt and event controller; need C representation for correct memory
+/// layout
+#[repr(C)]
+pub struct Exti {
+    pub imr: u32,
+    pub emr: u32,
+    pub rtsr: u32,
+    pub ftsr: u32,
+    pub swier: u32,
+    pub pr: u32,
+}
+
+impl Exti {
+    /// method to the instance of Exti
+    pub fn inst() -> &'static mut Exti {
+        unsafe { &mut *(0x4001_0400 as *mut Exti) }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///get the system control register of the core
+fn pwr_scb_scr() -> &'static mut u32 {
+    unsafe { &mut *(SCB_SCR_ADDR as *mut u32) }
+}
+
+///get the interrupt clear pending register of the NVIC
+fn pwr_nvic_icpr() -> &'static mut u32 {
+    unsafe { &mut *(NVIC_ICPR_ADDR as *mut u32) }
+}
+
+///enable the clock of the power control
+pub fn init() {
+    set_reg_bitmsk(&mut Rcc::inst().apb1enr, RCC_APB1ENR_PWREN);
+}
+
+///enable or disable the write access to the backup domain (rtc, backup registers, Rcc bdcr)
+pub fn pwr_set_backup_access(enable: bool) {
+    if enable {
+        set_reg_bitmsk(&mut Pwr::inst().cr, PWR_CR_DBP);
+    } else {
+        clr_reg_bitmsk(&mut Pwr::inst().cr, PWR_CR_DBP);
+    }
+}
+
+///enable or disable the wakeup from Standby by a rising edge of the wakeup pin; the pin is used as wakeup input
+///independent of the gpio configuration while enabled
+#[allow(dead_code)]
+pub fn pwr_cfg_wakeup_pin(pin: WakeupPin, enable: bool) {
+    if enable {
+        set_reg_bitmsk(&mut Pwr::inst().csr, 1 << (pin as u32));
+    } else {
+        clr_reg_bitmsk(&mut Pwr::inst().csr, 1 << (pin as u32));
+    }
+}
+
+///enable or disable the wakeup from Stop by the EXTI line as event; configurable lines use the rising edge
+#[allow(dead_code)]
+pub fn pwr_cfg_exti_wakeup(line: ExtiLine, enable: bool) {
+    let inst: &mut Exti = Exti::inst();
+    let msk: u32 = 1 << (line as u32);
+    if enable {
+        if line.is_configurable() {
+            set_reg_bitmsk(&mut inst.rtsr, msk);
+        }
+        set_reg_bitmsk(&mut inst.emr, msk);
+    } else {
+        clr_reg_bitmsk(&mut inst.emr, msk);
+        if line.is_configurable() {
+            clr_reg_bitmsk(&mut inst.rtsr, msk);
+        }
+    }
+}
+
+///true if the Mcu was woken from Standby; the flag is cleared
+#[allow(dead_code)]
+pub fn pwr_is_standby_wakeup() -> bool {
+    let standby: bool = (get_reg(&Pwr::inst().csr) & PWR_CSR_SBF) != 0;
+    set_reg_bitmsk(&mut Pwr::inst().cr, PWR_CR_CSBF);
+    standby
+}
+
+///wait for an event; the first WFE clears a pending event, so the core sleeps until a new event
+#[cfg(not(test))]
+fn pwr_wait_for_event() {
+    unsafe { asm!("sev", "wfe", "wfe") };
+}
+
+///the host test build has no event to wait for
+#[cfg(test)]
+fn pwr_wait_for_event() {}
+
+///wait for an interrupt
+#[cfg(not(test))]
+fn pwr_wait_for_interrupt() {
+    unsafe { asm!("wfi") };
+}
+
+///the host test build has no interrupt to wait for
+#[cfg(test)]
+fn pwr_wait_for_interrupt() {}
+
+///enter Sleep until one of the wakeup interrupts becomes pending; wakeup_irqs is the mask of the interrupt numbers
+///(bit n is the interrupt number n); the pending bits of the wakeup interrupts are cleared before
+#[allow(dead_code)]
+pub fn pwr_enter_sleep(wakeup_irqs: u32) {
+    clr_reg_bitmsk(pwr_scb_scr(), SCB_SCR_SLEEPDEEP);
+    set_reg_bitmsk(pwr_scb_scr(), SCB_SCR_SEVONPEND);
+    set_reg(pwr_nvic_icpr(), wakeup_irqs);
+    pwr_wait_for_event();
+}
+
+///enter Stop until an event of a configured EXTI line; the clock tree is restored after the wakeup
+#[allow(dead_code)]
+pub fn pwr_enter_stop(regulator: PwrRegulator) -> Result<(), RccError> {
+    let inst: &mut Pwr = Pwr::inst();
+    clr_reg_bitmsk(&mut inst.cr, PWR_CR_PDDS);
+    match regulator {
+        PwrRegulator::Main => clr_reg_bitmsk(&mut inst.cr, PWR_CR_LPDS),
+        PwrRegulator::LowPower => set_reg_bitmsk(&mut inst.cr, PWR_CR_LPDS),
+    }
+    //pending flags of the configurable lines would end the Stop immediately
+    set_reg(&mut Exti::inst().pr, 0xFFFF_FFFF);
+
+    set_reg_bitmsk(pwr_scb_scr(), SCB_SCR_SLEEPDEEP);
+    pwr_wait_for_event();
+    clr_reg_bitmsk(pwr_scb_scr(), SCB_SCR_SLEEPDEEP);
+
+    //the Mcu wakes with the hsi as system clock
+    rcc::init()?;
+    //a failed synchronization is reported by rtc_get_datetime
+    let _ = rtc::rtc_sync_shadow();
+    Ok(())
+}
+
+///enter Standby; is left by a reset only
+pub fn pwr_enter_standby() -> ! {
+    let inst: &mut Pwr = Pwr::inst();
+    set_reg_bitmsk(&mut inst.cr, PWR_CR_PDDS);
+    //a set wakeup flag would end the Standby immediately
+    set_reg_bitmsk(&mut inst.cr, PWR_CR_CWUF);
+    set_reg_bitmsk(pwr_scb_scr(), SCB_SCR_SLEEPDEEP);
+    loop {
+        pwr_wait_for_interrupt();
+    }
+}
@@ -18,7 +18,14 @@
 //! Rcc module implementation for the Mcu Rcc peripheral
 //!
 //! # Detailed description
-//! @todo
+//! The clock tree is configured by init from the const configuration RCC_CFG: system clock source (HSI, HSE with
+//! optional bypass, PLL or HSI48), PLL source, multiplier and input divider, AHB and APB prescaler. The flash wait
+//! states are derived from the system clock. All clock frequencies (SYSCLK_HZ, HCLK_HZ, PCLK_HZ, TIM_CLK_HZ and
+//! F_CPU_HZ) are derived from RCC_CFG at compile time, so the drivers and the scheduler calculate their timings
+//! from the configured clock; the configuration is checked at compile time.
+//! Example for 48 MHz from the HSI: sysclk Pll, pll_src HsiDiv2, pll_mul 12.
+//! The reset flags of CSR are decoded by rcc_store_reset_cause at the start of the reset handler, before the ram is
+//! initialized, and are cleared; the cause is kept in the no-init ram and can be read with get_reset_cause.
 //!
 //!
 //!
@@ -26,6 +33,12 @@
 //---------------------------------------------------------------------------------------------------------------------
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::flash::Flash;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::modify_reg_bits;
+use crate::mcal::util::set_reg_bitmsk;
+use core::ptr;
 
 //---------------------------------------------------------------------------------------------------------------------
 // Types
@@ -34,21 +47,210 @@
 //---------------------------------------------------------------------------------------------------------------------
 // Statics
 //---------------------------------------------------------------------------------------------------------------------
+///decoded cause of the last reset; located in the no-init ram, so it is not touched by the ram initialization
+#[unsafe(link_section = ".noinit")]
+static mut RCC_RESET_CAUSE: ResetCause = ResetCause::Unknown;
 
 //---------------------------------------------------------------------------------------------------------------------
 // Constants
 //---------------------------------------------------------------------------------------------------------------------
+///frequency of the internal oscillators
+const HSI_HZ: u32 = 8_000_000;
+const HSI48_HZ: u32 = 48_000_000;
+
+///clock configuration
+const RCC_CFG: RccCfg = RccCfg {
+    sysclk: SysClk::Hsi,
+    hse_hz: 0,
+    hse_bypass: false,
+    pll_src: PllSrc::HsiDiv2,
+    pll_mul: 12,
+    pll_prediv: 1,
+    ahb_div: AhbDiv::Div1,
+    apb_div: ApbDiv::Div1,
+};
+
+///system clock in Hz
+pub const SYSCLK_HZ: u32 = rcc_sysclk_hz(&RCC_CFG);
+///clock of the core, the memories and the ahb peripherals in Hz
+pub const HCLK_HZ: u32 = rcc_hclk_hz(&RCC_CFG);
+///clock of the apb peripherals in Hz
+pub const PCLK_HZ: u32 = rcc_pclk_hz(&RCC_CFG);
+///clock of the timers in Hz
+pub const TIM_CLK_HZ: u32 = rcc_tim_clk_hz(&RCC_CFG);
 
 ///Mcu/cpu operation frequency in Hz; 8 MHz -> 8_000_000
-pub const F_CPU_HZ: i32 = 8_000_000;
+pub const F_CPU_HZ: i32 = HCLK_HZ as i32;
+
+///flash wait states
+const FLASH_LATENCY: u32 = rcc_flash_latency(&RCC_CFG);
+
+///number of register polls until a clock is reported as not ready
+const RCC_TIMEOUT_CNT: u32 = 100_000;
+
+const RCC_CR_HSEON: u32 = 1 << 16;
+const RCC_CR_HSERDY: u32 = 1 << 17;
+const RCC_CR_HSEBYP: u32 = 1 << 18;
+const RCC_CR_PLLON: u32 = 1 << 24;
+const RCC_CR_PLLRDY: u32 = 1 << 25;
+const RCC_CR2_HSI48ON: u32 = 1 << 16;
+const RCC_CR2_HSI48RDY: u32 = 1 << 17;
+
+const RCC_CFGR_SW_MSK: u32 = 0x3;
+const RCC_CFGR_SWS_POS: u32 = 2;
+const RCC_CFGR_HPRE_POS: u32 = 4;
+const RCC_CFGR_PPRE_POS: u32 = 8;
+const RCC_CFGR_PRE_MSK: u32 = (0xF << RCC_CFGR_HPRE_POS) | (0x7 << RCC_CFGR_PPRE_POS);
+const RCC_CFGR_PLLSRC_POS: u32 = 15;
+const RCC_CFGR_PLLMUL_POS: u32 = 18;
+const RCC_CFGR_PLL_MSK: u32 = (0x3 << RCC_CFGR_PLLSRC_POS) | (0xF << RCC_CFGR_PLLMUL_POS);
+const RCC_CFGR2_PREDIV_MSK: u32 = 0xF;
+
+const RCC_CSR_RMVF: u32 = 1 << 24;
+const RCC_CSR_OBLRSTF: u32 = 1 << 25;
+const RCC_CSR_PINRSTF: u32 = 1 << 26;
+const RCC_CSR_PORRSTF: u32 = 1 << 27;
+const RCC_CSR_SFTRSTF: u32 = 1 << 28;
+const RCC_CSR_IWDGRSTF: u32 = 1 << 29;
+const RCC_CSR_WWDGRSTF: u32 = 1 << 30;
+const RCC_CSR_LPWRRSTF: u32 = 1 << 31;
+
+const FLASH_ACR_LATENCY_MSK: u32 = 0x7;
+const FLASH_ACR_PRFTBE: u32 = 1 << 4;
 
 //---------------------------------------------------------------------------------------------------------------------
 // Enums
 //---------------------------------------------------------------------------------------------------------------------
+///system clock source; the values match the SW bits of CFGR
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SysClk {
+    Hsi = 0,
+    Hse = 1,
+    Pll = 2,
+    Hsi48 = 3,
+}
+
+///pll input; the values match the PLLSRC bits of CFGR; except HsiDiv2 the input is divided by the pll_prediv
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum PllSrc {
+    HsiDiv2 = 0,
+    Hsi = 1,
+    Hse = 2,
+    Hsi48 = 3,
+}
+
+///ahb prescaler; the values match the HPRE bits of CFGR
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum AhbDiv {
+    Div1 = 0,
+    Div2 = 8,
+    Div4 = 9,
+    Div8 = 10,
+    Div16 = 11,
+    Div64 = 12,
+    Div128 = 13,
+    Div256 = 14,
+    Div512 = 15,
+}
+
+impl AhbDiv {
+    const fn div(self) -> u32 {
+        match self {
+            AhbDiv::Div1 => 1,
+            AhbDiv::Div2 => 2,
+            AhbDiv::Div4 => 4,
+            AhbDiv::Div8 => 8,
+            AhbDiv::Div16 => 16,
+            AhbDiv::Div64 => 64,
+            AhbDiv::Div128 => 128,
+            AhbDiv::Div256 => 256,
+            AhbDiv::Div512 => 512,
+        }
+    }
+}
+
+///apb prescaler; the values match the PPRE bits of CFGR
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum ApbDiv {
+    Div1 = 0,
+    Div2 = 4,
+    Div4 = 5,
+    Div8 = 6,
+    Div16 = 7,
+}
+
+impl ApbDiv {
+    const fn div(self) -> u32 {
+        match self {
+            ApbDiv::Div1 => 1,
+            ApbDiv::Div2 => 2,
+            ApbDiv::Div4 => 4,
+            ApbDiv::Div8 => 8,
+            ApbDiv::Div16 => 16,
+        }
+    }
+}
+
+///cause of the last reset
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum ResetCause {
+    PowerOn,
+    Pin,
+    Software,
+    IndependentWatchdog,
+    WindowWatchdog,
+    LowPower,
+    OptionByteLoad,
+    Unknown,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum RccError {
+    HseNotReady,
+    Hsi48NotReady,
+    PllNotReady,
+    SwitchFailed,
+}
 
 //---------------------------------------------------------------------------------------------------------------------
 // Structs
 //---------------------------------------------------------------------------------------------------------------------
+///clock tree configuration
+#[derive(Copy, Clone)]
+struct RccCfg {
+    sysclk: SysClk,
+    ///frequency of the crystal or external clock; 0 if no hse is used
+    hse_hz: u32,
+    ///external clock instead of a crystal
+    hse_bypass: bool,
+    pll_src: PllSrc,
+    ///pll multiplier 2 - 16
+    pll_mul: u32,
+    ///pll input divider 1 - 16
+    pll_prediv: u32,
+    ahb_div: AhbDiv,
+    apb_div: ApbDiv,
+}
+
+impl RccCfg {
+    const fn uses_pll(&self) -> bool {
+        matches!(self.sysclk, SysClk::Pll)
+    }
+
+    const fn uses_hse(&self) -> bool {
+        matches!(self.sysclk, SysClk::Hse) || (self.uses_pll() && matches!(self.pll_src, PllSrc::Hse))
+    }
+
+    const fn uses_hsi48(&self) -> bool {
+        matches!(self.sysclk, SysClk::Hsi48) || (self.uses_pll() && matches!(self.pll_src, PllSrc::Hsi48))
+    }
+}
 
 /// register structure for Rcc module registers; need C representation for correct memory layout
 #[repr(C)]
@@ -87,3 +289,257 @@ impl Rcc {
 //---------------------------------------------------------------------------------------------------------------------
 // Functions
 //---------------------------------------------------------------------------------------------------------------------
+
+///get the pll input frequency of the configuration
+const fn rcc_pll_in_hz(cfg: &RccCfg) -> u32 {
+    match cfg.pll_src {
+        PllSrc::HsiDiv2 => HSI_HZ / 2,
+        PllSrc::Hsi => HSI_HZ / cfg.pll_prediv,
+        PllSrc::Hse => cfg.hse_hz / cfg.pll_prediv,
+        PllSrc::Hsi48 => HSI48_HZ / cfg.pll_prediv,
+    }
+}
+
+///get the system clock frequency of the configuration
+const fn rcc_sysclk_hz(cfg: &RccCfg) -> u32 {
+    match cfg.sysclk {
+        SysClk::Hsi => HSI_HZ,
+        SysClk::Hse => cfg.hse_hz,
+        SysClk::Pll => rcc_pll_in_hz(cfg) * cfg.pll_mul,
+        SysClk::Hsi48 => HSI48_HZ,
+    }
+}
+
+///get the clock of the core, the memories and the ahb peripherals of the configuration
+const fn rcc_hclk_hz(cfg: &RccCfg) -> u32 {
+    rcc_sysclk_hz(cfg) / cfg.ahb_div.div()
+}
+
+///get the clock of the apb peripherals of the configuration
+const fn rcc_pclk_hz(cfg: &RccCfg) -> u32 {
+    rcc_hclk_hz(cfg) / cfg.apb_div.div()
+}
+
+///get the clock of the timers of the configuration; the timer clock is doubled if the apb prescaler is not 1
+const fn rcc_tim_clk_hz(cfg: &RccCfg) -> u32 {
+    match cfg.apb_div {
+        ApbDiv::Div1 => rcc_pclk_hz(cfg),
+        _ => 2 * rcc_pclk_hz(cfg),
+    }
+}
+
+///get the flash wait states of the configuration; one wait state above 24 MHz system clock
+const fn rcc_flash_latency(cfg: &RccCfg) -> u32 {
+    if rcc_sysclk_hz(cfg) > 24_000_000 {
+        1
+    } else {
+        0
+    }
+}
+
+///poll the register until the bits of the mask have the given state; false on timeout
+fn rcc_wait(reg: &u32, msk: u32, set: bool) -> bool {
+    let mut cnt: u32 = 0;
+    while cnt < RCC_TIMEOUT_CNT {
+        if ((get_reg(reg) & msk) != 0) == set {
+            return true;
+        }
+        cnt += 1;
+    }
+    false
+}
+
+///configure the clock tree by RCC_CFG; has to be called first, before any peripheral is configured; on error the
+///system clock stays at the hsi
+pub fn init() -> Result<(), RccError> {
+    let inst: &mut Rcc = Rcc::inst();
+
+    if RCC_CFG.uses_hse() {
+        if RCC_CFG.hse_bypass {
+            set_reg_bitmsk(&mut inst.cr, RCC_CR_HSEBYP);
+        }
+        set_reg_bitmsk(&mut inst.cr, RCC_CR_HSEON);
+        if !rcc_wait(&inst.cr, RCC_CR_HSERDY, true) {
+            return Err(RccError::HseNotReady);
+        }
+    }
+    if RCC_CFG.uses_hsi48() {
+        set_reg_bitmsk(&mut inst.cr2, RCC_CR2_HSI48ON);
+        if !rcc_wait(&inst.cr2, RCC_CR2_HSI48RDY, true) {
+            return Err(RccError::Hsi48NotReady);
+        }
+    }
+    if RCC_CFG.uses_pll() {
+        //the pll can only be configured while it is off
+        clr_reg_bitmsk(&mut inst.cr, RCC_CR_PLLON);
+        if !rcc_wait(&inst.cr, RCC_CR_PLLRDY, false) {
+            return Err(RccError::PllNotReady);
+        }
+        modify_reg_bits(
+            &mut inst.cfgr,
+            RCC_CFGR_PLL_MSK,
+            ((RCC_CFG.pll_src as u32) << RCC_CFGR_PLLSRC_POS) | ((RCC_CFG.pll_mul - 2) << RCC_CFGR_PLLMUL_POS),
+        );
+        modify_reg_bits(&mut inst.cfgr2, RCC_CFGR2_PREDIV_MSK, RCC_CFG.pll_prediv - 1);
+        set_reg_bitmsk(&mut inst.cr, RCC_CR_PLLON);
+        if !rcc_wait(&inst.cr, RCC_CR_PLLRDY, true) {
+            return Err(RccError::PllNotReady);
+        }
+    }
+
+    //the wait states are set before the switch; during the switch more wait states than needed are no problem
+    modify_reg_bits(
+        &mut Flash::inst().acr,
+        FLASH_ACR_LATENCY_MSK | FLASH_ACR_PRFTBE,
+        FLASH_LATENCY | FLASH_ACR_PRFTBE,
+    );
+    modify_reg_bits(
+        &mut inst.cfgr,
+        RCC_CFGR_PRE_MSK,
+        ((RCC_CFG.ahb_div as u32) << RCC_CFGR_HPRE_POS) | ((RCC_CFG.apb_div as u32) << RCC_CFGR_PPRE_POS),
+    );
+    modify_reg_bits(&mut inst.cfgr, RCC_CFGR_SW_MSK, RCC_CFG.sysclk as u32);
+    let mut cnt: u32 = 0;
+    while ((get_reg(&inst.cfgr) >> RCC_CFGR_SWS_POS) & RCC_CFGR_SW_MSK) != RCC_CFG.sysclk as u32 {
+        cnt += 1;
+        if cnt >= RCC_TIMEOUT_CNT {
+            return Err(RccError::SwitchFailed);
+        }
+    }
+    Ok(())
+}
+
+///decode the reset flags; the pin flag is also set by all internal resets, so it is checked last
+const fn rcc_decode_reset_cause(csr: u32) -> ResetCause {
+    if (csr & RCC_CSR_PORRSTF) != 0 {
+        ResetCause::PowerOn
+    } else if (csr & RCC_CSR_LPWRRSTF) != 0 {
+        ResetCause::LowPower
+    } else if (csr & RCC_CSR_IWDGRSTF) != 0 {
+        ResetCause::IndependentWatchdog
+    } else if (csr & RCC_CSR_WWDGRSTF) != 0 {
+        ResetCause::WindowWatchdog
+    } else if (csr & RCC_CSR_SFTRSTF) != 0 {
+        ResetCause::Software
+    } else if (csr & RCC_CSR_OBLRSTF) != 0 {
+        ResetCause::OptionByteLoad
+    } else if (csr & RCC_CSR_PINRSTF) != 0 {
+        ResetCause::Pin
+    } else {
+        ResetCause::Unknown
+    }
+}
+
+///read and clear the reset flags and store the cause in the no-init ram; must be called once at the start of the
+///reset handler; uses no initialized ram
+pub fn rcc_store_reset_cause() {
+    let inst: &mut Rcc = Rcc::inst();
+    let cause: ResetCause = rcc_decode_reset_cause(get_reg(&inst.csr));
+    set_reg_bitmsk(&mut inst.csr, RCC_CSR_RMVF);
+    unsafe { ptr::write_volatile(&raw mut RCC_RESET_CAUSE, cause) };
+}
+
+///get the cause of the last reset
+pub fn get_reset_cause() -> ResetCause {
+    unsafe { ptr::read_volatile(&raw const RCC_RESET_CAUSE) }
+}
+
+///check that the configuration is in the limits of the datasheet
+const fn rcc_check_cfg(cfg: &RccCfg) {
+    assert!(!cfg.uses_hse() || (cfg.hse_hz >= 4_000_000 && cfg.hse_hz <= 32_000_000));
+    if cfg.uses_pll() {
+        assert!(cfg.pll_mul >= 2 && cfg.pll_mul <= 16);
+        assert!(cfg.pll_prediv >= 1 && cfg.pll_prediv <= 16);
+        assert!(rcc_pll_in_hz(cfg) >= 1_000_000 && rcc_pll_in_hz(cfg) <= 24_000_000);
+        assert!(rcc_sysclk_hz(cfg) >= 16_000_000);
+    }
+    assert!(rcc_sysclk_hz(cfg) <= 48_000_000);
+}
+
+const _: () = rcc_check_cfg(&RCC_CFG);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///8 MHz from the hsi
+    const RCC_CFG_HSI: RccCfg = RccCfg {
+        sysclk: SysClk::Hsi,
+        hse_hz: 0,
+        hse_bypass: false,
+        pll_src: PllSrc::HsiDiv2,
+        pll_mul: 12,
+        pll_prediv: 1,
+        ahb_div: AhbDiv::Div1,
+        apb_div: ApbDiv::Div1,
+    };
+
+    ///48 MHz from the hsi by the pll
+    const RCC_CFG_PLL_HSI: RccCfg = RccCfg {
+        sysclk: SysClk::Pll,
+        ..RCC_CFG_HSI
+    };
+
+    ///48 MHz from an 8 MHz crystal by the pll; apb at 24 MHz
+    const RCC_CFG_PLL_HSE: RccCfg = RccCfg {
+        sysclk: SysClk::Pll,
+        hse_hz: 8_000_000,
+        hse_bypass: false,
+        pll_src: PllSrc::Hse,
+        pll_mul: 12,
+        pll_prediv: 2,
+        ahb_div: AhbDiv::Div1,
+        apb_div: ApbDiv::Div2,
+    };
+
+    ///48 MHz from the hsi48; ahb at 24 MHz
+    const RCC_CFG_HSI48: RccCfg = RccCfg {
+        sysclk: SysClk::Hsi48,
+        ahb_div: AhbDiv::Div2,
+        ..RCC_CFG_HSI
+    };
+
+    const _: () = rcc_check_cfg(&RCC_CFG_HSI);
+    const _: () = rcc_check_cfg(&RCC_CFG_PLL_HSI);
+    const _: () = rcc_check_cfg(&RCC_CFG_PLL_HSE);
+    const _: () = rcc_check_cfg(&RCC_CFG_HSI48);
+
+    ///get sysclk, hclk, pclk, timer clock and flash wait states of the configuration
+    fn clocks(cfg: &RccCfg) -> (u32, u32, u32, u32, u32) {
+        (
+            rcc_sysclk_hz(cfg),
+            rcc_hclk_hz(cfg),
+            rcc_pclk_hz(cfg),
+            rcc_tim_clk_hz(cfg),
+            rcc_flash_latency(cfg),
+        )
+    }
+
+    #[test]
+    fn clocks_of_hsi() {
+        assert_eq!(clocks(&RCC_CFG_HSI), (8_000_000, 8_000_000, 8_000_000, 8_000_000, 0));
+    }
+
+    #[test]
+    fn clocks_of_pll_from_hsi() {
+        assert_eq!(clocks(&RCC_CFG_PLL_HSI), (48_000_000, 48_000_000, 48_000_000, 48_000_000, 1));
+    }
+
+    #[test]
+    fn clocks_of_pll_from_hse() {
+        assert_eq!(rcc_pll_in_hz(&RCC_CFG_PLL_HSE), 4_000_000);
+        assert_eq!(clocks(&RCC_CFG_PLL_HSE), (48_000_000, 48_000_000, 24_000_000, 48_000_000, 1));
+    }
+
+    #[test]
+    fn clocks_of_hsi48() {
+        //the wait states follow the system clock, not the ahb clock
+        assert_eq!(clocks(&RCC_CFG_HSI48), (48_000_000, 24_000_000, 24_000_000, 24_000_000, 1));
+    }
+
+    #[test]
+    fn clocks_of_configuration() {
+        assert_eq!(clocks(&RCC_CFG), (SYSCLK_HZ, HCLK_HZ, PCLK_HZ, TIM_CLK_HZ, FLASH_LATENCY));
+        assert_eq!(F_CPU_HZ as u32, HCLK_HZ);
+    }
+}
@@ -0,0 +1,527 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : rtc.rs
+//!
+//! # Short description
+//! Rtc module implementation with calendar, alarm A and backup registers
+//!
+//! # Detailed description
+//! The rtc is clocked by the LSE (32.768 kHz crystal or external clock with bypass) or the LSI (about 40 kHz, not
+//! accurate) selected by RTC_CLK in Rcc bdcr; the prescalers are set for a calendar clock of 1 Hz (about 1 Hz for
+//! the LSI).
+//! The rtc is part of the backup domain, so the calendar keeps running over resets (and in Stop / Standby): init
+//! only configures the rtc if it is not already running with the configured clock; the write access to the backup
+//! domain stays enabled after init.
+//! The calendar is set and read as RtcDateTime in binary values; the BCD format of the registers is handled
+//! internally. The time is read with the subseconds in ms from the shadow registers, which are only valid after the
+//! synchronization flag RSF is set: after a system reset, init waits for the synchronization of a running rtc, after
+//! the wakeup from Stop pwr_enter_stop calls rtc_sync_shadow; rtc_get_datetime returns NotSynchronized before.
+//! Alarm A compares day, hour, minute and second; a field with None is not compared. The alarm is polled with
+//! rtc_is_alarm_a; the alarm flag also is the EXTI line 17 event, which can wake the Mcu from Stop (see pwr).
+//! The backup registers keep their content over resets and in Standby; they are cleared by a backup domain reset.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::pwr;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::modify_reg_bits;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///clock source of the rtc
+const RTC_CLK: RtcClk = RtcClk::Lse { bypass: false };
+
+///asynchronous prescaler; the highest value for the lowest current consumption
+const RTC_PREDIV_A: u32 = 127;
+///synchronous prescaler for the 1 Hz calendar clock; is also the subsecond resolution
+const RTC_PREDIV_S: u32 = RTC_CLK.freq_hz() / (RTC_PREDIV_A + 1) - 1;
+
+///number of register polls until the rtc is reported as not ready; the LSE needs up to 2 s to start
+const RTC_LSE_TIMEOUT_CNT: u32 = 2_000_000;
+const RTC_TIMEOUT_CNT: u32 = 100_000;
+
+///number of backup registers
+pub const RTC_BKP_NUM: usize = 5;
+
+const RTC_WPR_KEY1: u32 = 0xCA;
+const RTC_WPR_KEY2: u32 = 0x53;
+const RTC_WPR_LOCK: u32 = 0xFF;
+
+const RTC_CR_ALRAE: u32 = 1 << 8;
+
+const RTC_ISR_ALRAWF: u32 = 1 << 0;
+const RTC_ISR_INITS: u32 = 1 << 4;
+const RTC_ISR_RSF: u32 = 1 << 5;
+const RTC_ISR_INITF: u32 = 1 << 6;
+const RTC_ISR_INIT: u32 = 1 << 7;
+const RTC_ISR_ALRAF: u32 = 1 << 8;
+
+const RTC_ALRMAR_MSK_SECOND: u32 = 1 << 7;
+const RTC_ALRMAR_MSK_MINUTE: u32 = 1 << 15;
+const RTC_ALRMAR_MSK_HOUR: u32 = 1 << 23;
+const RTC_ALRMAR_MSK_DAY: u32 = 1 << 31;
+
+const RCC_BDCR_LSEON: u32 = 1 << 0;
+const RCC_BDCR_LSERDY: u32 = 1 << 1;
+const RCC_BDCR_LSEBYP: u32 = 1 << 2;
+const RCC_BDCR_RTCSEL_POS: u32 = 8;
+const RCC_BDCR_RTCSEL_MSK: u32 = 0x3 << RCC_BDCR_RTCSEL_POS;
+const RCC_BDCR_RTCEN: u32 = 1 << 15;
+const RCC_BDCR_BDRST: u32 = 1 << 16;
+const RCC_CSR_LSION: u32 = 1 << 0;
+const RCC_CSR_LSIRDY: u32 = 1 << 1;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum RtcClk {
+    Lse { bypass: bool },
+    Lsi,
+}
+
+impl RtcClk {
+    const fn freq_hz(self) -> u32 {
+        match self {
+            RtcClk::Lse { .. } => 32_768,
+            RtcClk::Lsi => 40_000,
+        }
+    }
+
+    ///get the RTCSEL value of Rcc bdcr
+    const fn rtcsel(self) -> u32 {
+        match self {
+            RtcClk::Lse { .. } => 1,
+            RtcClk::Lsi => 2,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum RtcError {
+    ClockNotReady,
+    InitTimeout,
+    InvalidDateTime,
+    NotSet,
+    NotSynchronized,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for rtc registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Rtc {
+    pub tr: u32,
+    pub dr: u32,
+    pub cr: u32,
+    pub isr: u32,
+    pub prer: u32,
+    pub reserved0: [u32; 2],
+    pub alrmar: u32,
+    pub reserved1: u32,
+    pub wpr: u32,
+    pub ssr: u32,
+    pub shiftr: u32,
+    pub tstr: u32,
+    pub tsdr: u32,
+    pub tsssr: u32,
+    pub calr: u32,
+    pub tafcr: u32,
+    pub alrmassr: u32,
+    pub reserved2: [u32; 2],
+    pub bkpr: [u32; RTC_BKP_NUM],
+}
+
+impl Rtc {
+    /// method to the instance of Rtc
+    pub fn inst() -> &'static mut Rtc {
+        unsafe { &mut *(0x4000_2800 as *mut Rtc) }
+    }
+
+    fn unlock(&mut self) {
+        set_reg(&mut self.wpr, RTC_WPR_KEY1);
+        set_reg(&mut self.wpr, RTC_WPR_KEY2);
+    }
+
+    fn lock(&mut self) {
+        set_reg(&mut self.wpr, RTC_WPR_LOCK);
+    }
+
+    ///clear flags of ISR; the flags are cleared by writing 0, the INIT bit is kept
+    fn clr_isr_flags(&mut self, flags: u32) {
+        let init: u32 = get_reg(&self.isr) & RTC_ISR_INIT;
+        set_reg(&mut self.isr, !(flags | RTC_ISR_INIT) | init);
+    }
+}
+
+///calendar date and time; year 2000 - 2099, weekday 1 (monday) - 7 (sunday)
+#[derive(PartialEq, Copy, Clone)]
+pub struct RtcDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub weekday: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl RtcDateTime {
+    const fn is_valid(&self) -> bool {
+        self.year >= 2000
+            && self.year <= 2099
+            && self.month >= 1
+            && self.month <= 12
+            && self.day >= 1
+            && self.day <= rtc_days_of_month(self.year, self.month)
+            && self.weekday >= 1
+            && self.weekday <= 7
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+    }
+
+    ///get the seconds since 01.01.2000 00:00:00
+    #[allow(dead_code)]
+    pub const fn get_secs_since_2000(&self) -> u32 {
+        let mut days: u32 = 0;
+        let mut year: u16 = 2000;
+        while year < self.year {
+            days += if rtc_is_leap_year(year) { 366 } else { 365 };
+            year += 1;
+        }
+        let mut month: u8 = 1;
+        while month < self.month {
+            days += rtc_days_of_month(self.year, month) as u32;
+            month += 1;
+        }
+        days += self.day as u32 - 1;
+        ((days * 24 + self.hour as u32) * 60 + self.minute as u32) * 60 + self.second as u32
+    }
+}
+
+///alarm compare values; a field with None is not compared
+#[derive(PartialEq, Copy, Clone)]
+pub struct RtcAlarm {
+    pub day: Option<u8>,
+    pub hour: Option<u8>,
+    pub minute: Option<u8>,
+    pub second: Option<u8>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+const fn rtc_is_leap_year(year: u16) -> bool {
+    //all years divisible by 4 in 2000 - 2099 are leap years
+    year.is_multiple_of(4)
+}
+
+const fn rtc_days_of_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 => {
+            if rtc_is_leap_year(year) {
+                29
+            } else {
+                28
+            }
+        }
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+const fn rtc_to_bcd(value: u8) -> u32 {
+    (((value / 10) << 4) | (value % 10)) as u32
+}
+
+const fn rtc_from_bcd(bcd: u32) -> u8 {
+    ((bcd >> 4) * 10 + (bcd & 0xF)) as u8
+}
+
+///poll the register until the bits of the mask are set; false on timeout
+fn rtc_wait(reg: &u32, msk: u32, cnt_max: u32) -> bool {
+    let mut cnt: u32 = 0;
+    while cnt < cnt_max {
+        if (get_reg(reg) & msk) == msk {
+            return true;
+        }
+        cnt += 1;
+    }
+    false
+}
+
+///start the rtc clock source and enable the rtc; a running rtc with the configured clock is kept
+pub fn init() -> Result<(), RtcError> {
+    pwr::pwr_set_backup_access(true);
+    let rcc: &mut Rcc = Rcc::inst();
+
+    let bdcr: u32 = get_reg(&rcc.bdcr);
+    let rtcsel: u32 = (bdcr & RCC_BDCR_RTCSEL_MSK) >> RCC_BDCR_RTCSEL_POS;
+    if (bdcr & RCC_BDCR_RTCEN) != 0 && rtcsel == RTC_CLK.rtcsel() {
+        if let RtcClk::Lsi = RTC_CLK {
+            //the lsi is not part of the backup domain and is off after reset
+            set_reg_bitmsk(&mut rcc.csr, RCC_CSR_LSION);
+        }
+        return rtc_sync_shadow();
+    }
+
+    //the clock source can only be changed by a backup domain reset
+    if rtcsel != 0 && rtcsel != RTC_CLK.rtcsel() {
+        set_reg_bitmsk(&mut rcc.bdcr, RCC_BDCR_BDRST);
+        clr_reg_bitmsk(&mut rcc.bdcr, RCC_BDCR_BDRST);
+    }
+
+    match RTC_CLK {
+        RtcClk::Lse { bypass } => {
+            if bypass {
+                set_reg_bitmsk(&mut rcc.bdcr, RCC_BDCR_LSEBYP);
+            }
+            set_reg_bitmsk(&mut rcc.bdcr, RCC_BDCR_LSEON);
+            if !rtc_wait(&rcc.bdcr, RCC_BDCR_LSERDY, RTC_LSE_TIMEOUT_CNT) {
+                return Err(RtcError::ClockNotReady);
+            }
+        }
+        RtcClk::Lsi => {
+            set_reg_bitmsk(&mut rcc.csr, RCC_CSR_LSION);
+            if !rtc_wait(&rcc.csr, RCC_CSR_LSIRDY, RTC_TIMEOUT_CNT) {
+                return Err(RtcError::ClockNotReady);
+            }
+        }
+    }
+    modify_reg_bits(
+        &mut rcc.bdcr,
+        RCC_BDCR_RTCSEL_MSK,
+        RTC_CLK.rtcsel() << RCC_BDCR_RTCSEL_POS,
+    );
+    set_reg_bitmsk(&mut rcc.bdcr, RCC_BDCR_RTCEN);
+
+    let inst: &mut Rtc = Rtc::inst();
+    inst.unlock();
+    let result: Result<(), RtcError> = rtc_enter_init(inst);
+    if result.is_ok() {
+        set_reg(&mut inst.prer, RTC_PREDIV_S);
+        set_reg(&mut inst.prer, (RTC_PREDIV_A << 16) | RTC_PREDIV_S);
+        rtc_exit_init(inst);
+    }
+    inst.lock();
+    result
+}
+
+///enter the init mode, in which the calendar is stopped and can be written; the rtc must be unlocked
+fn rtc_enter_init(inst: &mut Rtc) -> Result<(), RtcError> {
+    set_reg_bitmsk(&mut inst.isr, RTC_ISR_INIT);
+    if rtc_wait(&inst.isr, RTC_ISR_INITF, RTC_TIMEOUT_CNT) {
+        Ok(())
+    } else {
+        clr_reg_bitmsk(&mut inst.isr, RTC_ISR_INIT);
+        Err(RtcError::InitTimeout)
+    }
+}
+
+///leave the init mode; the calendar restarts
+fn rtc_exit_init(inst: &mut Rtc) {
+    clr_reg_bitmsk(&mut inst.isr, RTC_ISR_INIT);
+}
+
+///clear the synchronization flag and wait until the shadow registers are updated from the calendar; the rtc must be
+///unlocked; false on timeout
+fn rtc_wait_sync(inst: &mut Rtc) -> bool {
+    inst.clr_isr_flags(RTC_ISR_RSF);
+    rtc_wait(&inst.isr, RTC_ISR_RSF, RTC_TIMEOUT_CNT)
+}
+
+///synchronize the shadow registers with the calendar; must be called after the wakeup from Stop, because the shadow
+///registers are not updated in Stop; nothing is done if the rtc is not enabled
+pub fn rtc_sync_shadow() -> Result<(), RtcError> {
+    if (get_reg(&Rcc::inst().bdcr) & RCC_BDCR_RTCEN) == 0 {
+        return Ok(());
+    }
+    let inst: &mut Rtc = Rtc::inst();
+    inst.unlock();
+    let synced: bool = rtc_wait_sync(inst);
+    inst.lock();
+    if synced {
+        Ok(())
+    } else {
+        Err(RtcError::NotSynchronized)
+    }
+}
+
+///set the calendar date and time; the subseconds restart at 0
+#[allow(dead_code)]
+pub fn rtc_set_datetime(datetime: &RtcDateTime) -> Result<(), RtcError> {
+    if !datetime.is_valid() {
+        return Err(RtcError::InvalidDateTime);
+    }
+    let tr: u32 = (rtc_to_bcd(datetime.hour) << 16) | (rtc_to_bcd(datetime.minute) << 8) | rtc_to_bcd(datetime.second);
+    let dr: u32 = (rtc_to_bcd((datetime.year - 2000) as u8) << 16)
+        | ((datetime.weekday as u32) << 13)
+        | (rtc_to_bcd(datetime.month) << 8)
+        | rtc_to_bcd(datetime.day);
+
+    let inst: &mut Rtc = Rtc::inst();
+    inst.unlock();
+    let mut result: Result<(), RtcError> = rtc_enter_init(inst);
+    if result.is_ok() {
+        set_reg(&mut inst.tr, tr);
+        set_reg(&mut inst.dr, dr);
+        rtc_exit_init(inst);
+        //the shadow registers are updated with the new calendar at the next rtc clock
+        if !rtc_wait_sync(inst) {
+            result = Err(RtcError::InitTimeout);
+        }
+    }
+    inst.lock();
+    result
+}
+
+///get the calendar date and time and the subseconds in ms; NotSet if the calendar was not set since the last backup
+///domain reset, NotSynchronized if the shadow registers are not synchronized
+#[allow(dead_code)]
+pub fn rtc_get_datetime() -> Result<(RtcDateTime, u16), RtcError> {
+    let inst: &mut Rtc = Rtc::inst();
+    let isr: u32 = get_reg(&inst.isr);
+    if (isr & RTC_ISR_INITS) == 0 {
+        return Err(RtcError::NotSet);
+    }
+    if (isr & RTC_ISR_RSF) == 0 {
+        return Err(RtcError::NotSynchronized);
+    }
+    //reading SSR locks TR and DR until DR is read, so the values are consistent
+    let ssr: u32 = get_reg(&inst.ssr);
+    let tr: u32 = get_reg(&inst.tr);
+    let dr: u32 = get_reg(&inst.dr);
+
+    let datetime: RtcDateTime = RtcDateTime {
+        year: 2000 + rtc_from_bcd((dr >> 16) & 0xFF) as u16,
+        month: rtc_from_bcd((dr >> 8) & 0x1F),
+        day: rtc_from_bcd(dr & 0x3F),
+        weekday: ((dr >> 13) & 0x7) as u8,
+        hour: rtc_from_bcd((tr >> 16) & 0x3F),
+        minute: rtc_from_bcd((tr >> 8) & 0x7F),
+        second: rtc_from_bcd(tr & 0x7F),
+    };
+    //the subsecond counter counts down from RTC_PREDIV_S
+    let subsec_ms: u32 = (RTC_PREDIV_S - ssr.min(RTC_PREDIV_S)) * 1000 / (RTC_PREDIV_S + 1);
+    Ok((datetime, subsec_ms as u16))
+}
+
+///set and enable alarm A; a pending alarm flag is cleared
+#[allow(dead_code)]
+pub fn rtc_set_alarm_a(alarm: &RtcAlarm) -> Result<(), RtcError> {
+    let mut alrmar: u32 = 0;
+    match alarm.day {
+        Some(day) if (1..=31).contains(&day) => alrmar |= rtc_to_bcd(day) << 24,
+        Some(_) => return Err(RtcError::InvalidDateTime),
+        None => alrmar |= RTC_ALRMAR_MSK_DAY,
+    }
+    match alarm.hour {
+        Some(hour) if hour < 24 => alrmar |= rtc_to_bcd(hour) << 16,
+        Some(_) => return Err(RtcError::InvalidDateTime),
+        None => alrmar |= RTC_ALRMAR_MSK_HOUR,
+    }
+    match alarm.minute {
+        Some(minute) if minute < 60 => alrmar |= rtc_to_bcd(minute) << 8,
+        Some(_) => return Err(RtcError::InvalidDateTime),
+        None => alrmar |= RTC_ALRMAR_MSK_MINUTE,
+    }
+    match alarm.second {
+        Some(second) if second < 60 => alrmar |= rtc_to_bcd(second),
+        Some(_) => return Err(RtcError::InvalidDateTime),
+        None => alrmar |= RTC_ALRMAR_MSK_SECOND,
+    }
+
+    let inst: &mut Rtc = Rtc::inst();
+    inst.unlock();
+    clr_reg_bitmsk(&mut inst.cr, RTC_CR_ALRAE);
+    let result: Result<(), RtcError> = if rtc_wait(&inst.isr, RTC_ISR_ALRAWF, RTC_TIMEOUT_CNT) {
+        set_reg(&mut inst.alrmar, alrmar);
+        //the subseconds are not compared
+        set_reg(&mut inst.alrmassr, 0);
+        inst.clr_isr_flags(RTC_ISR_ALRAF);
+        set_reg_bitmsk(&mut inst.cr, RTC_CR_ALRAE);
+        Ok(())
+    } else {
+        Err(RtcError::InitTimeout)
+    };
+    inst.lock();
+    result
+}
+
+///disable alarm A
+#[allow(dead_code)]
+pub fn rtc_disable_alarm_a() {
+    let inst: &mut Rtc = Rtc::inst();
+    inst.unlock();
+    clr_reg_bitmsk(&mut inst.cr, RTC_CR_ALRAE);
+    inst.lock();
+}
+
+///true if alarm A occurred since the last call; the alarm flag is cleared
+#[allow(dead_code)]
+pub fn rtc_is_alarm_a() -> bool {
+    let inst: &mut Rtc = Rtc::inst();
+    if (get_reg(&inst.isr) & RTC_ISR_ALRAF) != 0 {
+        inst.clr_isr_flags(RTC_ISR_ALRAF);
+        true
+    } else {
+        false
+    }
+}
+
+///write the backup register; idx 0 - RTC_BKP_NUM - 1
+#[allow(dead_code)]
+pub fn rtc_write_backup(idx: usize, value: u32) {
+    set_reg(&mut Rtc::inst().bkpr[idx], value);
+}
+
+///read the backup register; idx 0 - RTC_BKP_NUM - 1
+#[allow(dead_code)]
+pub fn rtc_read_backup(idx: usize) -> u32 {
+    get_reg(&Rtc::inst().bkpr[idx])
+}
+
+const _: () = assert!(RTC_PREDIV_S <= 0x7FFF);
@@ -0,0 +1,608 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : spi.rs
+//!
+//! # Short description
+//! Spi module implementation for SPI1 and SPI2 in master mode
+//!
+//! # Detailed description
+//! The driver works poll based without interrupts. The connected devices are configured by the SPI_DEVICE_CFG table
+//! (index is the SpiDevice); each device defines its bus, spi mode, frame size, prescaler and the gpio pin used as
+//! chip select. The chip select is handled by software, so any gpio pin can be used.
+//! A transfer is done as job: spi_start_transfer copies the transmit frames to the job buffer of the bus, configures
+//! the bus for the device and selects the device. spi_task advances the job by at most SPI_FRAMES_PER_CALL frames per
+//! call and deselects the device when all frames are transferred. One frame is in transfer at a time and the task
+//! waits for each received frame, as long as the frames of the call fit into SPI_WAIT_TIME_MAX_US; a device with
+//! slower frames transfers one frame per call without waiting. The received frames can be fetched with
+//! spi_take_result afterwards. Only one job per bus can be active; a job of another device on the same bus is
+//! rejected with Busy.
+//! For 8 bit frames only the lower byte of a frame is transferred.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Ospeed;
+use crate::mcal::gpio::Pin;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::rcc::HCLK_HZ;
+use crate::mcal::rcc::PCLK_HZ;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+use crate::rte::RteData;
+use core::ptr;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const SPI_CR1_MSTR: u32 = 1 << 2;
+const SPI_CR1_BR_POS: u32 = 3;
+const SPI_CR1_SPE: u32 = 1 << 6;
+const SPI_CR1_LSBFIRST: u32 = 1 << 7;
+const SPI_CR1_SSI: u32 = 1 << 8;
+const SPI_CR1_SSM: u32 = 1 << 9;
+
+const SPI_CR2_DS_POS: u32 = 8;
+const SPI_CR2_FRXTH: u32 = 1 << 12;
+
+const SPI_SR_RXNE: u32 = 1 << 0;
+const SPI_SR_TXE: u32 = 1 << 1;
+const SPI_SR_MODF: u32 = 1 << 5;
+const SPI_SR_OVR: u32 = 1 << 6;
+const SPI_SR_BSY: u32 = 1 << 7;
+
+///number of spi busses
+const SPI_BUS_NUM: usize = 2;
+
+///maximum number of frames of a job
+pub const SPI_JOB_BUF_SIZE: usize = 32;
+
+///maximum number of frames transferred per spi_task call and bus
+const SPI_FRAMES_PER_CALL: u32 = 4;
+
+///maximum time spi_task waits for the received frames per call and bus
+const SPI_WAIT_TIME_MAX_US: u32 = 50;
+
+///bus pin configuration; index is the SpiNo
+const SPI_BUS_CFG: [SpiBusCfg; SPI_BUS_NUM] = [
+    SpiBusCfg {
+        sck_pin: Pin::B3,
+        miso_pin: Pin::B4,
+        mosi_pin: Pin::B5,
+        af: Af::AlternateFunc0,
+    },
+    SpiBusCfg {
+        sck_pin: Pin::B13,
+        miso_pin: Pin::B14,
+        mosi_pin: Pin::B15,
+        af: Af::AlternateFunc0,
+    },
+];
+
+///number of configured devices
+const SPI_DEVICE_NUM: usize = 1;
+
+///device configuration; index is the SpiDevice
+const SPI_DEVICE_CFG: [SpiDeviceCfg; SPI_DEVICE_NUM] = [SpiDeviceCfg {
+    spi: SpiNo::Spi1,
+    mode: SpiMode::Mode0,
+    frame: FrameSize::Bits8,
+    prescaler: Prescaler::Div8,
+    lsb_first: false,
+    cs_pin: Pin::B6,
+}];
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SpiNo {
+    Spi1 = 0,
+    Spi2 = 1,
+}
+
+impl SpiNo {
+    const fn base(self) -> u32 {
+        match self {
+            SpiNo::Spi1 => 0x4001_3000,
+            SpiNo::Spi2 => 0x4000_3800,
+        }
+    }
+}
+
+///configured spi devices
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SpiDevice {
+    ExtFlash = 0,
+}
+
+///spi mode; the values match the CPOL / CPHA bits of CR1
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SpiMode {
+    ///clock idle low, data captured at the first (rising) edge
+    Mode0 = 0,
+    ///clock idle low, data captured at the second (falling) edge
+    Mode1 = 1,
+    ///clock idle high, data captured at the first (falling) edge
+    Mode2 = 2,
+    ///clock idle high, data captured at the second (rising) edge
+    Mode3 = 3,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum FrameSize {
+    Bits8,
+    Bits16,
+}
+
+///baud rate prescaler of the peripheral clock; the values match the BR bits of CR1
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum Prescaler {
+    Div2 = 0,
+    Div4 = 1,
+    Div8 = 2,
+    Div16 = 3,
+    Div32 = 4,
+    Div64 = 5,
+    Div128 = 6,
+    Div256 = 7,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SpiError {
+    Busy,
+    Length,
+    NoData,
+    Overrun,
+    ModeFault,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SpiJobState {
+    Idle,
+    Active,
+    Done,
+    Error(SpiError),
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for spi module registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Spi {
+    pub cr1: u32,
+    pub cr2: u32,
+    pub sr: u32,
+    pub dr: u32,
+    pub crcpr: u32,
+    pub rxcrcr: u32,
+    pub txcrcr: u32,
+    pub i2scfgr: u32,
+    pub i2spr: u32,
+}
+
+impl Spi {
+    /// method to the instance of the given spi
+    pub fn inst(spi: SpiNo) -> &'static mut Spi {
+        unsafe { &mut *(spi.base() as *mut Spi) }
+    }
+
+    ///configure the disabled spi as master for the device configuration; the slave select input is handled by
+    ///software, because the chip select is a gpio
+    pub fn configure(&mut self, cfg: &SpiDeviceCfg) {
+        clr_reg_bitmsk(&mut self.cr1, SPI_CR1_SPE);
+        let lsb: u32 = if cfg.lsb_first { SPI_CR1_LSBFIRST } else { 0 };
+        set_reg(
+            &mut self.cr1,
+            (cfg.mode as u32)
+                | SPI_CR1_MSTR
+                | ((cfg.prescaler as u32) << SPI_CR1_BR_POS)
+                | lsb
+                | SPI_CR1_SSI
+                | SPI_CR1_SSM,
+        );
+        //the rxne event is generated for each 8 bit frame with FRXTH
+        let cr2: u32 = match cfg.frame {
+            FrameSize::Bits8 => (7 << SPI_CR2_DS_POS) | SPI_CR2_FRXTH,
+            FrameSize::Bits16 => 15 << SPI_CR2_DS_POS,
+        };
+        set_reg(&mut self.cr2, cr2);
+    }
+
+    pub fn enable(&mut self) {
+        set_reg_bitmsk(&mut self.cr1, SPI_CR1_SPE);
+    }
+
+    pub fn disable(&mut self) {
+        clr_reg_bitmsk(&mut self.cr1, SPI_CR1_SPE);
+    }
+
+    pub fn is_busy(&self) -> bool {
+        (get_reg(&self.sr) & SPI_SR_BSY) != 0
+    }
+
+    ///write a frame if the transmit fifo has space; returns false if the frame was not written; 8 bit frames must be
+    ///written by byte access, otherwise two frames are packed into the fifo
+    pub fn try_write(&mut self, frame: FrameSize, data: u16) -> bool {
+        if (get_reg(&self.sr) & SPI_SR_TXE) == 0 {
+            return false;
+        }
+        match frame {
+            FrameSize::Bits8 => unsafe { ptr::write_volatile(&mut self.dr as *mut u32 as *mut u8, data as u8) },
+            FrameSize::Bits16 => unsafe { ptr::write_volatile(&mut self.dr as *mut u32 as *mut u16, data) },
+        }
+        true
+    }
+
+    ///read a received frame; the receive flag is polled at most polls times
+    pub fn wait_read(&mut self, frame: FrameSize, polls: u32) -> Option<u16> {
+        let mut cnt: u32 = 0;
+        while cnt < polls {
+            if let Some(data) = self.try_read(frame) {
+                return Some(data);
+            }
+            cnt += 1;
+        }
+        None
+    }
+
+    ///read a received frame if there is one
+    pub fn try_read(&mut self, frame: FrameSize) -> Option<u16> {
+        if (get_reg(&self.sr) & SPI_SR_RXNE) == 0 {
+            return None;
+        }
+        match frame {
+            FrameSize::Bits8 => Some(unsafe { ptr::read_volatile(&self.dr as *const u32 as *const u8) } as u16),
+            FrameSize::Bits16 => Some(unsafe { ptr::read_volatile(&self.dr as *const u32 as *const u16) }),
+        }
+    }
+
+    ///get a pending error; the error flags are cleared by the read sequence of the reference manual
+    pub fn get_error(&mut self) -> Option<SpiError> {
+        let sr: u32 = get_reg(&self.sr);
+        if (sr & SPI_SR_OVR) != 0 {
+            let _ = get_reg(&self.dr);
+            let _ = get_reg(&self.sr);
+            Some(SpiError::Overrun)
+        } else if (sr & SPI_SR_MODF) != 0 {
+            let cr1: u32 = get_reg(&self.cr1);
+            set_reg(&mut self.cr1, cr1);
+            Some(SpiError::ModeFault)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct SpiBusCfg {
+    sck_pin: Pin,
+    miso_pin: Pin,
+    mosi_pin: Pin,
+    af: Af,
+}
+
+#[derive(Copy, Clone)]
+pub struct SpiDeviceCfg {
+    pub spi: SpiNo,
+    pub mode: SpiMode,
+    pub frame: FrameSize,
+    pub prescaler: Prescaler,
+    pub lsb_first: bool,
+    pub cs_pin: Pin,
+}
+
+///job of a bus
+#[derive(Copy, Clone)]
+struct SpiJob {
+    device: SpiDevice,
+    state: SpiJobState,
+    buf: [u16; SPI_JOB_BUF_SIZE],
+    len: usize,
+    tx_idx: usize,
+    rx_idx: usize,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+pub struct SpiData {
+    jobs: [SpiJob; SPI_BUS_NUM],
+}
+
+impl SpiData {
+    pub const fn init() -> Self {
+        Self {
+            jobs: [SpiJob {
+                device: SpiDevice::ExtFlash,
+                state: SpiJobState::Idle,
+                buf: [0; SPI_JOB_BUF_SIZE],
+                len: 0,
+                tx_idx: 0,
+                rx_idx: 0,
+            }; SPI_BUS_NUM],
+        }
+    }
+
+    ///get the state of the job of the device; Idle if the device has no job
+    #[allow(dead_code)]
+    pub fn get_job_state(&self, device: SpiDevice) -> SpiJobState {
+        let job: &SpiJob = &self.jobs[SPI_DEVICE_CFG[device as usize].spi as usize];
+        if job.device == device {
+            job.state
+        } else {
+            SpiJobState::Idle
+        }
+    }
+}
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///init the busses and chip select pins of all configured devices; the devices are deselected
+pub fn init() {
+    //enable and reset only the busses that are used by a device
+    let mut idx: usize = 0;
+    while idx < SPI_DEVICE_NUM {
+        let cfg: &SpiDeviceCfg = &SPI_DEVICE_CFG[idx];
+        let bit: u32 = match cfg.spi {
+            SpiNo::Spi1 => 1 << 12,
+            SpiNo::Spi2 => 1 << 14,
+        };
+        let enabled: bool = match cfg.spi {
+            SpiNo::Spi1 => (get_reg(&Rcc::inst().apb2enr) & bit) != 0,
+            SpiNo::Spi2 => (get_reg(&Rcc::inst().apb1enr) & bit) != 0,
+        };
+        if !enabled {
+            match cfg.spi {
+                SpiNo::Spi1 => {
+                    set_reg_bitmsk(&mut Rcc::inst().apb2rstr, bit);
+                    clr_reg_bitmsk(&mut Rcc::inst().apb2rstr, bit);
+                    set_reg_bitmsk(&mut Rcc::inst().apb2enr, bit);
+                }
+                SpiNo::Spi2 => {
+                    set_reg_bitmsk(&mut Rcc::inst().apb1rstr, bit);
+                    clr_reg_bitmsk(&mut Rcc::inst().apb1rstr, bit);
+                    set_reg_bitmsk(&mut Rcc::inst().apb1enr, bit);
+                }
+            }
+            let bus: &SpiBusCfg = &SPI_BUS_CFG[cfg.spi as usize];
+            for pin in [bus.sck_pin, bus.miso_pin, bus.mosi_pin] {
+                pin.cfg_af(bus.af);
+                pin.cfg_ospeed(Ospeed::High);
+                pin.cfg_mode(Mode::Alternate);
+            }
+            //configure with the first device, so that the clock has the idle level of its mode
+            Spi::inst(cfg.spi).configure(cfg);
+        }
+
+        cfg.cs_pin.set();
+        cfg.cs_pin.cfg_mode(Mode::Output);
+        idx += 1;
+    }
+}
+
+///start a full duplex transfer of the frames with the device; the bus is configured for the device and the device
+///is selected; fails if the bus has an active job
+#[allow(dead_code)]
+pub fn spi_start_transfer(device: SpiDevice, tx: &[u16], rt_data: &mut RteData) -> Result<(), SpiError> {
+    let cfg: &SpiDeviceCfg = &SPI_DEVICE_CFG[device as usize];
+    let job: &mut SpiJob = &mut rt_data.spi_data.jobs[cfg.spi as usize];
+    if job.state == SpiJobState::Active {
+        return Err(SpiError::Busy);
+    }
+    if tx.is_empty() || tx.len() > SPI_JOB_BUF_SIZE {
+        return Err(SpiError::Length);
+    }
+
+    job.buf[..tx.len()].copy_from_slice(tx);
+    job.device = device;
+    job.len = tx.len();
+    job.tx_idx = 0;
+    job.rx_idx = 0;
+    job.state = SpiJobState::Active;
+
+    let inst: &mut Spi = Spi::inst(cfg.spi);
+    inst.configure(cfg);
+    inst.enable();
+    cfg.cs_pin.clr();
+    Ok(())
+}
+
+///copy the received frames of the finished job of the device; returns the number of frames; the job is set to Idle
+#[allow(dead_code)]
+pub fn spi_take_result(device: SpiDevice, rx: &mut [u16], rt_data: &mut RteData) -> Result<usize, SpiError> {
+    let job: &mut SpiJob = &mut rt_data.spi_data.jobs[SPI_DEVICE_CFG[device as usize].spi as usize];
+    if job.device != device {
+        return Err(SpiError::NoData);
+    }
+    match job.state {
+        SpiJobState::Active => Err(SpiError::Busy),
+        SpiJobState::Idle => Err(SpiError::NoData),
+        SpiJobState::Error(e) => {
+            job.state = SpiJobState::Idle;
+            Err(e)
+        }
+        SpiJobState::Done => {
+            let cnt: usize = job.len.min(rx.len());
+            rx[..cnt].copy_from_slice(&job.buf[..cnt]);
+            job.state = SpiJobState::Idle;
+            Ok(cnt)
+        }
+    }
+}
+
+///finish the job of the bus and deselect the device
+///get the transfer time of a frame of the device in cpu clocks
+const fn spi_frame_cpu_clk(cfg: &SpiDeviceCfg) -> u32 {
+    let bits: u32 = match cfg.frame {
+        FrameSize::Bits8 => 8,
+        FrameSize::Bits16 => 16,
+    };
+    (2 << (cfg.prescaler as u32)) * bits * (HCLK_HZ / PCLK_HZ)
+}
+
+///get the number of frames of the device per spi_task call that fit into SPI_WAIT_TIME_MAX_US, at most
+///SPI_FRAMES_PER_CALL; 0 if a frame is slower
+const fn spi_frames_per_call(cfg: &SpiDeviceCfg) -> u32 {
+    let frames: u32 = SPI_WAIT_TIME_MAX_US * (HCLK_HZ / 1_000_000) / spi_frame_cpu_clk(cfg);
+    if frames > SPI_FRAMES_PER_CALL {
+        SPI_FRAMES_PER_CALL
+    } else {
+        frames
+    }
+}
+
+fn spi_finish_job(inst: &mut Spi, job: &mut SpiJob, state: SpiJobState) {
+    inst.disable();
+    SPI_DEVICE_CFG[job.device as usize].cs_pin.set();
+    job.state = state;
+}
+
+///spi main function; advances the active jobs; must be called periodically by a task
+pub fn spi_task(rt_data: &mut RteData) {
+    let mut bus: usize = 0;
+    while bus < SPI_BUS_NUM {
+        let job: &mut SpiJob = &mut rt_data.spi_data.jobs[bus];
+        if job.state != SpiJobState::Active {
+            bus += 1;
+            continue;
+        }
+        let cfg: &SpiDeviceCfg = &SPI_DEVICE_CFG[job.device as usize];
+        let inst: &mut Spi = Spi::inst(cfg.spi);
+
+        if let Some(e) = inst.get_error() {
+            spi_finish_job(inst, job, SpiJobState::Error(e));
+            bus += 1;
+            continue;
+        }
+
+        //only one frame is in transfer, so the receive fifo can not overrun if the task is delayed; the received
+        //frame replaces the transmitted frame in the job buffer
+        let frames: u32 = spi_frames_per_call(cfg);
+        let mut cnt: u32 = 0;
+        loop {
+            if job.tx_idx > job.rx_idx {
+                //a frame of this call is awaited, a frame of the last call is already received; each poll takes more
+                //than one cpu clock, so the frame time in cpu clocks is enough polls
+                let polls: u32 = if cnt > 0 && frames > 0 { spi_frame_cpu_clk(cfg) } else { 1 };
+                match inst.wait_read(cfg.frame, polls) {
+                    Some(data) => {
+                        job.buf[job.rx_idx] = data;
+                        job.rx_idx += 1;
+                    }
+                    None => break,
+                }
+            }
+            if job.tx_idx == job.len || cnt >= frames.max(1) || !inst.try_write(cfg.frame, job.buf[job.tx_idx]) {
+                break;
+            }
+            job.tx_idx += 1;
+            cnt += 1;
+        }
+
+        if job.rx_idx == job.len && !inst.is_busy() {
+            spi_finish_job(inst, job, SpiJobState::Done);
+        }
+        bus += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESCALERS: [Prescaler; 8] = [
+        Prescaler::Div2,
+        Prescaler::Div4,
+        Prescaler::Div8,
+        Prescaler::Div16,
+        Prescaler::Div32,
+        Prescaler::Div64,
+        Prescaler::Div128,
+        Prescaler::Div256,
+    ];
+
+    fn device_cfg(frame: FrameSize, prescaler: Prescaler) -> SpiDeviceCfg {
+        SpiDeviceCfg {
+            frame,
+            prescaler,
+            ..SPI_DEVICE_CFG[0]
+        }
+    }
+
+    #[test]
+    fn frame_cpu_clk() {
+        assert_eq!(spi_frame_cpu_clk(&device_cfg(FrameSize::Bits8, Prescaler::Div2)), 16 * (HCLK_HZ / PCLK_HZ));
+        assert_eq!(spi_frame_cpu_clk(&device_cfg(FrameSize::Bits16, Prescaler::Div256)), 4096 * (HCLK_HZ / PCLK_HZ));
+    }
+
+    #[test]
+    fn frames_per_call_fit_into_wait_time() {
+        let wait_clk: u32 = SPI_WAIT_TIME_MAX_US * (HCLK_HZ / 1_000_000);
+        for frame in [FrameSize::Bits8, FrameSize::Bits16] {
+            for prescaler in PRESCALERS {
+                let cfg: SpiDeviceCfg = device_cfg(frame, prescaler);
+                let frames: u32 = spi_frames_per_call(&cfg);
+                assert!(frames <= SPI_FRAMES_PER_CALL);
+                assert!(frames * spi_frame_cpu_clk(&cfg) <= wait_clk);
+                //one more frame would not fit, unless the maximum is reached
+                assert!(frames == SPI_FRAMES_PER_CALL || (frames + 1) * spi_frame_cpu_clk(&cfg) > wait_clk);
+            }
+        }
+    }
+
+    #[test]
+    fn configured_device_transfers_several_frames_per_call() {
+        assert!(spi_frames_per_call(&SPI_DEVICE_CFG[0]) > 1);
+    }
+
+    #[test]
+    fn slow_device_is_not_awaited() {
+        assert_eq!(spi_frames_per_call(&device_cfg(FrameSize::Bits16, Prescaler::Div256)), 0);
+    }
+}
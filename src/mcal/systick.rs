@@ -0,0 +1,117 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : systick.rs
+//!
+//! # Short description
+//! SysTick module implementation for the system timer of the Cortex-M core
+//!
+//! # Detailed description
+//! The SysTick is a 24 bit down counter of the core, so it is available on every Cortex-M. It is clocked with HCLK
+//! and reloaded every 1 ms; no interrupt is used. The methods have the same behavior as those of the basic timers
+//! (Timer6_7), so the SysTick can be used as time base instead of a timer: get_cnt_value counts up from 0 after
+//! the reload or reset_cnt_value, timer_elapsed reports the reload.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::rcc::HCLK_HZ;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const SYST_CSR_ENABLE: u32 = 1 << 0;
+const SYST_CSR_CLKSOURCE: u32 = 1 << 2;
+const SYST_CSR_COUNTFLAG: u32 = 1 << 16;
+
+///counter clock; the processor clock
+pub const SYSTICK_CLK_HZ: u32 = HCLK_HZ;
+///reload value for a period of 1 ms
+const SYSTICK_RELOAD: u32 = SYSTICK_CLK_HZ / 1000 - 1;
+
+const _: () = assert!(SYSTICK_RELOAD <= 0x00FF_FFFF);
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for SysTick registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct SysTick {
+    pub csr: u32,
+    pub rvr: u32,
+    pub cvr: u32,
+    pub calib: u32,
+}
+
+impl SysTick {
+    /// method to the instance of SysTick
+    pub fn inst() -> &'static mut SysTick {
+        unsafe { &mut *(0xE000_E010 as *mut SysTick) }
+    }
+
+    ///configure the SysTick to run at SYSTICK_CLK_HZ with a period of 1 ms
+    pub fn init(&mut self) {
+        set_reg(&mut self.csr, 0);
+        set_reg(&mut self.rvr, SYSTICK_RELOAD);
+        set_reg(&mut self.cvr, 0);
+        set_reg(&mut self.csr, SYST_CSR_CLKSOURCE | SYST_CSR_ENABLE);
+    }
+
+    ///true if the counter was reloaded since the last call; the flag is cleared by the read
+    pub fn timer_elapsed(&mut self) -> bool {
+        (get_reg(&self.csr) & SYST_CSR_COUNTFLAG) != 0
+    }
+
+    ///get the counts since the reload; the counter counts down from the reload value to 0 and is reloaded at the
+    ///next count, so 0 is the last count of the period and counts as start of the next period
+    pub fn get_cnt_value(&self) -> u32 {
+        let cvr: u32 = get_reg(&self.cvr);
+        (SYSTICK_RELOAD + 1 - cvr) % (SYSTICK_RELOAD + 1)
+    }
+
+    ///reset the counter; it is reloaded with the next count
+    pub fn reset_cnt_value(&mut self) {
+        set_reg(&mut self.cvr, 0);
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
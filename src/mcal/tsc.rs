@@ -0,0 +1,408 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : tsc.rs
+//!
+//! # Short description
+//! Tsc module implementation for capacitive touch sensing with the touch sensing controller
+//!
+//! # Detailed description
+//! The touch sensing controller measures the capacitance of an electrode by transferring its charge into a sampling
+//! capacitor until the sampling capacitor reaches the threshold voltage; the number of transfers is the count. A
+//! touch raises the electrode capacitance, so the count decreases.
+//! The analog groups are configured by the TSC_GROUP_CFG table with the io of the sampling capacitor, the touch
+//! channels by the TSC_CFG table (index is the TscId) with group, io and touch threshold. The groups 7 and 8 are not
+//! available in the LQFP64 package.
+//!
+//! No interrupts are used: tsc_task acquires one channel per call; it reads the count of the finished acquisition and
+//! starts the acquisition of the next channel (between the acquisitions the ios are driven low, which discharges the
+//! capacitors). The first TSC_CALIB_NUM counts of a channel give its reference; afterwards the channel is touched if
+//! the count is more than the threshold below the reference. The reference follows slow changes (temperature,
+//! humidity) while the channel is not touched. A count that reaches the maximum count (e.g. open electrode) makes the
+//! channel invalid until the next valid acquisition.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Otype;
+use crate::mcal::gpio::Pin;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::rcc::HCLK_HZ;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+use crate::rte::RteData;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const TSC_CR_TSCE: u32 = 1 << 0;
+const TSC_CR_START: u32 = 1 << 1;
+const TSC_CR_MCV_POS: u32 = 5;
+const TSC_CR_PGPSC_POS: u32 = 12;
+const TSC_CR_CTPL_POS: u32 = 24;
+const TSC_CR_CTPH_POS: u32 = 28;
+
+const TSC_ISR_EOAF: u32 = 1 << 0;
+const TSC_ISR_MCEF: u32 = 1 << 1;
+const TSC_ICR_ALL: u32 = TSC_ISR_EOAF | TSC_ISR_MCEF;
+
+const RCC_AHBENR_TSCEN: u32 = 1 << 24;
+
+///maximum frequency of the pulse generator clock
+const TSC_PGCLK_MAX_HZ: u32 = 4_000_000;
+///pulse generator prescaler exponent (PGPSC); the smallest prescaler that keeps the clock in its limit
+const TSC_PGPSC: u32 = tsc_calc_pgpsc(HCLK_HZ);
+///charge transfer pulse high / low time in pulse generator clock cycles
+const TSC_CTPH_CYCLES: u32 = 2;
+const TSC_CTPL_CYCLES: u32 = 2;
+///max count value (MCV); the count limit is 2^(MCV + 8) - 1
+const TSC_MCV: u32 = 5;
+
+///alternate function of the tsc ios
+const TSC_AF: Af = Af::AlternateFunc3;
+
+///number of acquisitions for the reference of a channel
+const TSC_CALIB_NUM: u16 = 8;
+///reference tracking: the reference moves by 1 / 2^TSC_DRIFT_SHIFT of the difference per acquisition
+const TSC_DRIFT_SHIFT: u32 = 4;
+
+///group configuration
+const TSC_GROUP_CFG: [TscGroupCfg; 1] = [TscGroupCfg {
+    group: TscGroup::G3,
+    sampling_io: TscIo::Io4,
+}];
+
+///number of configured channels
+const TSC_CHANNEL_NUM: usize = 2;
+
+///channel configuration; index is the TscId
+const TSC_CFG: [TscCfg; TSC_CHANNEL_NUM] = [
+    TscCfg {
+        group: TscGroup::G3,
+        io: TscIo::Io2,
+        threshold: 50,
+    },
+    TscCfg {
+        group: TscGroup::G3,
+        io: TscIo::Io3,
+        threshold: 50,
+    },
+];
+
+const _: () = tsc_check_cfg(&TSC_GROUP_CFG, &TSC_CFG);
+const _: () = assert!(TSC_CTPH_CYCLES >= 1 && TSC_CTPH_CYCLES <= 16);
+const _: () = assert!(TSC_CTPL_CYCLES >= 1 && TSC_CTPL_CYCLES <= 16);
+const _: () = assert!(TSC_MCV <= 6);
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///configured channels
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum TscId {
+    Key1 = 0,
+    Key2 = 1,
+}
+
+///analog groups available in the LQFP64 package
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum TscGroup {
+    G1 = 0,
+    G2 = 1,
+    G3 = 2,
+    G4 = 3,
+    G5 = 4,
+    G6 = 5,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum TscIo {
+    Io1 = 0,
+    Io2 = 1,
+    Io3 = 2,
+    Io4 = 3,
+}
+
+impl TscGroup {
+    ///get the pin of the io of the group
+    const fn pin(self, io: TscIo) -> Pin {
+        let pins: [Pin; 4] = match self {
+            TscGroup::G1 => [Pin::A0, Pin::A1, Pin::A2, Pin::A3],
+            TscGroup::G2 => [Pin::A4, Pin::A5, Pin::A6, Pin::A7],
+            TscGroup::G3 => [Pin::C5, Pin::B0, Pin::B1, Pin::B2],
+            TscGroup::G4 => [Pin::A9, Pin::A10, Pin::A11, Pin::A12],
+            TscGroup::G5 => [Pin::B3, Pin::B4, Pin::B6, Pin::B7],
+            TscGroup::G6 => [Pin::B11, Pin::B12, Pin::B13, Pin::B14],
+        };
+        pins[io as usize]
+    }
+
+    ///get the bit of the io in the io registers (IOHCR, IOSCR, IOCCR)
+    const fn io_bit(self, io: TscIo) -> u32 {
+        1 << (4 * self as u32 + io as u32)
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct TscGroupCfg {
+    group: TscGroup,
+    ///io of the sampling capacitor
+    sampling_io: TscIo,
+}
+
+#[derive(Copy, Clone)]
+struct TscCfg {
+    group: TscGroup,
+    io: TscIo,
+    ///count decrease against the reference that is detected as touch
+    threshold: u16,
+}
+
+#[derive(Copy, Clone)]
+pub struct TscData {
+    ///channel of the running acquisition
+    active: Option<usize>,
+    count: [u16; TSC_CHANNEL_NUM],
+    ///reference scaled by 2^TSC_DRIFT_SHIFT; sum of the counts during the calibration
+    reference: [u32; TSC_CHANNEL_NUM],
+    calib_cnt: [u16; TSC_CHANNEL_NUM],
+    valid: [bool; TSC_CHANNEL_NUM],
+    touched: [bool; TSC_CHANNEL_NUM],
+    max_count_err_cnt: [u16; TSC_CHANNEL_NUM],
+}
+
+impl TscData {
+    pub const fn init() -> Self {
+        Self {
+            active: None,
+            count: [0; TSC_CHANNEL_NUM],
+            reference: [0; TSC_CHANNEL_NUM],
+            calib_cnt: [0; TSC_CHANNEL_NUM],
+            valid: [false; TSC_CHANNEL_NUM],
+            touched: [false; TSC_CHANNEL_NUM],
+            max_count_err_cnt: [0; TSC_CHANNEL_NUM],
+        }
+    }
+
+    ///true if the channel is touched; false while the channel is not calibrated or invalid
+    #[allow(dead_code)]
+    pub fn is_touched(&self, id: TscId) -> bool {
+        self.valid[id as usize] && self.touched[id as usize]
+    }
+
+    ///get the count of the latest acquisition; None while the channel is not calibrated or invalid
+    #[allow(dead_code)]
+    pub fn get_count(&self, id: TscId) -> Option<u16> {
+        if self.valid[id as usize] {
+            Some(self.count[id as usize])
+        } else {
+            None
+        }
+    }
+
+    ///get the reference count; None while the channel is not calibrated
+    #[allow(dead_code)]
+    pub fn get_reference(&self, id: TscId) -> Option<u16> {
+        if self.calib_cnt[id as usize] < TSC_CALIB_NUM {
+            None
+        } else {
+            Some((self.reference[id as usize] >> TSC_DRIFT_SHIFT) as u16)
+        }
+    }
+
+    ///get the number of acquisitions that reached the maximum count
+    #[allow(dead_code)]
+    pub fn get_max_count_err_cnt(&self, id: TscId) -> u16 {
+        self.max_count_err_cnt[id as usize]
+    }
+
+    ///process the count of a finished acquisition of the channel
+    fn process_count(&mut self, ch: usize, count: u16) {
+        self.count[ch] = count;
+        if self.calib_cnt[ch] < TSC_CALIB_NUM {
+            self.reference[ch] += count as u32;
+            self.calib_cnt[ch] += 1;
+            if self.calib_cnt[ch] == TSC_CALIB_NUM {
+                //scale the sum to the reference format
+                self.reference[ch] = (self.reference[ch] << TSC_DRIFT_SHIFT) / TSC_CALIB_NUM as u32;
+                self.valid[ch] = true;
+            }
+            return;
+        }
+        self.valid[ch] = true;
+        let reference: u32 = self.reference[ch] >> TSC_DRIFT_SHIFT;
+        self.touched[ch] = (count as u32) + (TSC_CFG[ch].threshold as u32) < reference;
+        if !self.touched[ch] {
+            //the reference moves by a part of the difference
+            self.reference[ch] = self.reference[ch] - (self.reference[ch] >> TSC_DRIFT_SHIFT) + count as u32;
+        }
+    }
+}
+
+/// register structure for touch sensing controller registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Tsc {
+    pub cr: u32,
+    pub ier: u32,
+    pub icr: u32,
+    pub isr: u32,
+    pub iohcr: u32,
+    pub reserved0: u32,
+    pub ioascr: u32,
+    pub reserved1: u32,
+    pub ioscr: u32,
+    pub reserved2: u32,
+    pub ioccr: u32,
+    pub reserved3: u32,
+    pub iogcsr: u32,
+    pub iogcr: [u32; 8],
+}
+
+impl Tsc {
+    /// method to the instance of Tsc
+    pub fn inst() -> &'static mut Tsc {
+        unsafe { &mut *(0x4002_4000 as *mut Tsc) }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///get the smallest prescaler exponent that keeps the pulse generator clock in its limit
+const fn tsc_calc_pgpsc(hclk_hz: u32) -> u32 {
+    let mut pgpsc: u32 = 0;
+    while pgpsc < 7 && (hclk_hz >> pgpsc) > TSC_PGCLK_MAX_HZ {
+        pgpsc += 1;
+    }
+    pgpsc
+}
+
+///check the configuration at compile time
+const fn tsc_check_cfg(groups: &[TscGroupCfg], channels: &[TscCfg; TSC_CHANNEL_NUM]) {
+    let mut ch: usize = 0;
+    while ch < TSC_CHANNEL_NUM {
+        //the group of the channel must be configured and the channel must not be the sampling io
+        let mut found: bool = false;
+        let mut idx: usize = 0;
+        while idx < groups.len() {
+            if groups[idx].group as u32 == channels[ch].group as u32 {
+                assert!(groups[idx].sampling_io as u32 != channels[ch].io as u32);
+                found = true;
+            }
+            idx += 1;
+        }
+        assert!(found);
+        ch += 1;
+    }
+}
+
+///init the touch sensing controller and the ios of the configured groups and channels
+pub fn init() {
+    set_reg_bitmsk(&mut Rcc::inst().ahbenr, RCC_AHBENR_TSCEN);
+    let inst: &mut Tsc = Tsc::inst();
+
+    let mut sampling: u32 = 0;
+    for cfg in TSC_GROUP_CFG.iter() {
+        let pin: Pin = cfg.group.pin(cfg.sampling_io);
+        pin.cfg_otype(Otype::OpenDrain);
+        pin.cfg_af(TSC_AF);
+        pin.cfg_mode(Mode::Alternate);
+        sampling |= cfg.group.io_bit(cfg.sampling_io);
+    }
+    let mut channels: u32 = 0;
+    for cfg in TSC_CFG.iter() {
+        let pin: Pin = cfg.group.pin(cfg.io);
+        pin.cfg_otype(Otype::PushPull);
+        pin.cfg_af(TSC_AF);
+        pin.cfg_mode(Mode::Alternate);
+        channels |= cfg.group.io_bit(cfg.io);
+    }
+    //the schmitt trigger hysteresis of the ios is disabled for the analog measurement
+    clr_reg_bitmsk(&mut inst.iohcr, sampling | channels);
+    set_reg(&mut inst.ioscr, sampling);
+
+    set_reg(
+        &mut inst.cr,
+        ((TSC_CTPH_CYCLES - 1) << TSC_CR_CTPH_POS)
+            | ((TSC_CTPL_CYCLES - 1) << TSC_CR_CTPL_POS)
+            | (TSC_PGPSC << TSC_CR_PGPSC_POS)
+            | (TSC_MCV << TSC_CR_MCV_POS)
+            | TSC_CR_TSCE,
+    );
+}
+
+///start the acquisition of the channel
+fn tsc_start(ch: usize) {
+    let inst: &mut Tsc = Tsc::inst();
+    let cfg: &TscCfg = &TSC_CFG[ch];
+    set_reg(&mut inst.ioccr, cfg.group.io_bit(cfg.io));
+    set_reg(&mut inst.iogcsr, 1 << (cfg.group as u32));
+    set_reg(&mut inst.icr, TSC_ICR_ALL);
+    set_reg_bitmsk(&mut inst.cr, TSC_CR_START);
+}
+
+///tsc task; reads the count of the finished acquisition and starts the acquisition of the next channel
+pub fn tsc_task(rt_data: &mut RteData) {
+    let data: &mut TscData = &mut rt_data.tsc_data;
+    let inst: &mut Tsc = Tsc::inst();
+    let next: usize = match data.active {
+        Some(ch) => {
+            let isr: u32 = get_reg(&inst.isr);
+            if (isr & TSC_ISR_MCEF) != 0 {
+                data.valid[ch] = false;
+                data.max_count_err_cnt[ch] = data.max_count_err_cnt[ch].saturating_add(1);
+            } else if (isr & TSC_ISR_EOAF) != 0 {
+                let count: u16 = get_reg(&inst.iogcr[TSC_CFG[ch].group as usize]) as u16;
+                data.process_count(ch, count);
+            } else {
+                //acquisition is running
+                return;
+            }
+            (ch + 1) % TSC_CHANNEL_NUM
+        }
+        None => 0,
+    };
+    data.active = Some(next);
+    tsc_start(next);
+}
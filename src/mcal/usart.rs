@@ -0,0 +1,628 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : usart.rs
+//!
+//! # Short description
+//! Usart module implementation for USART1 - USART8
+//!
+//! # Detailed description
+//! The driver works poll based without interrupts. The application writes to / reads from software ring buffers
+//! (usart_send / usart_receive) and usart_task moves the data between the ring buffers and the peripheral.
+//! The Mcu has no receive fifo, so usart_task must be called at least once per received character; e.g. at
+//! 9600 baud a character takes about 1 ms. For a channel without dma the character time must be longer than
+//! USART_TASK_PERIOD_US (checked at compile time), otherwise the reception overruns. For higher baud rates the
+//! reception must be done by dma: if rx_dma is configured for a channel, a circular dma channel writes the received
+//! characters to a buffer in the runtime data and usart_task copies the new characters to the receive ring buffer;
+//! then usart_task must be called at least once per USART_RX_DMA_BUF_SIZE received characters.
+//! The channels are configured by the USART_CFG table; the index of the table is the UsartChannel.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::common::ringbuf::RingBuf;
+use crate::mcal::dma;
+use crate::mcal::dma::DmaCfg;
+use crate::mcal::dma::DmaChannel;
+use crate::mcal::dma::DmaDir;
+use crate::mcal::dma::DmaPriority;
+use crate::mcal::dma::DmaRequest;
+use crate::mcal::dma::DmaSize;
+use crate::mcal::dma::DmaStatus;
+use crate::mcal::gpio::Af;
+use crate::mcal::gpio::Mode;
+use crate::mcal::gpio::Pin;
+use crate::mcal::gpio::Pupd;
+use crate::mcal::rcc::Rcc;
+use crate::mcal::rcc::PCLK_HZ;
+use crate::mcal::util::clr_reg_bitmsk;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+use crate::rte::RteData;
+use core::ptr;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const USART_CR1_UE: u32 = 1 << 0;
+const USART_CR1_RE: u32 = 1 << 2;
+const USART_CR1_TE: u32 = 1 << 3;
+const USART_CR1_PS: u32 = 1 << 9;
+const USART_CR1_PCE: u32 = 1 << 10;
+const USART_CR1_M0: u32 = 1 << 12;
+const USART_CR1_M1: u32 = 1 << 28;
+
+const USART_CR2_STOP_POS: u32 = 12;
+
+const USART_CR3_DMAR: u32 = 1 << 6;
+
+const USART_ISR_PE: u32 = 1 << 0;
+const USART_ISR_FE: u32 = 1 << 1;
+const USART_ISR_NF: u32 = 1 << 2;
+const USART_ISR_ORE: u32 = 1 << 3;
+const USART_ISR_RXNE: u32 = 1 << 5;
+const USART_ISR_TC: u32 = 1 << 6;
+const USART_ISR_TXE: u32 = 1 << 7;
+const USART_ISR_TEACK: u32 = 1 << 21;
+const USART_ISR_REACK: u32 = 1 << 22;
+
+const USART_ISR_ERR_MSK: u32 = USART_ISR_PE | USART_ISR_FE | USART_ISR_NF | USART_ISR_ORE;
+
+///size of the software transmit buffer of each channel
+pub const USART_TX_BUF_SIZE: usize = 64;
+///size of the software receive buffer of each channel
+pub const USART_RX_BUF_SIZE: usize = 64;
+///size of the dma receive buffer of each channel
+pub const USART_RX_DMA_BUF_SIZE: usize = 32;
+
+///dma configuration of the reception
+const USART_RX_DMA_CFG: DmaCfg = DmaCfg {
+    dir: DmaDir::PeriphToMem,
+    circular: true,
+    periph_size: DmaSize::Bits8,
+    mem_size: DmaSize::Bits8,
+    periph_inc: false,
+    mem_inc: true,
+    priority: DmaPriority::Medium,
+};
+
+///call period of usart_task; must be the period of the calling task (checked in the scheduler)
+pub const USART_TASK_PERIOD_US: u32 = 1000;
+
+///maximum number of characters written to the peripheral per usart_task call and channel
+const USART_TX_CHARS_PER_CALL: u32 = 2;
+
+///number of configured channels
+pub const USART_CHANNEL_NUM: usize = 1;
+
+///channel configuration; index is the UsartChannel
+const USART_CFG: [UsartCfg; USART_CHANNEL_NUM] = [UsartCfg {
+    usart: UsartNo::Usart2,
+    baud: 9600,
+    data_bits: DataBits::Bits8,
+    parity: Parity::None,
+    stop_bits: StopBits::One,
+    tx_pin: Pin::A2,
+    rx_pin: Pin::A3,
+    af: Af::AlternateFunc1,
+    rx_dma: Some(DmaChannel::Dma1Ch5),
+}];
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum UsartNo {
+    Usart1,
+    Usart2,
+    Usart3,
+    Usart4,
+    Usart5,
+    Usart6,
+    Usart7,
+    Usart8,
+}
+
+impl UsartNo {
+    const fn base(self) -> u32 {
+        match self {
+            UsartNo::Usart1 => 0x4001_3800,
+            UsartNo::Usart2 => 0x4000_4400,
+            UsartNo::Usart3 => 0x4000_4800,
+            UsartNo::Usart4 => 0x4000_4C00,
+            UsartNo::Usart5 => 0x4000_5000,
+            UsartNo::Usart6 => 0x4001_1400,
+            UsartNo::Usart7 => 0x4001_1800,
+            UsartNo::Usart8 => 0x4001_1C00,
+        }
+    }
+
+    ///get the clock enable / reset bit and whether the peripheral is located on apb2 (otherwise apb1)
+    const fn rcc_bit(self) -> (bool, u32) {
+        match self {
+            UsartNo::Usart1 => (true, 1 << 14),
+            UsartNo::Usart2 => (false, 1 << 17),
+            UsartNo::Usart3 => (false, 1 << 18),
+            UsartNo::Usart4 => (false, 1 << 19),
+            UsartNo::Usart5 => (false, 1 << 20),
+            UsartNo::Usart6 => (true, 1 << 5),
+            UsartNo::Usart7 => (true, 1 << 6),
+            UsartNo::Usart8 => (true, 1 << 7),
+        }
+    }
+
+    const fn dma_request(self) -> DmaRequest {
+        match self {
+            UsartNo::Usart1 => DmaRequest::Usart1,
+            UsartNo::Usart2 => DmaRequest::Usart2,
+            UsartNo::Usart3 => DmaRequest::Usart3,
+            UsartNo::Usart4 => DmaRequest::Usart4,
+            UsartNo::Usart5 => DmaRequest::Usart5,
+            UsartNo::Usart6 => DmaRequest::Usart6,
+            UsartNo::Usart7 => DmaRequest::Usart7,
+            UsartNo::Usart8 => DmaRequest::Usart8,
+        }
+    }
+}
+
+///configured usart channels
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum UsartChannel {
+    Debug = 0,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum DataBits {
+    Bits7,
+    Bits8,
+    Bits9,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+///stop bits; the values match the STOP bits of CR2
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum StopBits {
+    One = 0,
+    Half = 1,
+    Two = 2,
+    OneAndHalf = 3,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum UsartError {
+    Parity,
+    Framing,
+    Noise,
+    Overrun,
+    Config,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+
+/// register structure for usart module registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Usart {
+    pub cr1: u32,
+    pub cr2: u32,
+    pub cr3: u32,
+    pub brr: u32,
+    pub gtpr: u32,
+    pub rtor: u32,
+    pub rqr: u32,
+    pub isr: u32,
+    pub icr: u32,
+    pub rdr: u32,
+    pub tdr: u32,
+}
+
+impl Usart {
+    /// method to the instance of the given usart
+    pub fn inst(usart: UsartNo) -> &'static mut Usart {
+        unsafe { &mut *(usart.base() as *mut Usart) }
+    }
+
+    ///reset and configure the usart, enable transmitter and receiver
+    pub fn init(usart: UsartNo, cfg: &UsartCfg) -> Result<(), UsartError> {
+        let brr: u32 = usart_calc_brr(cfg.baud)?;
+
+        //the word length includes the parity bit
+        let cr1_frame: u32 = match (cfg.data_bits, cfg.parity != Parity::None) {
+            (DataBits::Bits7, false) => USART_CR1_M1,
+            (DataBits::Bits7, true) | (DataBits::Bits8, false) => 0,
+            (DataBits::Bits8, true) | (DataBits::Bits9, false) => USART_CR1_M0,
+            (DataBits::Bits9, true) => return Err(UsartError::Config),
+        };
+        let cr1_parity: u32 = match cfg.parity {
+            Parity::None => 0,
+            Parity::Even => USART_CR1_PCE,
+            Parity::Odd => USART_CR1_PCE | USART_CR1_PS,
+        };
+
+        let (apb2, bit) = usart.rcc_bit();
+        if apb2 {
+            set_reg_bitmsk(&mut Rcc::inst().apb2rstr, bit);
+            clr_reg_bitmsk(&mut Rcc::inst().apb2rstr, bit);
+            set_reg_bitmsk(&mut Rcc::inst().apb2enr, bit);
+        } else {
+            set_reg_bitmsk(&mut Rcc::inst().apb1rstr, bit);
+            clr_reg_bitmsk(&mut Rcc::inst().apb1rstr, bit);
+            set_reg_bitmsk(&mut Rcc::inst().apb1enr, bit);
+        }
+
+        cfg.tx_pin.cfg_af(cfg.af);
+        cfg.tx_pin.cfg_mode(Mode::Alternate);
+        cfg.rx_pin.cfg_af(cfg.af);
+        cfg.rx_pin.cfg_pupd(Pupd::PullUp);
+        cfg.rx_pin.cfg_mode(Mode::Alternate);
+
+        let inst: &mut Usart = Usart::inst(usart);
+        set_reg(&mut inst.cr1, 0);
+        set_reg(&mut inst.brr, brr);
+        set_reg(&mut inst.cr2, (cfg.stop_bits as u32) << USART_CR2_STOP_POS);
+        set_reg(&mut inst.cr3, if cfg.rx_dma.is_some() { USART_CR3_DMAR } else { 0 });
+        set_reg(
+            &mut inst.cr1,
+            cr1_frame | cr1_parity | USART_CR1_TE | USART_CR1_RE | USART_CR1_UE,
+        );
+        Ok(())
+    }
+
+    ///true if the transmitter and receiver are enabled after init
+    #[allow(dead_code)]
+    pub fn is_ready(&self) -> bool {
+        let ack: u32 = USART_ISR_TEACK | USART_ISR_REACK;
+        (get_reg(&self.isr) & ack) == ack
+    }
+
+    ///write a character if the transmit data register is empty; returns false if the character was not written
+    pub fn try_write(&mut self, data: u16) -> bool {
+        if (get_reg(&self.isr) & USART_ISR_TXE) != 0 {
+            set_reg(&mut self.tdr, data as u32);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///read a received character if there is one; a reception error is returned once and cleared; a character with
+    ///a framing, parity or noise error is discarded; in case of an overrun the last received character is still
+    ///available
+    pub fn try_read(&mut self) -> Option<Result<u16, UsartError>> {
+        let isr: u32 = get_reg(&self.isr);
+        if let Some(e) = self.get_error() {
+            if (isr & (USART_ISR_FE | USART_ISR_PE | USART_ISR_NF)) != 0 && (isr & USART_ISR_RXNE) != 0 {
+                let _ = get_reg(&self.rdr);
+            }
+            return Some(Err(e));
+        }
+        if (get_reg(&self.isr) & USART_ISR_RXNE) != 0 {
+            Some(Ok(get_reg(&self.rdr) as u16))
+        } else {
+            None
+        }
+    }
+
+    ///get a reception error; the error is returned once and cleared
+    pub fn get_error(&mut self) -> Option<UsartError> {
+        let isr: u32 = get_reg(&self.isr);
+        if (isr & USART_ISR_ERR_MSK) == 0 {
+            return None;
+        }
+        set_reg(&mut self.icr, isr & USART_ISR_ERR_MSK);
+        Some(if (isr & USART_ISR_ORE) != 0 {
+            UsartError::Overrun
+        } else if (isr & USART_ISR_FE) != 0 {
+            UsartError::Framing
+        } else if (isr & USART_ISR_PE) != 0 {
+            UsartError::Parity
+        } else {
+            UsartError::Noise
+        })
+    }
+
+    ///true if the transmission of all written characters is complete
+    #[allow(dead_code)]
+    pub fn is_tx_complete(&self) -> bool {
+        (get_reg(&self.isr) & USART_ISR_TC) != 0
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct UsartCfg {
+    pub usart: UsartNo,
+    pub baud: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub tx_pin: Pin,
+    pub rx_pin: Pin,
+    pub af: Af,
+    ///dma channel of the reception; None for reception by usart_task
+    pub rx_dma: Option<DmaChannel>,
+}
+
+///runtime data of a usart channel
+#[derive(Copy, Clone)]
+pub struct UsartChannelData {
+    tx_buf: RingBuf<USART_TX_BUF_SIZE>,
+    rx_buf: RingBuf<USART_RX_BUF_SIZE>,
+    rx_dma_buf: [u8; USART_RX_DMA_BUF_SIZE],
+    rx_dma_idx: usize,
+    rx_errors: u16,
+    rx_lost: u16,
+    last_error: Option<UsartError>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+pub struct UsartData {
+    channels: [UsartChannelData; USART_CHANNEL_NUM],
+}
+
+impl UsartData {
+    pub const fn init() -> Self {
+        Self {
+            channels: [UsartChannelData {
+                tx_buf: RingBuf::init(),
+                rx_buf: RingBuf::init(),
+                rx_dma_buf: [0; USART_RX_DMA_BUF_SIZE],
+                rx_dma_idx: 0,
+                rx_errors: 0,
+                rx_lost: 0,
+                last_error: None,
+            }; USART_CHANNEL_NUM],
+        }
+    }
+
+    ///get the number of reception errors and the number of characters lost due to a full receive buffer
+    #[allow(dead_code)]
+    pub fn get_error_cnt(&self, channel: UsartChannel) -> (u16, u16) {
+        let ch: &UsartChannelData = &self.channels[channel as usize];
+        (ch.rx_errors, ch.rx_lost)
+    }
+
+    #[allow(dead_code)]
+    pub fn get_last_error(&self, channel: UsartChannel) -> Option<UsartError> {
+        self.channels[channel as usize].last_error
+    }
+}
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///get the time of a character including start, parity and stop bits in us (rounded down)
+const fn usart_char_time_us(cfg: &UsartCfg) -> u32 {
+    let data_bits: u32 = match cfg.data_bits {
+        DataBits::Bits7 => 7,
+        DataBits::Bits8 => 8,
+        DataBits::Bits9 => 9,
+    };
+    let parity_bits: u32 = if matches!(cfg.parity, Parity::None) { 0 } else { 1 };
+    let stop_half_bits: u32 = match cfg.stop_bits {
+        StopBits::Half => 1,
+        StopBits::One => 2,
+        StopBits::OneAndHalf => 3,
+        StopBits::Two => 4,
+    };
+    let half_bits: u32 = 2 * (1 + data_bits + parity_bits) + stop_half_bits;
+    (half_bits as u64 * 1_000_000 / (2 * cfg.baud as u64)) as u32
+}
+
+///calculate the baud rate register value for oversampling by 16; the usart clock is PCLK_HZ
+const fn usart_calc_brr(baud: u32) -> Result<u32, UsartError> {
+    if baud == 0 {
+        return Err(UsartError::Config);
+    }
+    let brr: u32 = (PCLK_HZ + baud / 2) / baud;
+    if brr < 16 || brr > 0xFFFF {
+        Err(UsartError::Config)
+    } else {
+        Ok(brr)
+    }
+}
+
+///init all configured usart channels; the dma of the reception is started for the channels with rx_dma
+pub fn init(rt_data: &mut RteData) {
+    let mut idx: usize = 0;
+    while idx < USART_CHANNEL_NUM {
+        let cfg: &UsartCfg = &USART_CFG[idx];
+        //the configuration is checked at compile time, so init can not fail here
+        let _ = Usart::init(cfg.usart, cfg);
+        if let Some(dma_ch) = cfg.rx_dma {
+            usart_start_rx_dma(cfg, dma_ch, &mut rt_data.usart_data.channels[idx]);
+        }
+        idx += 1;
+    }
+}
+
+///add the data to the transmit buffer of the channel; returns the number of added bytes
+#[allow(dead_code)]
+pub fn usart_send(channel: UsartChannel, data: &[u8], rt_data: &mut RteData) -> usize {
+    let tx_buf: &mut RingBuf<USART_TX_BUF_SIZE> = &mut rt_data.usart_data.channels[channel as usize].tx_buf;
+    let mut cnt: usize = 0;
+    while cnt < data.len() && tx_buf.push(data[cnt]) {
+        cnt += 1;
+    }
+    cnt
+}
+
+///take received data from the receive buffer of the channel; returns the number of taken bytes
+#[allow(dead_code)]
+pub fn usart_receive(channel: UsartChannel, data: &mut [u8], rt_data: &mut RteData) -> usize {
+    let rx_buf: &mut RingBuf<USART_RX_BUF_SIZE> = &mut rt_data.usart_data.channels[channel as usize].rx_buf;
+    let mut cnt: usize = 0;
+    while cnt < data.len() {
+        match rx_buf.pop() {
+            Some(byte) => data[cnt] = byte,
+            None => break,
+        }
+        cnt += 1;
+    }
+    cnt
+}
+
+///start the circular dma of the reception to the dma receive buffer of the channel
+fn usart_start_rx_dma(cfg: &UsartCfg, dma_ch: DmaChannel, ch: &mut UsartChannelData) {
+    ch.rx_dma_idx = 0;
+    dma::dma_start(
+        dma_ch,
+        cfg.usart.dma_request(),
+        &USART_RX_DMA_CFG,
+        &Usart::inst(cfg.usart).rdr as *const u32 as u32,
+        ch.rx_dma_buf.as_mut_ptr() as u32,
+        USART_RX_DMA_BUF_SIZE as u16,
+    );
+}
+
+///store a received character in the receive buffer or count the reception error
+fn usart_store_rx(ch: &mut UsartChannelData, result: Result<u16, UsartError>) {
+    match result {
+        Ok(data) => {
+            if !ch.rx_buf.push(data as u8) {
+                ch.rx_lost = ch.rx_lost.saturating_add(1);
+            }
+        }
+        Err(e) => {
+            ch.rx_errors = ch.rx_errors.saturating_add(1);
+            ch.last_error = Some(e);
+        }
+    }
+}
+
+///usart main function; moves the data between the software buffers and the peripherals; must be called
+///periodically by a task
+pub fn usart_task(rt_data: &mut RteData) {
+    let mut idx: usize = 0;
+    while idx < USART_CHANNEL_NUM {
+        let inst: &mut Usart = Usart::inst(USART_CFG[idx].usart);
+        let ch: &mut UsartChannelData = &mut rt_data.usart_data.channels[idx];
+
+        let mut cnt: u32 = 0;
+        while cnt < USART_TX_CHARS_PER_CALL {
+            match ch.tx_buf.peek() {
+                Some(byte) if inst.try_write(byte as u16) => {
+                    ch.tx_buf.pop();
+                }
+                _ => break,
+            }
+            cnt += 1;
+        }
+
+        match USART_CFG[idx].rx_dma {
+            Some(dma_ch) => {
+                //the channel is disabled by a transfer error; the received characters are lost
+                if dma::dma_get_status(dma_ch) == DmaStatus::Error {
+                    ch.rx_errors = ch.rx_errors.saturating_add(1);
+                    usart_start_rx_dma(&USART_CFG[idx], dma_ch, ch);
+                }
+                //copy the characters between the last read position and the current dma position
+                let pos: usize =
+                    (USART_RX_DMA_BUF_SIZE - dma::dma_get_remaining(dma_ch) as usize) % USART_RX_DMA_BUF_SIZE;
+                while ch.rx_dma_idx != pos {
+                    let data: u8 = unsafe { ptr::read_volatile(&ch.rx_dma_buf[ch.rx_dma_idx]) };
+                    usart_store_rx(ch, Ok(data as u16));
+                    ch.rx_dma_idx = (ch.rx_dma_idx + 1) % USART_RX_DMA_BUF_SIZE;
+                }
+                if let Some(e) = inst.get_error() {
+                    usart_store_rx(ch, Err(e));
+                }
+            }
+            None => {
+                //the peripheral holds only one received character, so one read per call is enough
+                if let Some(result) = inst.try_read() {
+                    usart_store_rx(ch, result);
+                }
+            }
+        }
+        idx += 1;
+    }
+}
+
+//the configured baud rates must be possible with the usart clock; usart_task must read each character of a
+//channel without dma before the next one is received
+const _: () = {
+    let mut idx: usize = 0;
+    while idx < USART_CHANNEL_NUM {
+        assert!(usart_calc_brr(USART_CFG[idx].baud).is_ok());
+        assert!(USART_CFG[idx].rx_dma.is_some() || usart_char_time_us(&USART_CFG[idx]) > USART_TASK_PERIOD_US);
+        idx += 1;
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(baud: u32, data_bits: DataBits, parity: Parity, stop_bits: StopBits) -> UsartCfg {
+        UsartCfg {
+            baud,
+            data_bits,
+            parity,
+            stop_bits,
+            rx_dma: None,
+            ..USART_CFG[0]
+        }
+    }
+
+    #[test]
+    fn char_time() {
+        assert_eq!(usart_char_time_us(&cfg(9600, DataBits::Bits8, Parity::None, StopBits::One)), 1041);
+        assert_eq!(usart_char_time_us(&cfg(9600, DataBits::Bits8, Parity::Even, StopBits::Two)), 1250);
+        assert_eq!(usart_char_time_us(&cfg(9600, DataBits::Bits7, Parity::None, StopBits::Half)), 885);
+        assert_eq!(usart_char_time_us(&cfg(115_200, DataBits::Bits9, Parity::None, StopBits::OneAndHalf)), 99);
+    }
+
+    #[test]
+    fn polled_reception_limit() {
+        //9600 baud 8N1 can be polled by usart_task, 19200 baud needs dma
+        assert!(usart_char_time_us(&cfg(9600, DataBits::Bits8, Parity::None, StopBits::One)) > USART_TASK_PERIOD_US);
+        assert!(usart_char_time_us(&cfg(19_200, DataBits::Bits8, Parity::None, StopBits::One)) < USART_TASK_PERIOD_US);
+    }
+}
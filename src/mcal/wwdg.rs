@@ -0,0 +1,209 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : wwdg.rs
+//!
+//! # Short description
+//! Wwdg module implementation for the window watchdog
+//!
+//! # Detailed description
+//! The window watchdog resets the Mcu if it is not refreshed in the time window [window_us, timeout_us] after the
+//! last refresh (or the start): a refresh before window_us and a missing refresh until timeout_us cause a reset. The
+//! counter runs with PCLK / 4096 / prescaler; the prescaler and the counter / window values are calculated from
+//! WWDG_CFG at compile time with the smallest prescaler that reaches the timeout, so the times are rounded to the
+//! counter tick (timeout down, window up). wwdg_timing gives the rounded times for an apb clock, so the user of the
+//! watchdog can check its timing also for other clock setups.
+//!
+//! One tick before the reset the early wakeup flag is set; it is polled by wwdg_is_early_wakeup (no interrupt is
+//! used), so a caller that polls faster than WWDG_TICK_US can store diagnostic data before the reset.
+//! The watchdog can not be stopped after the start; it is halted while the core is halted by the debugger and in
+//! Stop (no PCLK). So the timeout must also cover the nvm page erase, which stalls the Mcu for up to
+//! FLASH_PAGE_ERASE_MAX_US; the scheduler maintenance does the erase directly between two refreshes (checked in the
+//! scheduler). With PCLK 48 MHz the longest possible timeout is about 43.7 ms, so the timeout is kept below it.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::mcal::rcc::Rcc;
+use crate::mcal::rcc::PCLK_HZ;
+use crate::mcal::util::get_reg;
+use crate::mcal::util::set_reg;
+use crate::mcal::util::set_reg_bitmsk;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+const WWDG_CR_WDGA: u32 = 1 << 7;
+const WWDG_CFR_WDGTB_POS: u32 = 7;
+const WWDG_SR_EWIF: u32 = 1 << 0;
+
+///counter value at which the reset occurs when it is reached by counting down
+const WWDG_CNT_RESET: u32 = 0x3F;
+///maximum counter value
+const WWDG_CNT_MAX: u32 = 0x7F;
+
+///fixed divider of PCLK
+const WWDG_CLK_DIV: u32 = 4096;
+
+///freeze of the watchdog while the core is halted (DBG_WWDG_STOP of DBGMCU_APB1_FZ)
+const DBGMCU_APB1_FZ_ADDR: u32 = 0x4001_5808;
+const DBGMCU_APB1_FZ_DBG_WWDG_STOP: u32 = 1 << 11;
+const RCC_APB2ENR_DBGMCUEN: u32 = 1 << 22;
+
+const RCC_APB1ENR_WWDGEN: u32 = 1 << 11;
+
+///watchdog configuration
+const WWDG_CFG: WwdgCfg = WwdgCfg {
+    timeout_us: 43_000,
+    window_us: 5_000,
+};
+
+///timing of the configuration with the apb clock
+const WWDG_TIMING: WwdgTiming = wwdg_timing(PCLK_HZ);
+///counter value loaded at the refresh
+const WWDG_CNT: u32 = WWDG_CNT_RESET + WWDG_TIMING.timeout_us / WWDG_TIMING.tick_us;
+///window value; the counter must be below or equal to it at a refresh
+const WWDG_WINDOW: u32 = WWDG_CNT - WWDG_TIMING.window_us / WWDG_TIMING.tick_us;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct WwdgCfg {
+    ///maximum time between two refreshes
+    timeout_us: u32,
+    ///minimum time between two refreshes
+    window_us: u32,
+}
+
+///watchdog timing of the configuration rounded to the counter tick
+#[derive(Copy, Clone)]
+pub struct WwdgTiming {
+    ///prescaler exponent (WDGTB); the smallest prescaler that reaches the timeout
+    wdgtb: u32,
+    ///time of a counter tick
+    pub tick_us: u32,
+    ///minimum time between two refreshes
+    pub window_us: u32,
+    ///maximum time between two refreshes
+    pub timeout_us: u32,
+}
+
+/// register structure for window watchdog registers; need C representation for correct memory layout
+#[repr(C)]
+pub struct Wwdg {
+    pub cr: u32,
+    pub cfr: u32,
+    pub sr: u32,
+}
+
+impl Wwdg {
+    /// method to the instance of Wwdg
+    pub fn inst() -> &'static mut Wwdg {
+        unsafe { &mut *(0x4000_2C00 as *mut Wwdg) }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///get the time of a counter tick with the apb clock and the prescaler exponent
+const fn wwdg_tick_us(pclk_hz: u32, wdgtb: u32) -> u32 {
+    ((WWDG_CLK_DIV as u64 * (1 << wdgtb) * 1_000_000) / pclk_hz as u64) as u32
+}
+
+///get the smallest prescaler exponent that reaches the timeout; 3 if no prescaler reaches it (checked by
+///wwdg_check_cfg)
+const fn wwdg_calc_wdgtb(cfg: &WwdgCfg, pclk_hz: u32) -> u32 {
+    let mut wdgtb: u32 = 0;
+    while wdgtb < 3 && cfg.timeout_us / wwdg_tick_us(pclk_hz, wdgtb) > WWDG_CNT_MAX - WWDG_CNT_RESET {
+        wdgtb += 1;
+    }
+    wdgtb
+}
+
+///check the configuration with the tick time at compile time
+const fn wwdg_check_cfg(cfg: &WwdgCfg, tick_us: u32) {
+    assert!(cfg.timeout_us / tick_us <= WWDG_CNT_MAX - WWDG_CNT_RESET);
+    assert!(cfg.timeout_us >= tick_us);
+    //the window must leave at least one tick to refresh
+    assert!(cfg.window_us.div_ceil(tick_us) < cfg.timeout_us / tick_us);
+}
+
+///get the timing of the configuration with the apb clock; fails at compile time if the configuration can not be
+///reached with the apb clock
+pub const fn wwdg_timing(pclk_hz: u32) -> WwdgTiming {
+    let wdgtb: u32 = wwdg_calc_wdgtb(&WWDG_CFG, pclk_hz);
+    let tick_us: u32 = wwdg_tick_us(pclk_hz, wdgtb);
+    wwdg_check_cfg(&WWDG_CFG, tick_us);
+    WwdgTiming {
+        wdgtb,
+        tick_us,
+        window_us: WWDG_CFG.window_us.div_ceil(tick_us) * tick_us,
+        timeout_us: (WWDG_CFG.timeout_us / tick_us) * tick_us,
+    }
+}
+
+///start the watchdog; must be refreshed by wwdg_refresh in the window from now on
+pub fn init() {
+    let rcc: &mut Rcc = Rcc::inst();
+    set_reg_bitmsk(&mut rcc.apb1enr, RCC_APB1ENR_WWDGEN);
+    set_reg_bitmsk(&mut rcc.apb2enr, RCC_APB2ENR_DBGMCUEN);
+    set_reg_bitmsk(unsafe { &mut *(DBGMCU_APB1_FZ_ADDR as *mut u32) }, DBGMCU_APB1_FZ_DBG_WWDG_STOP);
+
+    let inst: &mut Wwdg = Wwdg::inst();
+    set_reg(&mut inst.cfr, (WWDG_TIMING.wdgtb << WWDG_CFR_WDGTB_POS) | WWDG_WINDOW);
+    set_reg(&mut inst.sr, 0);
+    set_reg(&mut inst.cr, WWDG_CR_WDGA | WWDG_CNT);
+}
+
+///refresh the watchdog; causes a reset if it is called before the window
+pub fn wwdg_refresh() {
+    set_reg(&mut Wwdg::inst().cr, WWDG_CR_WDGA | WWDG_CNT);
+}
+
+///true if the reset by the watchdog follows within one tick; the flag is cleared
+pub fn wwdg_is_early_wakeup() -> bool {
+    let inst: &mut Wwdg = Wwdg::inst();
+    if (get_reg(&inst.sr) & WWDG_SR_EWIF) != 0 {
+        set_reg(&mut inst.sr, 0);
+        true
+    } else {
+        false
+    }
+}
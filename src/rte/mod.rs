@@ -28,6 +28,16 @@
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
 use crate::appl::ledm;
+use crate::mcal::adc;
+use crate::mcal::cec;
+use crate::mcal::i2c;
+use crate::mcal::icu;
+use crate::mcal::spi;
+use crate::mcal::tsc;
+use crate::mcal::usart;
+use crate::servl::fm;
+use crate::servl::nvm;
+use crate::servl::supv;
 use crate::servl::swtimer;
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -57,14 +67,34 @@ pub static mut RTE_D: RteData = RteData::init();
 pub struct RteData {
     pub ledm_data: ledm::LedmData,
     pub swtimer_data: swtimer::TimerData,
+    pub supv_data: supv::SupvData,
+    pub fm_data: fm::FmData,
+    pub nvm_data: nvm::NvmData,
+    pub usart_data: usart::UsartData,
+    pub spi_data: spi::SpiData,
+    pub i2c_data: i2c::I2cData,
+    pub adc_data: adc::AdcData,
+    pub icu_data: icu::IcuData,
+    pub tsc_data: tsc::TscData,
+    pub cec_data: cec::CecData,
 }
 
 impl RteData {
     ///init the runtime environment with default data
-    const fn init() -> Self {
+    pub const fn init() -> Self {
         Self {
             ledm_data: ledm::LedmData::init(),
             swtimer_data: swtimer::TimerData::init(),
+            supv_data: supv::SupvData::init(),
+            fm_data: fm::FmData::init(),
+            nvm_data: nvm::NvmData::init(),
+            usart_data: usart::UsartData::init(),
+            spi_data: spi::SpiData::init(),
+            i2c_data: i2c::I2cData::init(),
+            adc_data: adc::AdcData::init(),
+            icu_data: icu::IcuData::init(),
+            tsc_data: tsc::TscData::init(),
+            cec_data: cec::CecData::init(),
         }
     }
 }
@@ -0,0 +1,570 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : fm.rs
+//!
+//! # Short description
+//! Failure manager with event memory
+//!
+//! # Detailed description
+//! Modules report the test results of their registered faults to the failure manager with fm_report.
+//! Each fault is debounced counter based: a failed result adds inc_step, a passed result subtracts dec_step; the
+//! fault is qualified failed at fail_threshold and qualified passed at pass_threshold (counter saturates there).
+//! The fault status is handled by status bits:
+//! FM_STATUS_TEST_FAILED : the last qualified result is failed
+//! FM_STATUS_PENDING     : the fault was reported failed at least once, but is not qualified yet; is cleared with
+//!                         a qualified passed result
+//! FM_STATUS_CONFIRMED   : the fault was qualified failed and is stored in the event memory; stays set until the
+//!                         event memory is cleared
+//!
+//! A confirmed fault is stored in the fixed-size event memory with occurrence counter and first / last timestamp
+//! (scheduler time in us). If the event memory is full, the overflow flag is set and the entry with the oldest last
+//! occurrence whose fault is currently not failed is displaced; if there is no such entry the new event is lost.
+//!
+//! The event memory is stored in the nvm block FmEventMem at each change and is loaded by fm_init at startup, so
+//! it is kept over resets.
+//!
+//! At startup fm_init reports the fault WatchdogReset with the reset cause of the last reset (failed for a watchdog
+//! reset), so a watchdog reset is stored in the event memory; the reset cause is also kept in the failure manager data.
+//!
+//! Each confirmed fault requests the reaction of the reaction table; the failure manager mode is the most severe
+//! reaction of all currently failed faults. At a mode change the action of the new mode is called once.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::appl::ledm::LedState;
+use crate::mcal::rcc;
+use crate::mcal::rcc::ResetCause;
+use crate::rte::RteData;
+use crate::servl::nvm::NvmBlockId;
+use crate::servl::sched;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+///action function that is called when the failure manager mode changes
+type FmActionFunction = fn(&mut RteData);
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///last qualified test result is failed
+pub const FM_STATUS_TEST_FAILED: u8 = 1 << 0;
+///fault reported failed, but not qualified yet
+pub const FM_STATUS_PENDING: u8 = 1 << 1;
+///fault qualified failed and stored in event memory
+pub const FM_STATUS_CONFIRMED: u8 = 1 << 2;
+
+///number of registered faults
+const FM_FAULT_NUM: usize = 3;
+
+///number of event memory entries
+pub const FM_EVENT_MEM_SIZE: usize = 8;
+
+///size of a serialized event memory entry : [fault u8][reserved u8][occurrences u16][first u64][last u64]
+const FM_EVENT_SIZE: usize = 20;
+///fault id of an empty serialized event memory entry
+const FM_EVENT_EMPTY: u8 = 0xFF;
+///size of the nvm block of the event memory : entries and overflow flag (padded to half-word)
+pub const FM_NVM_BLOCK_SIZE: usize = FM_EVENT_MEM_SIZE * FM_EVENT_SIZE + 2;
+
+///fault configuration; index is the FaultId
+const FM_FAULT_CFG: [FaultCfg; FM_FAULT_NUM] = [
+    FaultCfg {
+        inc_step: 1,
+        dec_step: 1,
+        fail_threshold: 1,
+        pass_threshold: -1,
+        reaction: FmMode::Degraded,
+    },
+    FaultCfg {
+        inc_step: 1,
+        dec_step: 1,
+        fail_threshold: 3,
+        pass_threshold: 0,
+        reaction: FmMode::Normal,
+    },
+    FaultCfg {
+        inc_step: 1,
+        dec_step: 1,
+        fail_threshold: 1,
+        pass_threshold: 0,
+        reaction: FmMode::Normal,
+    },
+];
+
+///actions called at a mode change; index is the FmMode
+const FM_MODE_ACTION: [FmActionFunction; 3] = [fm_normal_action, fm_degraded_action, fm_safe_state_action];
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///registered faults
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum FaultId {
+    SupvLedm = 0,
+    NvmWrite = 1,
+    WatchdogReset = 2,
+}
+
+impl FaultId {
+    const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(FaultId::SupvLedm),
+            1 => Some(FaultId::NvmWrite),
+            2 => Some(FaultId::WatchdogReset),
+            _ => None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum TestResult {
+    Passed,
+    Failed,
+}
+
+///failure manager mode; is also the reaction of a confirmed fault; ordered by severity
+#[allow(dead_code)]
+#[derive(PartialEq, PartialOrd, Copy, Clone)]
+pub enum FmMode {
+    Normal = 0,
+    Degraded = 1,
+    SafeState = 2,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct FaultCfg {
+    inc_step: i16,
+    dec_step: i16,
+    fail_threshold: i16,
+    pass_threshold: i16,
+    reaction: FmMode,
+}
+
+///event memory entry
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub struct FmEvent {
+    pub fault: FaultId,
+    pub occurrences: u16,
+    pub first_timestamp_us: u64,
+    pub last_timestamp_us: u64,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+pub struct FmData {
+    debounce_cnt: [i16; FM_FAULT_NUM],
+    status: [u8; FM_FAULT_NUM],
+    event_mem: [Option<FmEvent>; FM_EVENT_MEM_SIZE],
+    event_mem_overflow: bool,
+    mode: FmMode,
+    reset_cause: ResetCause,
+}
+
+impl FmData {
+    pub const fn init() -> Self {
+        Self {
+            debounce_cnt: [0; FM_FAULT_NUM],
+            status: [0; FM_FAULT_NUM],
+            event_mem: [None; FM_EVENT_MEM_SIZE],
+            event_mem_overflow: false,
+            mode: FmMode::Normal,
+            reset_cause: ResetCause::Unknown,
+        }
+    }
+
+    ///get the status bits of the fault
+    #[allow(dead_code)]
+    pub fn get_status(&self, fault: FaultId) -> u8 {
+        self.status[fault as usize]
+    }
+
+    #[allow(dead_code)]
+    pub fn get_mode(&self) -> FmMode {
+        self.mode
+    }
+
+    ///get the cause of the last reset
+    #[allow(dead_code)]
+    pub fn get_reset_cause(&self) -> ResetCause {
+        self.reset_cause
+    }
+
+    #[allow(dead_code)]
+    pub fn get_event_mem(&self) -> &[Option<FmEvent>; FM_EVENT_MEM_SIZE] {
+        &self.event_mem
+    }
+
+    #[allow(dead_code)]
+    pub fn is_event_mem_overflow(&self) -> bool {
+        self.event_mem_overflow
+    }
+
+    ///serialize the event memory for the nvm block; multi byte values are little endian
+    fn serialize(&self, data: &mut [u8; FM_NVM_BLOCK_SIZE]) {
+        data.fill(0);
+        let mut idx: usize = 0;
+        while idx < FM_EVENT_MEM_SIZE {
+            let entry: &mut [u8] = &mut data[idx * FM_EVENT_SIZE..(idx + 1) * FM_EVENT_SIZE];
+            match self.event_mem[idx] {
+                Some(event) => {
+                    entry[0] = event.fault as u8;
+                    entry[2..4].copy_from_slice(&event.occurrences.to_le_bytes());
+                    entry[4..12].copy_from_slice(&event.first_timestamp_us.to_le_bytes());
+                    entry[12..20].copy_from_slice(&event.last_timestamp_us.to_le_bytes());
+                }
+                None => entry[0] = FM_EVENT_EMPTY,
+            }
+            idx += 1;
+        }
+        data[FM_EVENT_MEM_SIZE * FM_EVENT_SIZE] = self.event_mem_overflow as u8;
+    }
+
+    ///restore the event memory from the nvm block; the stored faults get confirmed
+    fn deserialize(&mut self, data: &[u8; FM_NVM_BLOCK_SIZE]) {
+        let mut idx: usize = 0;
+        while idx < FM_EVENT_MEM_SIZE {
+            let entry: &[u8] = &data[idx * FM_EVENT_SIZE..(idx + 1) * FM_EVENT_SIZE];
+            self.event_mem[idx] = match FaultId::from_u8(entry[0]) {
+                Some(fault) => {
+                    self.status[fault as usize] |= FM_STATUS_CONFIRMED;
+                    Some(FmEvent {
+                        fault,
+                        occurrences: u16::from_le_bytes([entry[2], entry[3]]),
+                        first_timestamp_us: u64::from_le_bytes([
+                            entry[4], entry[5], entry[6], entry[7], entry[8], entry[9], entry[10], entry[11],
+                        ]),
+                        last_timestamp_us: u64::from_le_bytes([
+                            entry[12], entry[13], entry[14], entry[15], entry[16], entry[17], entry[18], entry[19],
+                        ]),
+                    })
+                }
+                None => None,
+            };
+            idx += 1;
+        }
+        self.event_mem_overflow = data[FM_EVENT_MEM_SIZE * FM_EVENT_SIZE] != 0;
+    }
+
+    ///store a confirmed fault to the event memory
+    fn store_event(&mut self, fault: FaultId, timestamp_us: u64) {
+        let mut free_idx: Option<usize> = None;
+        let mut displace_idx: Option<usize> = None;
+        let mut idx: usize = 0;
+        while idx < FM_EVENT_MEM_SIZE {
+            match self.event_mem[idx] {
+                Some(ref mut event) if event.fault == fault => {
+                    event.occurrences = event.occurrences.saturating_add(1);
+                    event.last_timestamp_us = timestamp_us;
+                    return;
+                }
+                Some(event) => {
+                    let displaceable: bool = (self.status[event.fault as usize] & FM_STATUS_TEST_FAILED) == 0;
+                    let older: bool = match displace_idx {
+                        Some(d) => match self.event_mem[d] {
+                            Some(d_event) => event.last_timestamp_us < d_event.last_timestamp_us,
+                            None => false,
+                        },
+                        None => true,
+                    };
+                    if displaceable && older {
+                        displace_idx = Some(idx);
+                    }
+                }
+                None => {
+                    if free_idx.is_none() {
+                        free_idx = Some(idx);
+                    }
+                }
+            }
+            idx += 1;
+        }
+
+        let store_idx: usize = match (free_idx, displace_idx) {
+            (Some(i), _) => i,
+            (None, Some(i)) => {
+                //the displaced fault is not in the event memory anymore
+                if let Some(event) = self.event_mem[i] {
+                    self.status[event.fault as usize] &= !FM_STATUS_CONFIRMED;
+                }
+                self.event_mem_overflow = true;
+                i
+            }
+            (None, None) => {
+                self.event_mem_overflow = true;
+                return;
+            }
+        };
+        self.event_mem[store_idx] = Some(FmEvent {
+            fault,
+            occurrences: 1,
+            first_timestamp_us: timestamp_us,
+            last_timestamp_us: timestamp_us,
+        });
+    }
+
+    ///get the most severe reaction of all currently failed faults
+    fn get_requested_mode(&self) -> FmMode {
+        let mut mode: FmMode = FmMode::Normal;
+        let mut idx: usize = 0;
+        while idx < FM_FAULT_NUM {
+            if (self.status[idx] & FM_STATUS_TEST_FAILED) != 0 && FM_FAULT_CFG[idx].reaction > mode {
+                mode = FM_FAULT_CFG[idx].reaction;
+            }
+            idx += 1;
+        }
+        mode
+    }
+}
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///load the event memory from the nvm and report the reset cause; must be called once at startup after nvm_init
+pub fn fm_init(rt_data: &mut RteData) {
+    let mut data: [u8; FM_NVM_BLOCK_SIZE] = [0; FM_NVM_BLOCK_SIZE];
+    if rt_data.nvm_data.read(NvmBlockId::FmEventMem, &mut data).is_ok() {
+        rt_data.fm_data.deserialize(&data);
+    }
+
+    let cause: ResetCause = rcc::get_reset_cause();
+    rt_data.fm_data.reset_cause = cause;
+    let result: TestResult = match cause {
+        ResetCause::IndependentWatchdog | ResetCause::WindowWatchdog => TestResult::Failed,
+        _ => TestResult::Passed,
+    };
+    fm_report(FaultId::WatchdogReset, result, rt_data);
+}
+
+///request the write of the event memory to the nvm
+fn fm_store(rt_data: &mut RteData) {
+    let mut data: [u8; FM_NVM_BLOCK_SIZE] = [0; FM_NVM_BLOCK_SIZE];
+    rt_data.fm_data.serialize(&mut data);
+    let _ = rt_data.nvm_data.write(NvmBlockId::FmEventMem, &data);
+}
+
+///report a test result of a fault to the failure manager
+pub fn fm_report(fault: FaultId, result: TestResult, rt_data: &mut RteData) {
+    let fm: &mut FmData = &mut rt_data.fm_data;
+    let idx: usize = fault as usize;
+    let cfg: &FaultCfg = &FM_FAULT_CFG[idx];
+    let mut stored: bool = false;
+
+    match result {
+        TestResult::Failed => {
+            //a qualified failed fault is not pending
+            if (fm.status[idx] & FM_STATUS_TEST_FAILED) == 0 {
+                fm.status[idx] |= FM_STATUS_PENDING;
+            }
+            fm.debounce_cnt[idx] = fm.debounce_cnt[idx]
+                .saturating_add(cfg.inc_step)
+                .min(cfg.fail_threshold);
+            if fm.debounce_cnt[idx] >= cfg.fail_threshold && (fm.status[idx] & FM_STATUS_TEST_FAILED) == 0 {
+                fm.status[idx] |= FM_STATUS_TEST_FAILED | FM_STATUS_CONFIRMED;
+                fm.status[idx] &= !FM_STATUS_PENDING;
+                fm.store_event(fault, sched::get_timestamp_ext_us());
+                stored = true;
+            }
+        }
+        TestResult::Passed => {
+            fm.debounce_cnt[idx] = fm.debounce_cnt[idx]
+                .saturating_sub(cfg.dec_step)
+                .max(cfg.pass_threshold);
+            if fm.debounce_cnt[idx] <= cfg.pass_threshold {
+                fm.status[idx] &= !(FM_STATUS_TEST_FAILED | FM_STATUS_PENDING);
+            }
+        }
+    }
+
+    let mode: FmMode = fm.get_requested_mode();
+    if mode != fm.mode {
+        fm.mode = mode;
+        (FM_MODE_ACTION[mode as usize])(rt_data);
+    }
+    if stored {
+        fm_store(rt_data);
+    }
+}
+
+///clear the event memory and the confirmed status of all faults
+#[allow(dead_code)]
+pub fn fm_clear_event_mem(rt_data: &mut RteData) {
+    let fm: &mut FmData = &mut rt_data.fm_data;
+    fm.event_mem = [None; FM_EVENT_MEM_SIZE];
+    fm.event_mem_overflow = false;
+    for status in fm.status.iter_mut() {
+        *status &= !FM_STATUS_CONFIRMED;
+    }
+    fm_store(rt_data);
+}
+
+///action for the change back to normal mode
+fn fm_normal_action(_rt_data: &mut RteData) {}
+
+///action for the change to degraded mode; the application modules check the mode and limit their functionality
+fn fm_degraded_action(_rt_data: &mut RteData) {}
+
+///action for the change to safe state; switch all leds off
+fn fm_safe_state_action(rt_data: &mut RteData) {
+    let mut idx: usize = 0;
+    while idx < rt_data.ledm_data.get_leds().len() {
+        rt_data.ledm_data.set_leds_state(idx, LedState::Off);
+        idx += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAILED_PENDING: u8 = FM_STATUS_TEST_FAILED | FM_STATUS_PENDING;
+
+    fn event(fault: FaultId, last_timestamp_us: u64) -> Option<FmEvent> {
+        Some(FmEvent {
+            fault,
+            occurrences: 1,
+            first_timestamp_us: last_timestamp_us,
+            last_timestamp_us,
+        })
+    }
+
+    #[test]
+    fn debounce_and_status_bits() {
+        let mut rt_data: RteData = RteData::init();
+        let fault: FaultId = FaultId::NvmWrite;
+        //fail_threshold 3 : pending until the third failed result
+        for _ in 0..2 {
+            fm_report(fault, TestResult::Failed, &mut rt_data);
+            assert_eq!(rt_data.fm_data.get_status(fault), FM_STATUS_PENDING);
+        }
+        fm_report(fault, TestResult::Failed, &mut rt_data);
+        assert_eq!(rt_data.fm_data.get_status(fault), FM_STATUS_TEST_FAILED | FM_STATUS_CONFIRMED);
+        //a qualified failed fault does not get pending again
+        fm_report(fault, TestResult::Failed, &mut rt_data);
+        assert_eq!(rt_data.fm_data.get_status(fault) & FAILED_PENDING, FM_STATUS_TEST_FAILED);
+        assert_eq!(rt_data.fm_data.get_event_mem()[0].map(|e| e.occurrences), Some(1));
+
+        //the counter saturates at the fail threshold, so 3 passed results are needed to get to pass_threshold 0
+        for _ in 0..2 {
+            fm_report(fault, TestResult::Passed, &mut rt_data);
+            assert_eq!(rt_data.fm_data.get_status(fault) & FAILED_PENDING, FM_STATUS_TEST_FAILED);
+        }
+        fm_report(fault, TestResult::Passed, &mut rt_data);
+        //confirmed stays set until the event memory is cleared
+        assert_eq!(rt_data.fm_data.get_status(fault), FM_STATUS_CONFIRMED);
+
+        //a new failure is pending again and the next qualification counts as occurrence
+        fm_report(fault, TestResult::Failed, &mut rt_data);
+        assert_eq!(rt_data.fm_data.get_status(fault), FM_STATUS_PENDING | FM_STATUS_CONFIRMED);
+        fm_report(fault, TestResult::Passed, &mut rt_data);
+        assert_eq!(rt_data.fm_data.get_status(fault), FM_STATUS_CONFIRMED);
+        for _ in 0..3 {
+            fm_report(fault, TestResult::Failed, &mut rt_data);
+        }
+        assert_eq!(rt_data.fm_data.get_event_mem()[0].map(|e| e.occurrences), Some(2));
+
+        fm_clear_event_mem(&mut rt_data);
+        assert_eq!(rt_data.fm_data.get_status(fault), FM_STATUS_TEST_FAILED);
+        assert!(rt_data.fm_data.get_event_mem().iter().all(|e| e.is_none()));
+    }
+
+    #[test]
+    fn mode_follows_failed_faults() {
+        let mut rt_data: RteData = RteData::init();
+        fm_report(FaultId::SupvLedm, TestResult::Failed, &mut rt_data);
+        assert!(rt_data.fm_data.get_mode() == FmMode::Degraded);
+        //pass_threshold -1 : 2 passed results are needed
+        fm_report(FaultId::SupvLedm, TestResult::Passed, &mut rt_data);
+        assert!(rt_data.fm_data.get_mode() == FmMode::Degraded);
+        fm_report(FaultId::SupvLedm, TestResult::Passed, &mut rt_data);
+        assert!(rt_data.fm_data.get_mode() == FmMode::Normal);
+    }
+
+    #[test]
+    fn event_mem_displaces_oldest_not_failed() {
+        let mut fm: FmData = FmData::init();
+        //full event memory; the oldest entry belongs to a currently failed fault
+        for (idx, entry) in fm.event_mem.iter_mut().enumerate() {
+            let fault: FaultId = if idx % 2 == 0 { FaultId::SupvLedm } else { FaultId::WatchdogReset };
+            *entry = event(fault, 1000 - 100 * idx as u64);
+        }
+        fm.status[FaultId::SupvLedm as usize] = FM_STATUS_TEST_FAILED | FM_STATUS_CONFIRMED;
+        fm.status[FaultId::WatchdogReset as usize] = FM_STATUS_CONFIRMED;
+        fm.status[FaultId::NvmWrite as usize] = FM_STATUS_TEST_FAILED | FM_STATUS_CONFIRMED;
+
+        fm.store_event(FaultId::NvmWrite, 5000);
+        //entry 7 is the oldest entry of the not failed fault WatchdogReset
+        assert!(fm.event_mem[7].is_some_and(|e| e.fault == FaultId::NvmWrite && e.first_timestamp_us == 5000));
+        assert!(fm.event_mem[6].is_some_and(|e| e.fault == FaultId::SupvLedm));
+        assert!(fm.is_event_mem_overflow());
+        assert_eq!(fm.status[FaultId::WatchdogReset as usize] & FM_STATUS_CONFIRMED, 0);
+    }
+
+    #[test]
+    fn event_mem_loses_event_if_all_failed() {
+        let mut fm: FmData = FmData::init();
+        for (idx, entry) in fm.event_mem.iter_mut().enumerate() {
+            *entry = event(FaultId::SupvLedm, idx as u64);
+        }
+        fm.status[FaultId::SupvLedm as usize] = FM_STATUS_TEST_FAILED | FM_STATUS_CONFIRMED;
+        let before: [Option<FmEvent>; FM_EVENT_MEM_SIZE] = fm.event_mem;
+
+        fm.store_event(FaultId::NvmWrite, 5000);
+        assert!(fm.is_event_mem_overflow());
+        for (entry, old) in fm.event_mem.iter().zip(before.iter()) {
+            assert_eq!(entry.map(|e| e.last_timestamp_us), old.map(|e| e.last_timestamp_us));
+        }
+    }
+
+    #[test]
+    fn event_mem_survives_nvm_roundtrip() {
+        let mut rt_data: RteData = RteData::init();
+        fm_report(FaultId::SupvLedm, TestResult::Failed, &mut rt_data);
+        //the write of the event memory is requested at the change
+        assert!(!rt_data.nvm_data.is_idle());
+        let mut data: [u8; FM_NVM_BLOCK_SIZE] = [0; FM_NVM_BLOCK_SIZE];
+        rt_data.fm_data.serialize(&mut data);
+
+        let mut fm: FmData = FmData::init();
+        fm.deserialize(&data);
+        assert_eq!(fm.get_status(FaultId::SupvLedm), FM_STATUS_CONFIRMED);
+        assert!(fm.event_mem[0].is_some_and(|e| e.fault == FaultId::SupvLedm && e.occurrences == 1));
+        assert!(fm.event_mem[1..].iter().all(|e| e.is_none()));
+    }
+}
@@ -26,7 +26,10 @@
 //---------------------------------------------------------------------------------------------------------------------
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
+pub mod fm;
+pub mod nvm;
 pub mod sched;
+pub mod supv;
 pub mod swtimer;
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,770 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : nvm.rs
+//!
+//! # Short description
+//! Non volatile memory manager storing registered data blocks in a reserved flash area
+//!
+//! # Detailed description
+//! The reserved flash area (see linker script, NVM region) consists of NVM_PAGE_NUM flash pages used as ring.
+//! The data of the registered blocks is appended as records to the active page; a new record of a block replaces
+//! the older ones. If the active page is full, the next page of the ring is erased and gets the active page; the
+//! latest data of all blocks is written to the new page first. So the pages are erased in sequence, which spreads
+//! the flash wear over all pages.
+//!
+//! Page layout : [page sequence u32][magic u16][reserved u16][records...]
+//! Record layout : [block id u16][data length u16][record sequence u32][data, padded to half-words][crc32 u32]
+//!
+//! The magic of a page and the crc of a record are programmed last, so a page or a record that was not completely
+//! written because of a power loss is detected and ignored at startup. At startup all pages are scanned and the
+//! valid record with the highest record sequence of each block is loaded to the ram mirror of the block. If the
+//! active page does not contain the latest data of all blocks (power loss during a page change), the missing
+//! blocks are written again.
+//!
+//! The records are programmed by nvm_task, which must be called periodically by a task; each call programs at
+//! most NVM_HALFWORDS_PER_CALL half-words, so the flash stall of the programming fits into the task time. A page
+//! erase stalls the flash for up to FLASH_PAGE_ERASE_MAX_US, which is longer than a timeslot: nvm_task only requests
+//! the erase and the scheduler calls nvm_erase at the end of the schedule cycle (see sched_maintenance) directly
+//! between two window watchdog refreshes; the watchdog timeout is sized to cover the erase (see WWDG_CFG, checked in
+//! the scheduler), so the erase does not cause a watchdog reset. Erase and programming wait for
+//! the end of the operation in the ram routine of the flash module, so no code is fetched from the stalled flash.
+//! NvmData::write only updates the ram mirror and requests the write.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::common::crc::crc32;
+use crate::common::crc::crc32_update;
+use crate::common::crc::CRC32_INIT;
+#[cfg(not(test))]
+use crate::mcal::flash::flash_erase_page;
+#[cfg(not(test))]
+use crate::mcal::flash::flash_program_halfword;
+use crate::mcal::flash::FlashError;
+use crate::mcal::flash::FLASH_PAGE_SIZE;
+use crate::mcal::flash::FLASH_PROGRAM_HALFWORD_MAX_US;
+use crate::rte::RteData;
+use crate::servl::fm;
+use crate::servl::fm::FaultId;
+use crate::servl::fm::TestResult;
+use crate::servl::fm::FM_NVM_BLOCK_SIZE;
+use crate::servl::sched;
+use core::ptr;
+#[cfg(test)]
+use tests::{sim_erase_page as flash_erase_page, sim_program_halfword as flash_program_halfword};
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///start address of the reserved flash area; must match the NVM region of the linker script
+const NVM_START_ADDR: u32 = 0x0803_E000;
+///number of flash pages of the reserved flash area
+const NVM_PAGE_NUM: usize = 4;
+
+const NVM_PAGE_MAGIC: u16 = 0xA55A;
+const NVM_PAGE_HEADER_SIZE: u32 = 8;
+const NVM_RECORD_HEADER_SIZE: u32 = 8;
+const NVM_RECORD_CRC_SIZE: u32 = 4;
+
+///number of half-words programmed per nvm_task call at most
+const NVM_HALFWORDS_PER_CALL: u32 = 2;
+///number of write attempts of a block before the write is given up
+const NVM_MAX_RETRIES: u8 = 3;
+
+///number of registered blocks
+const NVM_BLOCK_NUM: usize = 1;
+
+///block configuration; index is the NvmBlockId; offset is the offset of the block in the ram mirror
+const NVM_BLOCK_CFG: [BlockCfg; NVM_BLOCK_NUM] = [BlockCfg {
+    offset: 0,
+    len: FM_NVM_BLOCK_SIZE,
+}];
+
+///size of the ram mirror of all blocks
+const NVM_MIRROR_SIZE: usize = FM_NVM_BLOCK_SIZE;
+///size of the biggest block
+const NVM_MAX_BLOCK_SIZE: usize = FM_NVM_BLOCK_SIZE;
+///size of the job buffer holding a complete record
+const NVM_JOB_BUF_SIZE: usize = NVM_MAX_BLOCK_SIZE + 1 + (NVM_RECORD_HEADER_SIZE + NVM_RECORD_CRC_SIZE) as usize;
+
+//after a page change the latest data of all blocks must fit into the new page
+const _: () = assert!(
+    NVM_MIRROR_SIZE as u32
+        + NVM_BLOCK_NUM as u32 * (NVM_RECORD_HEADER_SIZE + NVM_RECORD_CRC_SIZE + 1)
+        + NVM_PAGE_HEADER_SIZE
+        <= FLASH_PAGE_SIZE
+);
+//the flash stall of the programming in one nvm_task call must fit into the task time
+const _: () = assert!(NVM_HALFWORDS_PER_CALL * FLASH_PROGRAM_HALFWORD_MAX_US < sched::SCHED_TASK_TIME_US);
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///registered blocks
+#[derive(PartialEq, Copy, Clone)]
+pub enum NvmBlockId {
+    FmEventMem = 0,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum NvmError {
+    NoData,
+    Length,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+enum NvmState {
+    Idle,
+    Erase,
+    Program,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+enum JobKind {
+    PageHeader,
+    Record(usize),
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct BlockCfg {
+    offset: usize,
+    len: usize,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+pub struct NvmData {
+    state: NvmState,
+    active_page: usize,
+    page_seq: u32,
+    write_off: u32,
+    record_seq: u32,
+    mirror: [u8; NVM_MIRROR_SIZE],
+    valid: [bool; NVM_BLOCK_NUM],
+    pending: [bool; NVM_BLOCK_NUM],
+    retries: u8,
+    job_kind: JobKind,
+    job_buf: [u8; NVM_JOB_BUF_SIZE],
+    job_len: u32,
+    job_addr: u32,
+    job_idx: u32,
+    last_error: Option<FlashError>,
+}
+
+impl NvmData {
+    pub const fn init() -> Self {
+        Self {
+            state: NvmState::Idle,
+            active_page: NVM_PAGE_NUM - 1,
+            page_seq: 0,
+            write_off: FLASH_PAGE_SIZE,
+            record_seq: 0,
+            mirror: [0; NVM_MIRROR_SIZE],
+            valid: [false; NVM_BLOCK_NUM],
+            pending: [false; NVM_BLOCK_NUM],
+            retries: 0,
+            job_kind: JobKind::PageHeader,
+            job_buf: [0xFF; NVM_JOB_BUF_SIZE],
+            job_len: 0,
+            job_addr: 0,
+            job_idx: 0,
+            last_error: None,
+        }
+    }
+
+    ///true if a page erase is requested; the erase is done by nvm_erase
+    pub fn is_erase_requested(&self) -> bool {
+        self.state == NvmState::Erase
+    }
+
+    ///true if no write is pending or active
+    #[allow(dead_code)]
+    pub fn is_idle(&self) -> bool {
+        self.state == NvmState::Idle && !self.pending.iter().any(|p| *p)
+    }
+
+    #[allow(dead_code)]
+    pub fn get_last_error(&self) -> Option<FlashError> {
+        self.last_error
+    }
+
+    ///copy the block data from the ram mirror; fails if the block was never written
+    pub fn read(&self, block: NvmBlockId, data: &mut [u8]) -> Result<(), NvmError> {
+        let cfg: &BlockCfg = &NVM_BLOCK_CFG[block as usize];
+        if data.len() != cfg.len {
+            return Err(NvmError::Length);
+        }
+        if !self.valid[block as usize] {
+            return Err(NvmError::NoData);
+        }
+        data.copy_from_slice(&self.mirror[cfg.offset..cfg.offset + cfg.len]);
+        Ok(())
+    }
+
+    ///update the ram mirror of the block and request the write to flash
+    pub fn write(&mut self, block: NvmBlockId, data: &[u8]) -> Result<(), NvmError> {
+        let cfg: &BlockCfg = &NVM_BLOCK_CFG[block as usize];
+        if data.len() != cfg.len {
+            return Err(NvmError::Length);
+        }
+        self.mirror[cfg.offset..cfg.offset + cfg.len].copy_from_slice(data);
+        self.pending[block as usize] = true;
+        self.retries = 0;
+        Ok(())
+    }
+
+    ///start the next job if idle; a page change requests the erase of the next page
+    fn start_job(&mut self) {
+        let block: usize = match self.pending.iter().position(|p| *p) {
+            Some(b) => b,
+            None => return,
+        };
+        self.job_idx = 0;
+        if self.page_seq == 0 || self.write_off + record_size(NVM_BLOCK_CFG[block].len) > FLASH_PAGE_SIZE {
+            //page change; the job is continued by nvm_erase
+            let next_page: usize = (self.active_page + 1) % NVM_PAGE_NUM;
+            self.job_addr = page_addr(next_page);
+            self.state = NvmState::Erase;
+        } else {
+            self.build_record(block);
+            self.pending[block] = false;
+            self.state = NvmState::Program;
+        }
+    }
+
+    ///write the record of the block from the ram mirror into the job buffer
+    fn build_record(&mut self, block: usize) {
+        let cfg: &BlockCfg = &NVM_BLOCK_CFG[block];
+        self.record_seq = self.record_seq.wrapping_add(1);
+        let data_size: usize = (cfg.len + 1) & !1;
+        self.job_buf[0..2].copy_from_slice(&(block as u16).to_le_bytes());
+        self.job_buf[2..4].copy_from_slice(&(cfg.len as u16).to_le_bytes());
+        self.job_buf[4..8].copy_from_slice(&self.record_seq.to_le_bytes());
+        self.job_buf[8..8 + cfg.len].copy_from_slice(&self.mirror[cfg.offset..cfg.offset + cfg.len]);
+        self.job_buf[8 + cfg.len..8 + data_size].fill(0xFF);
+        let crc: u32 = crc32(&self.job_buf[0..8 + data_size]);
+        self.job_buf[8 + data_size..12 + data_size].copy_from_slice(&crc.to_le_bytes());
+        self.job_kind = JobKind::Record(block);
+        self.job_len = record_size(cfg.len);
+        self.job_addr = page_addr(self.active_page) + self.write_off;
+    }
+
+    ///handle a failed flash operation; the active page is handled as full, so the retry is done on a fresh page
+    fn handle_error(&mut self, error: FlashError) {
+        self.last_error = Some(error);
+        self.write_off = FLASH_PAGE_SIZE;
+        if let JobKind::Record(block) = self.job_kind {
+            self.pending[block] = true;
+        }
+        self.retries += 1;
+        if self.retries >= NVM_MAX_RETRIES {
+            //give up until the next write request
+            self.pending = [false; NVM_BLOCK_NUM];
+        }
+        self.state = NvmState::Idle;
+    }
+}
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+fn page_addr(page: usize) -> u32 {
+    NVM_START_ADDR + page as u32 * FLASH_PAGE_SIZE
+}
+
+///size of a record with the given data length in bytes
+const fn record_size(len: usize) -> u32 {
+    NVM_RECORD_HEADER_SIZE + ((len as u32 + 1) & !1) + NVM_RECORD_CRC_SIZE
+}
+
+///get the pointer to the flash content at the address
+#[cfg(not(test))]
+fn flash_ptr(addr: u32) -> *const u8 {
+    addr as *const u8
+}
+
+///the host test build reads the simulated flash
+#[cfg(test)]
+fn flash_ptr(addr: u32) -> *const u8 {
+    tests::sim_ptr(addr)
+}
+
+fn read_u16(addr: u32) -> u16 {
+    unsafe { ptr::read_volatile(flash_ptr(addr) as *const u16) }
+}
+
+fn read_u32(addr: u32) -> u32 {
+    (read_u16(addr) as u32) | ((read_u16(addr + 2) as u32) << 16)
+}
+
+fn flash_slice(addr: u32, len: u32) -> &'static [u8] {
+    unsafe { core::slice::from_raw_parts(flash_ptr(addr), len as usize) }
+}
+
+///scan the reserved flash area and load the latest valid data of all blocks to the ram mirror; must be called
+///once at startup before the blocks are read
+pub fn nvm_init(rt_data: &mut RteData) {
+    let nvm: &mut NvmData = &mut rt_data.nvm_data;
+    //location of the latest record per block : (page, offset, record sequence)
+    let mut latest: [Option<(usize, u32, u32)>; NVM_BLOCK_NUM] = [None; NVM_BLOCK_NUM];
+
+    let mut page: usize = 0;
+    while page < NVM_PAGE_NUM {
+        let base: u32 = page_addr(page);
+        if read_u16(base + 4) == NVM_PAGE_MAGIC {
+            let page_seq: u32 = read_u32(base);
+            let mut off: u32 = NVM_PAGE_HEADER_SIZE;
+            while off + NVM_RECORD_HEADER_SIZE + NVM_RECORD_CRC_SIZE <= FLASH_PAGE_SIZE {
+                let block: u16 = read_u16(base + off);
+                let len: u16 = read_u16(base + off + 2);
+                if block == 0xFFFF && len == 0xFFFF {
+                    //start of the erased area
+                    break;
+                }
+                let size: u32 = record_size(len as usize);
+                if len == 0xFFFF || off + size > FLASH_PAGE_SIZE {
+                    //broken record header; the rest of the page can not be used
+                    off = FLASH_PAGE_SIZE;
+                    break;
+                }
+                let data_size: u32 = size - NVM_RECORD_CRC_SIZE;
+                let crc: u32 = crc32_update(CRC32_INIT, flash_slice(base + off, data_size));
+                let seq: u32 = read_u32(base + off + 4);
+                if crc == read_u32(base + off + data_size)
+                    && (block as usize) < NVM_BLOCK_NUM
+                    && len as usize == NVM_BLOCK_CFG[block as usize].len
+                {
+                    let newer: bool = match latest[block as usize] {
+                        Some((_, _, latest_seq)) => seq.wrapping_sub(latest_seq) as i32 > 0,
+                        None => true,
+                    };
+                    if newer {
+                        latest[block as usize] = Some((page, off, seq));
+                    }
+                    if seq.wrapping_sub(nvm.record_seq) as i32 > 0 {
+                        nvm.record_seq = seq;
+                    }
+                }
+                off += size;
+            }
+            if nvm.page_seq == 0 || page_seq.wrapping_sub(nvm.page_seq) as i32 > 0 {
+                nvm.page_seq = page_seq;
+                nvm.active_page = page;
+                nvm.write_off = off;
+            }
+        }
+        page += 1;
+    }
+
+    let mut block: usize = 0;
+    while block < NVM_BLOCK_NUM {
+        if let Some((page, off, _)) = latest[block] {
+            let cfg: &BlockCfg = &NVM_BLOCK_CFG[block];
+            let src: &[u8] = flash_slice(page_addr(page) + off + NVM_RECORD_HEADER_SIZE, cfg.len as u32);
+            nvm.mirror[cfg.offset..cfg.offset + cfg.len].copy_from_slice(src);
+            nvm.valid[block] = true;
+            //interrupted page change; the active page must contain the latest data of all blocks
+            nvm.pending[block] = page != nvm.active_page;
+        }
+        block += 1;
+    }
+}
+
+///nvm main function; must be called periodically by a task
+pub fn nvm_task(rt_data: &mut RteData) {
+    let nvm: &mut NvmData = &mut rt_data.nvm_data;
+    let mut result: Option<TestResult> = None;
+
+    match nvm.state {
+        NvmState::Idle => nvm.start_job(),
+        //the erase is done by nvm_erase
+        NvmState::Erase => {}
+        NvmState::Program => {
+            let mut cnt: u32 = 0;
+            while cnt < NVM_HALFWORDS_PER_CALL {
+                if nvm.job_idx >= nvm.job_len {
+                    match nvm.job_kind {
+                        JobKind::PageHeader => {
+                            //the new page must get the latest data of all blocks
+                            let mut block: usize = 0;
+                            while block < NVM_BLOCK_NUM {
+                                nvm.pending[block] |= nvm.valid[block];
+                                block += 1;
+                            }
+                        }
+                        JobKind::Record(block) => {
+                            nvm.write_off += nvm.job_len;
+                            nvm.valid[block] = true;
+                            nvm.retries = 0;
+                            result = Some(TestResult::Passed);
+                        }
+                    }
+                    nvm.state = NvmState::Idle;
+                    break;
+                }
+                let idx: usize = nvm.job_idx as usize;
+                let value: u16 = u16::from_le_bytes([nvm.job_buf[idx], nvm.job_buf[idx + 1]]);
+                if let Err(e) = flash_program_halfword(nvm.job_addr + nvm.job_idx, value) {
+                    nvm.handle_error(e);
+                    result = Some(TestResult::Failed);
+                    break;
+                }
+                nvm.job_idx += 2;
+                cnt += 1;
+            }
+        }
+    }
+
+    if let Some(test_result) = result {
+        fm::fm_report(FaultId::NvmWrite, test_result, rt_data);
+    }
+}
+
+///erase the next page of the ring if requested by nvm_task; blocks for up to FLASH_PAGE_ERASE_MAX_US, so it must be
+///called by the scheduler outside of the timeslots
+pub fn nvm_erase(rt_data: &mut RteData) {
+    let nvm: &mut NvmData = &mut rt_data.nvm_data;
+    if nvm.state != NvmState::Erase {
+        return;
+    }
+    if let Err(e) = flash_erase_page(nvm.job_addr) {
+        nvm.handle_error(e);
+        fm::fm_report(FaultId::NvmWrite, TestResult::Failed, rt_data);
+        return;
+    }
+    nvm.active_page = (nvm.active_page + 1) % NVM_PAGE_NUM;
+    nvm.page_seq = nvm.page_seq.wrapping_add(1);
+    nvm.write_off = NVM_PAGE_HEADER_SIZE;
+    nvm.job_buf[0..4].copy_from_slice(&nvm.page_seq.to_le_bytes());
+    nvm.job_buf[4..6].copy_from_slice(&NVM_PAGE_MAGIC.to_le_bytes());
+    nvm.job_kind = JobKind::PageHeader;
+    nvm.job_len = 6;
+    nvm.job_idx = 0;
+    nvm.state = NvmState::Program;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    ///size of the reserved flash area in half-words
+    const SIM_HALFWORDS: usize = NVM_PAGE_NUM * FLASH_PAGE_SIZE as usize / 2;
+    ///size of a record of the event memory block in half-words
+    const RECORD_HALFWORDS: u32 = record_size(FM_NVM_BLOCK_SIZE) / 2;
+
+    thread_local! {
+        ///simulated reserved flash area of the test thread; half-words for the alignment
+        static SIM_FLASH: Cell<*mut u16> =
+            Cell::new(Box::leak(vec![0xFFFF_u16; SIM_HALFWORDS].into_boxed_slice()).as_mut_ptr());
+        ///number of flash operations until the power loss; the operations after the power loss are not done
+        static SIM_OPS_LEFT: Cell<Option<u32>> = const { Cell::new(None) };
+    }
+
+    fn sim_idx(addr: u32) -> usize {
+        assert!((NVM_START_ADDR..NVM_START_ADDR + NVM_PAGE_NUM as u32 * FLASH_PAGE_SIZE).contains(&addr));
+        ((addr - NVM_START_ADDR) / 2) as usize
+    }
+
+    fn sim_read(addr: u32) -> u16 {
+        unsafe { *SIM_FLASH.with(|f| f.get()).add(sim_idx(addr)) }
+    }
+
+    fn sim_write(addr: u32, value: u16) {
+        unsafe { *SIM_FLASH.with(|f| f.get()).add(sim_idx(addr)) = value };
+    }
+
+    fn sim_save() -> Vec<u16> {
+        (0..SIM_HALFWORDS).map(|i| sim_read(NVM_START_ADDR + 2 * i as u32)).collect()
+    }
+
+    fn sim_restore(content: &[u16]) {
+        for (i, value) in content.iter().enumerate() {
+            sim_write(NVM_START_ADDR + 2 * i as u32, *value);
+        }
+    }
+
+    ///true if the operation is done; false after the power loss
+    fn sim_power_on() -> bool {
+        match SIM_OPS_LEFT.get() {
+            Some(0) => false,
+            Some(n) => {
+                SIM_OPS_LEFT.set(Some(n - 1));
+                true
+            }
+            None => true,
+        }
+    }
+
+    pub fn sim_ptr(addr: u32) -> *const u8 {
+        unsafe { (SIM_FLASH.with(|f| f.get()) as *const u8).add(addr as usize - NVM_START_ADDR as usize) }
+    }
+
+    pub fn sim_erase_page(addr: u32) -> Result<(), FlashError> {
+        let start: u32 = addr - (addr - NVM_START_ADDR) % FLASH_PAGE_SIZE;
+        if sim_power_on() {
+            for off in (0..FLASH_PAGE_SIZE).step_by(2) {
+                sim_write(start + off, 0xFFFF);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn sim_program_halfword(addr: u32, value: u16) -> Result<(), FlashError> {
+        //the flash reports a programming error for a half-word that is not erased
+        if sim_read(addr) != 0xFFFF {
+            return Err(FlashError::Programming);
+        }
+        if sim_power_on() {
+            sim_write(addr, value);
+        }
+        Ok(())
+    }
+
+    ///call nvm_task and the erase of the scheduler maintenance until all writes are done
+    fn run(rt_data: &mut RteData) {
+        for _ in 0..10_000 {
+            nvm_task(rt_data);
+            if rt_data.nvm_data.is_erase_requested() {
+                nvm_erase(rt_data);
+            }
+            if rt_data.nvm_data.is_idle() {
+                return;
+            }
+        }
+        panic!("nvm write not finished");
+    }
+
+    ///restart after a power loss: the flash content is kept, the ram is initialized
+    fn reboot() -> RteData {
+        SIM_OPS_LEFT.set(None);
+        let mut rt_data: RteData = RteData::init();
+        nvm_init(&mut rt_data);
+        rt_data
+    }
+
+    fn block_data(fill: u8) -> [u8; FM_NVM_BLOCK_SIZE] {
+        core::array::from_fn(|i| fill ^ (i as u8))
+    }
+
+    fn write_block(rt_data: &mut RteData, fill: u8) {
+        assert!(rt_data.nvm_data.write(NvmBlockId::FmEventMem, &block_data(fill)).is_ok());
+        run(rt_data);
+    }
+
+    ///get the fill value of the block data, None if there is no data
+    fn read_block(rt_data: &RteData) -> Option<u8> {
+        let mut data: [u8; FM_NVM_BLOCK_SIZE] = [0; FM_NVM_BLOCK_SIZE];
+        rt_data.nvm_data.read(NvmBlockId::FmEventMem, &mut data).ok()?;
+        assert!(data == block_data(data[0]));
+        Some(data[0])
+    }
+
+    ///get the page sequence of all pages with a valid header
+    fn page_seqs() -> [Option<u32>; NVM_PAGE_NUM] {
+        core::array::from_fn(|page| {
+            let base: u32 = page_addr(page);
+            (sim_read(base + 4) == NVM_PAGE_MAGIC).then(|| read_u32(base))
+        })
+    }
+
+    #[test]
+    fn record_layout_and_crc() {
+        let mut rt_data: RteData = reboot();
+        assert!(read_block(&rt_data).is_none());
+        write_block(&mut rt_data, 0x5A);
+
+        let base: u32 = page_addr(0);
+        assert_eq!(page_seqs(), [Some(1), None, None, None]);
+        let rec: u32 = base + NVM_PAGE_HEADER_SIZE;
+        assert_eq!(read_u16(rec), NvmBlockId::FmEventMem as u16);
+        assert_eq!(read_u16(rec + 2) as usize, FM_NVM_BLOCK_SIZE);
+        assert_eq!(read_u32(rec + 4), 1);
+        assert_eq!(flash_slice(rec + NVM_RECORD_HEADER_SIZE, FM_NVM_BLOCK_SIZE as u32), block_data(0x5A));
+        let crc_off: u32 = record_size(FM_NVM_BLOCK_SIZE) - NVM_RECORD_CRC_SIZE;
+        assert_eq!(read_u32(rec + crc_off), crc32(flash_slice(rec, crc_off)));
+        assert_eq!(read_block(&reboot()), Some(0x5A));
+
+        //a changed bit of the data or of the crc invalidates the record
+        let data_addr: u32 = rec + NVM_RECORD_HEADER_SIZE + 10;
+        let data: u16 = sim_read(data_addr);
+        sim_write(data_addr, data ^ 0x0100);
+        assert!(read_block(&reboot()).is_none());
+        sim_write(data_addr, data);
+        sim_write(rec + crc_off, sim_read(rec + crc_off) ^ 1);
+        assert!(read_block(&reboot()).is_none());
+    }
+
+    #[test]
+    fn latest_record_over_page_ring() {
+        let mut rt_data: RteData = reboot();
+        //more records than fit into all pages, so the ring wraps
+        let records_per_page: u32 = (FLASH_PAGE_SIZE - NVM_PAGE_HEADER_SIZE) / record_size(FM_NVM_BLOCK_SIZE);
+        let writes: u32 = 3 * NVM_PAGE_NUM as u32 * records_per_page;
+        for fill in 0..writes as u8 {
+            write_block(&mut rt_data, fill);
+            let rebooted: RteData = reboot();
+            assert_eq!(read_block(&rebooted), Some(fill));
+            assert_eq!(rebooted.nvm_data.active_page, rt_data.nvm_data.active_page);
+            assert_eq!(rebooted.nvm_data.write_off, rt_data.nvm_data.write_off);
+            //the active page contains the latest data, nothing to write again
+            assert!(rebooted.nvm_data.is_idle());
+        }
+        //the pages are used in sequence
+        let seqs: [Option<u32>; NVM_PAGE_NUM] = page_seqs();
+        let active: usize = rt_data.nvm_data.active_page;
+        for (page, seq) in seqs.iter().enumerate() {
+            let age: u32 = ((active + NVM_PAGE_NUM - page) % NVM_PAGE_NUM) as u32;
+            assert_eq!(*seq, Some(rt_data.nvm_data.page_seq - age));
+        }
+    }
+
+    #[test]
+    fn sequence_wraparound() {
+        let mut rt_data: RteData = reboot();
+        rt_data.nvm_data.page_seq = u32::MAX - 1;
+        rt_data.nvm_data.record_seq = u32::MAX - 15;
+        for fill in 0..40 {
+            write_block(&mut rt_data, fill);
+            rt_data = reboot();
+            assert_eq!(read_block(&rt_data), Some(fill));
+        }
+    }
+
+    #[test]
+    fn power_loss_in_record() {
+        for cut in 0..RECORD_HALFWORDS {
+            //start on an erased flash
+            sim_restore(&[0xFFFF; SIM_HALFWORDS]);
+            let mut rt_data: RteData = reboot();
+            write_block(&mut rt_data, 1);
+            SIM_OPS_LEFT.set(Some(cut));
+            write_block(&mut rt_data, 2);
+
+            //the crc is programmed last, so the torn record is ignored
+            let mut rt_data: RteData = reboot();
+            assert_eq!(read_block(&rt_data), Some(1), "cut {}", cut);
+            assert!(rt_data.nvm_data.is_idle());
+            write_block(&mut rt_data, 3);
+            assert_eq!(read_block(&reboot()), Some(3), "cut {}", cut);
+        }
+    }
+
+    #[test]
+    fn power_loss_in_page_change() {
+        let mut rt_data: RteData = reboot();
+        let mut fill: u8 = 0;
+        //fill the second page, so the next write changes the page
+        while rt_data.nvm_data.page_seq < 2
+            || rt_data.nvm_data.write_off + record_size(FM_NVM_BLOCK_SIZE) <= FLASH_PAGE_SIZE
+        {
+            fill += 1;
+            write_block(&mut rt_data, fill);
+        }
+        let old_page: usize = rt_data.nvm_data.active_page;
+        let full: Vec<u16> = sim_save();
+        //the page change is the erase, the page header (3 half-words) and the copy of the block
+        let header_ops: u32 = 1 + 3;
+        for cut in 0..header_ops + RECORD_HALFWORDS {
+            sim_restore(&full);
+            let mut rt_data: RteData = reboot();
+            SIM_OPS_LEFT.set(Some(cut));
+            write_block(&mut rt_data, fill + 1);
+
+            let mut rt_data: RteData = reboot();
+            assert_eq!(read_block(&rt_data), Some(fill), "cut {}", cut);
+            //the page magic is programmed last
+            let page_valid: bool = cut >= header_ops;
+            let expected_page: usize = if page_valid { (old_page + 1) % NVM_PAGE_NUM } else { old_page };
+            assert_eq!(rt_data.nvm_data.active_page, expected_page, "cut {}", cut);
+            //a valid new page does not contain the latest data yet, so it is written again
+            assert_eq!(rt_data.nvm_data.is_idle(), !page_valid, "cut {}", cut);
+            run(&mut rt_data);
+            let rebooted: RteData = reboot();
+            assert_eq!(read_block(&rebooted), Some(fill), "cut {}", cut);
+            //a record header without length makes the rest of the page unusable, so the page is changed again
+            let expected_page: usize =
+                if cut == header_ops + 1 { (expected_page + 1) % NVM_PAGE_NUM } else { expected_page };
+            assert_eq!(rebooted.nvm_data.active_page, expected_page, "cut {}", cut);
+            assert!(rebooted.nvm_data.is_idle());
+        }
+    }
+
+    ///fill all pages of the ring and get the latest fill value
+    fn fill_ring(rt_data: &mut RteData) -> u8 {
+        let mut fill: u8 = 0;
+        while rt_data.nvm_data.page_seq <= NVM_PAGE_NUM as u32 {
+            fill += 1;
+            write_block(rt_data, fill);
+        }
+        fill
+    }
+
+    #[test]
+    fn half_erased_page() {
+        //power loss in the erase of the next page; the part of the page that is not erased yet keeps its content or
+        //has random content
+        let erased_parts: [(u32, u32, Option<u16>); 4] = [
+            (0, FLASH_PAGE_SIZE / 2, None),
+            (0, 8, None),
+            (FLASH_PAGE_SIZE / 2, FLASH_PAGE_SIZE, None),
+            (200, FLASH_PAGE_SIZE, Some(0x1234)),
+        ];
+        for (start, end, random) in erased_parts.iter() {
+            sim_restore(&[0xFFFF; SIM_HALFWORDS]);
+            let mut rt_data: RteData = reboot();
+            let fill: u8 = fill_ring(&mut rt_data);
+            let next: u32 = page_addr((rt_data.nvm_data.active_page + 1) % NVM_PAGE_NUM);
+            for off in (*start..*end).step_by(2) {
+                sim_write(next + off, random.map_or(0xFFFF, |r| r ^ off as u16));
+            }
+
+            //the old records of the next page are not newer than the latest record
+            let mut rt_data: RteData = reboot();
+            assert_eq!(read_block(&rt_data), Some(fill));
+            assert!(rt_data.nvm_data.is_idle());
+            //the next page change erases the page again
+            for more in 1..=12 {
+                write_block(&mut rt_data, fill + more);
+                assert_eq!(read_block(&reboot()), Some(fill + more));
+            }
+        }
+    }
+}
@@ -21,6 +21,26 @@
 //! This module implements the scheduling mechanism.
 //! @todo : expand comment!
 //!
+//! A scheduler mode change is requested with sched_request_mode and is done at the end of the schedule cycle:
+//! - Stop: the Mcu enters Stop until a configured wakeup event; the scheduler time does not advance in Stop, the
+//!   scheduling continues in Run mode after the wakeup
+//! - Shutdown: the pending nvm writes are finished (at most SCHED_SHUTDOWN_MAX_CYCLES schedule cycles), then the Mcu
+//!   enters Standby, which is left by a reset only
+//!
+//! Operations that stall the flash for longer than a timeslot (the nvm page erase) are done by sched_maintenance at
+//! the end of the schedule cycle; the scheduler time does not advance during the maintenance. The maintenance is done
+//! directly after the watchdog refresh and refreshes the watchdog again after the erase, so the watchdog timeout must
+//! only cover the erase time, and the shortest erase time must be out of the watchdog window. The watchdog timing is
+//! checked for the configured clock and for the apb clocks of the 48 MHz clock setups.
+//!
+//! The timeslots are timed by the counter of the time base SCHED_TIME_BASE: TIM6 or the SysTick of the core (for
+//! a Cortex-M without TIM6 or to use TIM6 as dac trigger).
+//!
+//! The window watchdog is started by sched_run and refreshed at the end of each schedule cycle, so the schedule
+//! cycle time must be in the watchdog window. The early wakeup flag of the watchdog is polled in every timeslot;
+//! when it is set, the active task slot and the scheduler time are stored in the no-init ram and are reported by
+//! get_wwdg_diag after the watchdog reset.
+//!
 //!
 //!
 
@@ -29,9 +49,30 @@
 //---------------------------------------------------------------------------------------------------------------------
 use crate::appl::ledm;
 use crate::common::util::create_volatile;
+use crate::rte;
+use crate::servl::nvm;
+use crate::servl::supv;
+use crate::servl::swtimer;
+use crate::mcal::dac;
+use crate::mcal::flash;
 use crate::mcal::gpt::Timer6_7;
+use crate::mcal::systick;
+use crate::mcal::systick::SysTick;
+use crate::mcal::adc;
+use crate::mcal::cec;
+use crate::mcal::i2c;
+use crate::mcal::icu;
+use crate::mcal::pwr;
+use crate::mcal::pwr::PwrRegulator;
+use crate::mcal::spi;
+use crate::mcal::tsc;
+use crate::mcal::rcc;
+use crate::mcal::rcc::ResetCause;
+use crate::mcal::usart;
+use crate::mcal::wwdg;
 use core::arch::asm;
 use core::num::Wrapping;
+use core::ptr;
 
 //---------------------------------------------------------------------------------------------------------------------
 // Types
@@ -91,6 +132,20 @@ static mut S_SCHED: SchedData = SchedData {
         process_func: proc_pid00,
     },
     active_process: PidT::PidMain,
+    mode: SchedMode::Run,
+    shutdown_cycles: 0,
+    wwdg_diag: None,
+};
+
+///diagnostic data stored at the early wakeup of the window watchdog; located in the no-init ram, so it is kept over
+///the watchdog reset
+#[unsafe(link_section = ".noinit")]
+static mut SCHED_WWDG_DIAG: SchedWwdgDiagRecord = SchedWwdgDiagRecord {
+    magic: 0,
+    diag: SchedWwdgDiag {
+        task_idx: 0,
+        timestamp_us: 0,
+    },
 };
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -111,6 +166,28 @@ const TASK_NUM_OF_SLOTS: usize = 40;
 ///number of different tasks
 const TASK_NUM: usize = 17;
 
+///period of the fast, medium and long tasks in us; given by the slots of the tasks in the task list
+const TASK_F_PERIOD_US: u32 = 4 * TASK_SCHEDULE_TIMESLOT_TIME_US;
+const TASK_M_PERIOD_US: u32 = 20 * TASK_SCHEDULE_TIMESLOT_TIME_US;
+const TASK_L_PERIOD_US: u32 = 40 * TASK_SCHEDULE_TIMESLOT_TIME_US;
+
+//icu_task is called by f_task0, usart_task by f_task1, handle_wheel by m_task2
+const _: () = assert!(icu::ICU_TASK_PERIOD_US == TASK_F_PERIOD_US);
+const _: () = assert!(usart::USART_TASK_PERIOD_US == TASK_F_PERIOD_US);
+const _: () = assert!(swtimer::WHEEL_TICK_US == TASK_M_PERIOD_US);
+
+///time base of the scheduler
+const SCHED_TIME_BASE: SchedTimeBase = SchedTimeBase::Tim6;
+
+///counter clock of the time base
+const SCHED_TB_CLK_HZ: u32 = match SCHED_TIME_BASE {
+    SchedTimeBase::Tim6 => crate::mcal::gpt::TIMER_CLK_HZ,
+    SchedTimeBase::SysTick => systick::SYSTICK_CLK_HZ,
+};
+
+//TIM6 can only be used by the dac, if it is not the time base
+const _: () = assert!(matches!(SCHED_TIME_BASE, SchedTimeBase::SysTick) || dac::DAC_TIM6_TRIGGER_HZ.is_none());
+
 ///timeslot duration for each timeslot; each timeslot starts with the urgent task followed by one task
 ///the time is in us
 const TASK_SCHEDULE_TIMESLOT_TIME_US: u32 = 250;
@@ -119,15 +196,35 @@ const TASK_SCHEDULE_TIMESLOT_TIME_US: u32 = 250;
 ///CAUTION: the user has to ensure that the mcu specific timer counter can reach this value!
 ///NOTE : this value compares to the counter value directly; the counter wrap value must be greater (because used to check if
 ///scheduling timing is in limits!)
-const TASK_SCHED_CNT_START_REF_VAL: i32 =
-    (crate::mcal::rcc::F_CPU_HZ / (1000000i32)) * (TASK_SCHEDULE_TIMESLOT_TIME_US as i32);
+const TASK_SCHED_CNT_START_REF_VAL: i32 = sched_cnt_ref_val(SCHED_TB_CLK_HZ, TASK_SCHEDULE_TIMESLOT_TIME_US);
+
+///maximum number of schedule cycles to wait for the pending nvm writes before the shutdown
+const SCHED_SHUTDOWN_MAX_CYCLES: u32 = 100;
 
 ///time for urgent task in us
 const TASK_SCHEDULE_URGENT_TASK_TIME_US: u32 = 25;
 
+///time of a timeslot that is left for the task after the urgent task in us
+pub const SCHED_TASK_TIME_US: u32 = TASK_SCHEDULE_TIMESLOT_TIME_US - TASK_SCHEDULE_URGENT_TASK_TIME_US;
+
 ///time amount that is used for urgent task; if the urgent task is faster, wait this value to be deterministic!
-const TASK_SCHED_CNT_URGENT_REF_VAL: i32 =
-    (crate::mcal::rcc::F_CPU_HZ / (1000000i32)) * (TASK_SCHEDULE_URGENT_TASK_TIME_US as i32);
+const TASK_SCHED_CNT_URGENT_REF_VAL: i32 = sched_cnt_ref_val(SCHED_TB_CLK_HZ, TASK_SCHEDULE_URGENT_TASK_TIME_US);
+
+///marker of a valid watchdog diagnostic record
+const SCHED_WWDG_DIAG_MAGIC: u32 = 0x5744_4447;
+
+///duration of a schedule cycle
+const SCHED_CYCLE_TIME_US: u32 = TASK_NUM_OF_SLOTS as u32 * TASK_SCHEDULE_TIMESLOT_TIME_US;
+
+const _: () = sched_check_wwdg(rcc::PCLK_HZ);
+//the 48 MHz clock setups run the apb with 48 MHz or 24 MHz
+const _: () = sched_check_wwdg(48_000_000);
+const _: () = sched_check_wwdg(24_000_000);
+
+//the timer counts per us must be an integer for the reference values
+const _: () = assert!(SCHED_TB_CLK_HZ.is_multiple_of(1_000_000));
+//both time bases wrap after 1 ms, the reference value has to be reached before
+const _: () = assert!((TASK_SCHED_CNT_START_REF_VAL as u32) < SCHED_TB_CLK_HZ / 1000);
 
 //---------------------------------------------------------------------------------------------------------------------
 // Enums
@@ -155,6 +252,25 @@ enum TaskID {
     Ltask9 = 16,
 }
 
+///time base of the scheduler; the counter of the time base is reset at the begin of each timeslot
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+enum SchedTimeBase {
+    ///basic timer TIM6; the counter reset is the trigger output of TIM6 (e.g. for the dac)
+    Tim6,
+    ///SysTick of the core; available on every Cortex-M, TIM6 is free for other use
+    SysTick,
+}
+
+///scheduler mode
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SchedMode {
+    Run,
+    Stop,
+    Shutdown,
+}
+
 #[derive(Copy, Clone)]
 pub enum PidT {
     Pid00 = 0,
@@ -186,6 +302,24 @@ pub struct SchedData {
     process_table: [Process; 3],
     main_process: Process,
     active_process: PidT,
+    mode: SchedMode,
+    shutdown_cycles: u32,
+    wwdg_diag: Option<SchedWwdgDiag>,
+}
+
+///state of the scheduler at the early wakeup of the window watchdog
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub struct SchedWwdgDiag {
+    ///index of the active task slot
+    pub task_idx: u32,
+    pub timestamp_us: u64,
+}
+
+#[derive(Copy, Clone)]
+struct SchedWwdgDiagRecord {
+    magic: u32,
+    diag: SchedWwdgDiag,
 }
 
 /// definition of a process
@@ -242,24 +376,32 @@ macro_rules! process_wait_time_elapse_us {
 //---------------------------------------------------------------------------------------------------------------------
 #[allow(clippy::fn_to_numeric_cast)]
 pub fn s_init() {
+    sched_tb_init();
     unsafe {
-        S_SCHED.tasks_period_us[TaskID::Ftask0 as usize] = 500;
-        S_SCHED.tasks_period_us[TaskID::Ftask1 as usize] = 500;
-        S_SCHED.tasks_period_us[TaskID::Mtask0 as usize] = 5000;
-        S_SCHED.tasks_period_us[TaskID::Mtask1 as usize] = 5000;
-        S_SCHED.tasks_period_us[TaskID::Mtask2 as usize] = 5000;
-        S_SCHED.tasks_period_us[TaskID::Mtask3 as usize] = 5000;
-        S_SCHED.tasks_period_us[TaskID::Mtask4 as usize] = 5000;
-        S_SCHED.tasks_period_us[TaskID::Ltask0 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask1 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask2 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask3 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask4 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask5 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask6 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask7 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask8 as usize] = 10000;
-        S_SCHED.tasks_period_us[TaskID::Ltask9 as usize] = 10000;
+        //take over the diagnostic data of a watchdog reset; the record is invalidated for the next reset
+        let record: SchedWwdgDiagRecord = ptr::read_volatile(&raw const SCHED_WWDG_DIAG);
+        if record.magic == SCHED_WWDG_DIAG_MAGIC && rcc::get_reset_cause() == ResetCause::WindowWatchdog {
+            S_SCHED.wwdg_diag = Some(record.diag);
+        }
+        ptr::write_volatile(&raw mut SCHED_WWDG_DIAG.magic, 0);
+
+        S_SCHED.tasks_period_us[TaskID::Ftask0 as usize] = TASK_F_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ftask1 as usize] = TASK_F_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Mtask0 as usize] = TASK_M_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Mtask1 as usize] = TASK_M_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Mtask2 as usize] = TASK_M_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Mtask3 as usize] = TASK_M_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Mtask4 as usize] = TASK_M_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask0 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask1 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask2 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask3 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask4 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask5 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask6 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask7 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask8 as usize] = TASK_L_PERIOD_US;
+        S_SCHED.tasks_period_us[TaskID::Ltask9 as usize] = TASK_L_PERIOD_US;
 
         S_SCHED.process_table[0].stack_ptr = (&mut S_SCHED.stack_pid00.stack
             [PROC_PID00_STACK_SIZE - NUM_OF_STACK_ELEMS]
@@ -289,38 +431,175 @@ pub fn s_init() {
 }
 
 pub fn sched_run() {
+    wwdg::init();
     unsafe {
         loop {
             S_SCHED.task_idx = 0;
             #[allow(static_mut_refs)]
             while S_SCHED.task_idx < S_SCHED.task_list.len() as u32 {
-                if Timer6_7::inst_6().get_cnt_value() >= TASK_SCHED_CNT_START_REF_VAL as u32 {
-                    Timer6_7::inst_6().reset_cnt_value();
+                if sched_tb_get_cnt() >= TASK_SCHED_CNT_START_REF_VAL as u32 {
+                    sched_tb_reset();
                     S_SCHED.active_task_func = S_SCHED.urgent_task;
                     (S_SCHED.urgent_task)();
-                    while Timer6_7::inst_6().get_cnt_value() < TASK_SCHED_CNT_URGENT_REF_VAL as u32
+                    if wwdg::wwdg_is_early_wakeup() {
+                        sched_store_wwdg_diag();
+                    }
+                    while sched_tb_get_cnt() < TASK_SCHED_CNT_URGENT_REF_VAL as u32
                     {
                     }
                     S_SCHED.active_task_func = S_SCHED.task_list[S_SCHED.task_idx as usize];
                     (S_SCHED.active_task_func)();
 
-                    if Timer6_7::inst_6().timer_elapsed() {
+                    if sched_tb_elapsed() {
                         asm!(".rept 3  ; \r\n", "nop       ; \r\n", ".endr  ; \r\n");
                     }
                     S_SCHED.task_idx += 1;
                 }
             }
+            wwdg::wwdg_refresh();
+            sched_maintenance();
+            sched_mode_transition();
         }
     }
 }
 
+///store the state of the scheduler for the diagnostic after the watchdog reset
+fn sched_store_wwdg_diag() {
+    unsafe {
+        let record: SchedWwdgDiagRecord = SchedWwdgDiagRecord {
+            magic: SCHED_WWDG_DIAG_MAGIC,
+            diag: SchedWwdgDiag {
+                task_idx: S_SCHED.task_idx,
+                timestamp_us: get_timestamp_ext_us(),
+            },
+        };
+        ptr::write_volatile(&raw mut SCHED_WWDG_DIAG, record);
+    }
+}
+
+///get the scheduler state stored before the last reset, if it was a window watchdog reset
+#[allow(dead_code)]
+pub fn get_wwdg_diag() -> Option<SchedWwdgDiag> {
+    unsafe { S_SCHED.wwdg_diag }
+}
+
+///do the operations that stall the flash for longer than a timeslot (nvm page erase); they are done at the end of the
+///schedule cycle directly after the watchdog refresh, the watchdog is refreshed again and the timeslots are restarted
+///afterwards
+fn sched_maintenance() {
+    #[allow(static_mut_refs)]
+    let rt_data: &mut rte::RteData = unsafe { &mut rte::RTE_D };
+    if rt_data.nvm_data.is_erase_requested() {
+        nvm::nvm_erase(rt_data);
+        wwdg::wwdg_refresh();
+        sched_tb_reset();
+    }
+}
+
+///check the watchdog timing against the schedule with the apb clock
+const fn sched_check_wwdg(pclk_hz: u32) {
+    let timing: wwdg::WwdgTiming = wwdg::wwdg_timing(pclk_hz);
+    //the watchdog is refreshed once per schedule cycle; the refresh may be shifted by one watchdog tick
+    assert!(SCHED_CYCLE_TIME_US > timing.window_us + timing.tick_us);
+    assert!(SCHED_CYCLE_TIME_US + timing.tick_us < timing.timeout_us);
+    //the maintenance refreshes directly before and after the nvm page erase
+    assert!(flash::FLASH_PAGE_ERASE_MIN_US > timing.window_us + timing.tick_us);
+    assert!(flash::FLASH_PAGE_ERASE_MAX_US + timing.tick_us < timing.timeout_us);
+    //the early wakeup flag is set one watchdog tick before the reset
+    assert!(TASK_SCHEDULE_TIMESLOT_TIME_US < timing.tick_us);
+}
+
+///request a scheduler mode change; is done at the end of the schedule cycle
 #[allow(dead_code)]
-fn get_timestamp_us() -> Wrapping<u32> {
+pub fn sched_request_mode(mode: SchedMode) {
+    unsafe {
+        S_SCHED.mode = mode;
+        S_SCHED.shutdown_cycles = 0;
+    }
+}
+
+///do the requested mode transition
+fn sched_mode_transition() {
+    unsafe {
+        match S_SCHED.mode {
+            SchedMode::Run => {}
+            SchedMode::Stop => {
+                //all clock dependent timings are derived from the clock configuration, so there is no operation
+                //without it
+                if pwr::pwr_enter_stop(PwrRegulator::LowPower).is_err() {
+                    #[allow(clippy::empty_loop)]
+                    loop {}
+                }
+                //the timeslot is restarted after the wakeup
+                sched_tb_reset();
+                S_SCHED.mode = SchedMode::Run;
+            }
+            SchedMode::Shutdown => {
+                S_SCHED.shutdown_cycles += 1;
+                #[allow(static_mut_refs)]
+                if rte::RTE_D.nvm_data.is_idle() || S_SCHED.shutdown_cycles >= SCHED_SHUTDOWN_MAX_CYCLES {
+                    pwr::pwr_enter_standby();
+                }
+            }
+        }
+    }
+}
+
+///get the count value of the time base running at tb_clk_hz after time_us
+const fn sched_cnt_ref_val(tb_clk_hz: u32, time_us: u32) -> i32 {
+    (tb_clk_hz as i32 / (1000000i32)) * (time_us as i32)
+}
+
+///init the time base; runs at SCHED_TB_CLK_HZ, so 1 count takes a time of 1 / SCHED_TB_CLK_HZ
+fn sched_tb_init() {
+    match SCHED_TIME_BASE {
+        SchedTimeBase::Tim6 => Timer6_7::inst_6().init(),
+        SchedTimeBase::SysTick => SysTick::inst().init(),
+    }
+}
+
+///get the counter value of the time base; counts up from the reset
+fn sched_tb_get_cnt() -> u32 {
+    match SCHED_TIME_BASE {
+        SchedTimeBase::Tim6 => Timer6_7::inst_6().get_cnt_value(),
+        SchedTimeBase::SysTick => SysTick::inst().get_cnt_value(),
+    }
+}
+
+///reset the counter of the time base
+fn sched_tb_reset() {
+    match SCHED_TIME_BASE {
+        SchedTimeBase::Tim6 => Timer6_7::inst_6().reset_cnt_value(),
+        SchedTimeBase::SysTick => SysTick::inst().reset_cnt_value(),
+    }
+}
+
+///true if the counter of the time base wrapped since the last call
+fn sched_tb_elapsed() -> bool {
+    match SCHED_TIME_BASE {
+        SchedTimeBase::Tim6 => Timer6_7::inst_6().timer_elapsed(),
+        SchedTimeBase::SysTick => SysTick::inst().timer_elapsed(),
+    }
+}
+
+///get the scheduler timestamp in us; the resolution is TASK_SCHEDULE_TIMESLOT_TIME_US
+pub fn get_timestamp_us() -> Wrapping<u32> {
     unsafe {
         S_SCHED.scheduler_timestamp
     }
 }
 
+///get the scheduler timestamp in us with the time of the current timeslot added from the time base counter, so the
+///resolution is 1 us; is monotonic within the tasks, because the timeslot timestamp is updated by the urgent task
+pub fn get_timestamp_fine_us() -> Wrapping<u32> {
+    unsafe { S_SCHED.scheduler_timestamp + Wrapping(sched_tb_get_cnt() / (SCHED_TB_CLK_HZ / 1_000_000)) }
+}
+
+///get the extended scheduler timestamp in us; does not wrap in practice
+pub fn get_timestamp_ext_us() -> u64 {
+    unsafe { S_SCHED.scheduler_extended_timestamp + S_SCHED.scheduler_timestamp.0 as u64 }
+}
+
 #[allow(dead_code)]
 fn get_elapsed_us(timestamp: Wrapping<u32>) -> Wrapping<u32> {
     unsafe {
@@ -337,7 +616,8 @@ fn is_elapsed_us(
 }
 
 fn get_task_sched_times(task_id: TaskID) {
-    let task_runtime: u32 = Timer6_7::inst_6().get_cnt_value() - 200;
+    //the task is started when the time base reaches the urgent reference value
+    let task_runtime: u32 = sched_tb_get_cnt().saturating_sub(TASK_SCHED_CNT_URGENT_REF_VAL as u32);
     unsafe {
         S_SCHED.tasks_max_runtime[task_id as usize] =
             if S_SCHED.tasks_max_runtime[task_id as usize] > task_runtime {
@@ -368,6 +648,7 @@ fn change_context_process(active_process_stack_addr: *mut u32, next_process_stac
     }
 }
 
+#[cfg(not(test))]
 #[unsafe(naked)]
 #[allow(unused_variables)]
 pub unsafe extern "C" fn change_context_internal(
@@ -449,6 +730,12 @@ pub unsafe extern "C" fn change_context_internal(
         );
 }
 
+///the host test build has no process stacks to switch
+#[cfg(test)]
+pub unsafe extern "C" fn change_context_internal(_active_process_stack_addr: *mut u32, _next_process_stack_addr: u32) {
+    unreachable!();
+}
+
 #[inline(always)]
 fn sched_get_stack_ptr(pid: PidT) -> *mut u32 {
     match pid {
@@ -505,11 +792,23 @@ fn u_task0() {
 }
 
 fn f_task0() {
+    #[allow(static_mut_refs)]
+    adc::adc_task(unsafe { &mut rte::RTE_D });
+    #[allow(static_mut_refs)]
+    icu::icu_task(unsafe { &mut rte::RTE_D });
     run_process(PidT::Pid00);
     get_task_sched_times(TaskID::Ftask0);
 }
 
 fn f_task1() {
+    #[allow(static_mut_refs)]
+    usart::usart_task(unsafe { &mut rte::RTE_D });
+    #[allow(static_mut_refs)]
+    spi::spi_task(unsafe { &mut rte::RTE_D });
+    #[allow(static_mut_refs)]
+    i2c::i2c_task(unsafe { &mut rte::RTE_D });
+    #[allow(static_mut_refs)]
+    cec::cec_task(unsafe { &mut rte::RTE_D });
     get_task_sched_times(TaskID::Ftask1);
 }
 
@@ -519,10 +818,14 @@ fn m_task0() {
 }
 
 fn m_task1() {
+    #[allow(static_mut_refs)]
+    tsc::tsc_task(unsafe { &mut rte::RTE_D });
     get_task_sched_times(TaskID::Mtask1);
 }
 
 fn m_task2() {
+    #[allow(static_mut_refs)]
+    swtimer::handle_wheel(unsafe { &mut rte::RTE_D });
     get_task_sched_times(TaskID::Mtask2);
 }
 
@@ -535,6 +838,8 @@ fn m_task4() {
 }
 
 fn l_task0() {
+    #[allow(static_mut_refs)]
+    supv::supv_main(unsafe { &mut rte::RTE_D });
     get_task_sched_times(TaskID::Ltask0);
 }
 
@@ -544,6 +849,8 @@ fn l_task1() {
 }
 
 fn l_task2() {
+    #[allow(static_mut_refs)]
+    nvm::nvm_task(unsafe { &mut rte::RTE_D });
     get_task_sched_times(TaskID::Ltask2);
 }
 
@@ -665,3 +972,61 @@ fn proc_pid02() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cnt_ref_val_at_8mhz() {
+        assert_eq!(sched_cnt_ref_val(8_000_000, TASK_SCHEDULE_TIMESLOT_TIME_US), 2_000);
+        assert_eq!(sched_cnt_ref_val(8_000_000, TASK_SCHEDULE_URGENT_TASK_TIME_US), 200);
+    }
+
+    #[test]
+    fn cnt_ref_val_at_48mhz() {
+        //timer clock of the 48 MHz pll configuration; the time base wraps after 48_000 counts
+        assert_eq!(sched_cnt_ref_val(48_000_000, TASK_SCHEDULE_TIMESLOT_TIME_US), 12_000);
+        assert_eq!(sched_cnt_ref_val(48_000_000, TASK_SCHEDULE_URGENT_TASK_TIME_US), 1_200);
+        assert!(sched_cnt_ref_val(48_000_000, TASK_SCHEDULE_TIMESLOT_TIME_US) < 48_000);
+    }
+
+    #[test]
+    fn cnt_ref_val_of_time_base() {
+        assert_eq!(TASK_SCHED_CNT_START_REF_VAL, sched_cnt_ref_val(SCHED_TB_CLK_HZ, 250));
+        assert_eq!(TASK_SCHED_CNT_URGENT_REF_VAL, sched_cnt_ref_val(SCHED_TB_CLK_HZ, 25));
+    }
+
+    ///get the distances in timeslots from each slot of the task to its next slot over the schedule cycle
+    fn task_slot_distances(task: TaskFunction) -> Vec<usize> {
+        let task_list: [TaskFunction; TASK_NUM_OF_SLOTS] = unsafe { S_SCHED.task_list };
+        let slots: Vec<usize> =
+            (0..TASK_NUM_OF_SLOTS).filter(|&i| task_list[i] as usize == task as usize).collect();
+        assert!(!slots.is_empty());
+        slots
+            .iter()
+            .zip(slots.iter().cycle().skip(1))
+            .map(|(&a, &b)| (b + TASK_NUM_OF_SLOTS - a - 1) % TASK_NUM_OF_SLOTS + 1)
+            .collect()
+    }
+
+    ///check that the task runs with a constant period in the task list
+    fn check_task_period(task: TaskFunction, period_us: u32) {
+        for distance in task_slot_distances(task) {
+            assert_eq!(distance as u32 * TASK_SCHEDULE_TIMESLOT_TIME_US, period_us);
+        }
+    }
+
+    #[test]
+    fn task_periods_match_task_list() {
+        for task in [f_task0, f_task1] {
+            check_task_period(task, TASK_F_PERIOD_US);
+        }
+        for task in [m_task0, m_task1, m_task2, m_task3, m_task4] {
+            check_task_period(task, TASK_M_PERIOD_US);
+        }
+        for task in [l_task0, l_task1, l_task2, l_task3, l_task4, l_task5, l_task6, l_task7, l_task8, l_task9] {
+            check_task_period(task, TASK_L_PERIOD_US);
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//!
+//!  ______    _          _    ____      
+//! |  ____|  | |        (_)  / __ \     
+//! | |__ __ _| |__  _ __ _  | |  | |___
+//! |  __/ _` | '_ \| '__| | | |  | / __|
+//! | | | (_| | |_) | |  | | | |__| \__ \
+//! |_|  \__,_|_.__/|_|  |_|  \____/|___/
+//!                                                                        
+//! Copyright (c) 2025, Flo1991
+//!
+//! BSD 3-Clause License - see LICENSE file for details
+//!
+//! Author : Florian Wank
+//! Creation Date : 19.10.2026
+//! File : supv.rs
+//!
+//! # Short description
+//! Alive and deadline supervision service
+//!
+//! # Detailed description
+//! Tasks and processes report checkpoints to this module. Two kinds of supervision are done:
+//! (1) alive supervision : the number of reports of a checkpoint within one supervision cycle must be in the
+//!     configured range [expected_min, expected_max]
+//! (2) deadline supervision : the time between a start and an end checkpoint must be in the configured range
+//!     [min_us, max_us]; the time is taken from the fine scheduler timestamp (timeslot timestamp plus time base
+//!     counter), so the resolution is 1 us and start and end may be reported within the same task call
+//!
+//! Each check belongs to a supervised entity. An entity gets Failed with the first failed check; if it fails
+//! for more than failed_cycles_tol supervision cycles in sequence it gets Expired. A Failed entity that passes
+//! a complete supervision cycle gets Ok again, an Expired entity stays Expired.
+//! The supervision cycle is handled by supv_main, which must be called periodically by a task; the
+//! supervision cycle time is SUPV_MAIN_CALLS_PER_CYCLE times the calling task period.
+//! After each supervision cycle the entity status is reported to the failure manager: Expired is reported as
+//! failed test result, Ok as passed test result of the fault configured for the entity.
+//!
+//!
+//!
+
+//---------------------------------------------------------------------------------------------------------------------
+// Includes
+//---------------------------------------------------------------------------------------------------------------------
+use crate::rte::RteData;
+use crate::servl::fm;
+use crate::servl::fm::FaultId;
+use crate::servl::fm::TestResult;
+use crate::servl::sched;
+use core::num::Wrapping;
+
+//---------------------------------------------------------------------------------------------------------------------
+// Types
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Statics
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Constants
+//---------------------------------------------------------------------------------------------------------------------
+///number of supervised entities
+const SUPV_ENTITY_NUM: usize = 1;
+
+///number of supv_main calls per supervision cycle
+const SUPV_MAIN_CALLS_PER_CYCLE: u16 = 10;
+
+///entity configuration; index is the SupvEntityId
+const SUPV_ENTITY_CFG: [EntityCfg; SUPV_ENTITY_NUM] = [EntityCfg {
+    failed_cycles_tol: 2,
+    fault: FaultId::SupvLedm,
+}];
+
+///alive supervision configuration
+const SUPV_ALIVE_CFG: [AliveCfg; 1] = [AliveCfg {
+    entity: SupvEntityId::Ledm,
+    checkpoint: CheckpointId::LedmTaskStart,
+    expected_min: 1,
+    expected_max: 10,
+}];
+
+///deadline supervision configuration
+const SUPV_DEADLINE_CFG: [DeadlineCfg; 1] = [DeadlineCfg {
+    entity: SupvEntityId::Ledm,
+    start: CheckpointId::LedmTaskStart,
+    end: CheckpointId::LedmTaskEnd,
+    min_us: 0,
+    max_us: 250,
+}];
+
+//---------------------------------------------------------------------------------------------------------------------
+// Enums
+//---------------------------------------------------------------------------------------------------------------------
+///supervised entities; a task, a process or a part of it
+#[derive(PartialEq, Copy, Clone)]
+pub enum SupvEntityId {
+    Ledm = 0,
+}
+
+///checkpoints that are reported by the supervised entities
+#[derive(PartialEq, Copy, Clone)]
+pub enum CheckpointId {
+    LedmTaskStart,
+    LedmTaskEnd,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum SupvStatus {
+    Ok,
+    Failed,
+    Expired,
+    Deactivated,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+// Structs
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct EntityCfg {
+    failed_cycles_tol: u8,
+    fault: FaultId,
+}
+
+#[derive(Copy, Clone)]
+struct AliveCfg {
+    entity: SupvEntityId,
+    checkpoint: CheckpointId,
+    expected_min: u16,
+    expected_max: u16,
+}
+
+#[derive(Copy, Clone)]
+struct DeadlineCfg {
+    entity: SupvEntityId,
+    start: CheckpointId,
+    end: CheckpointId,
+    min_us: u32,
+    max_us: u32,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+#[derive(Copy, Clone)]
+pub struct SupvData {
+    main_cnt: u16,
+    alive_cnt: [u16; SUPV_ALIVE_CFG.len()],
+    deadline_start: [Option<Wrapping<u32>>; SUPV_DEADLINE_CFG.len()],
+    cycle_failed: [bool; SUPV_ENTITY_NUM],
+    failed_cycles: [u8; SUPV_ENTITY_NUM],
+    status: [SupvStatus; SUPV_ENTITY_NUM],
+}
+
+impl SupvData {
+    pub const fn init() -> Self {
+        Self {
+            main_cnt: 0,
+            alive_cnt: [0; SUPV_ALIVE_CFG.len()],
+            deadline_start: [None; SUPV_DEADLINE_CFG.len()],
+            cycle_failed: [false; SUPV_ENTITY_NUM],
+            failed_cycles: [0; SUPV_ENTITY_NUM],
+            status: [SupvStatus::Ok; SUPV_ENTITY_NUM],
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn get_status(&self, entity: SupvEntityId) -> SupvStatus {
+        self.status[entity as usize]
+    }
+
+    ///get the worst status of all entities
+    #[allow(dead_code)]
+    pub fn get_global_status(&self) -> SupvStatus {
+        let mut global: SupvStatus = SupvStatus::Ok;
+        for status in self.status.iter() {
+            if *status == SupvStatus::Expired {
+                return SupvStatus::Expired;
+            } else if *status == SupvStatus::Failed {
+                global = SupvStatus::Failed;
+            }
+        }
+        global
+    }
+}
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Unions
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Macros
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+// Functions
+//---------------------------------------------------------------------------------------------------------------------
+
+///report a checkpoint of a supervised entity
+pub fn supv_checkpoint(checkpoint: CheckpointId, rt_data: &mut RteData) {
+    supv_checkpoint_at(checkpoint, sched::get_timestamp_fine_us(), rt_data);
+}
+
+///report a checkpoint of a supervised entity that is reached at the time now
+fn supv_checkpoint_at(checkpoint: CheckpointId, now: Wrapping<u32>, rt_data: &mut RteData) {
+    let supv: &mut SupvData = &mut rt_data.supv_data;
+
+    let mut idx: usize = 0;
+    while idx < SUPV_ALIVE_CFG.len() {
+        if SUPV_ALIVE_CFG[idx].checkpoint == checkpoint {
+            supv.alive_cnt[idx] = supv.alive_cnt[idx].saturating_add(1);
+        }
+        idx += 1;
+    }
+
+    idx = 0;
+    while idx < SUPV_DEADLINE_CFG.len() {
+        let cfg: &DeadlineCfg = &SUPV_DEADLINE_CFG[idx];
+        if cfg.end == checkpoint {
+            if let Some(start) = supv.deadline_start[idx] {
+                let elapsed: u32 = (now - start).0;
+                if elapsed < cfg.min_us || elapsed > cfg.max_us {
+                    supv.cycle_failed[cfg.entity as usize] = true;
+                }
+                supv.deadline_start[idx] = None;
+            }
+        }
+        if cfg.start == checkpoint {
+            supv.deadline_start[idx] = Some(now);
+        }
+        idx += 1;
+    }
+}
+
+///activate or deactivate the supervision of an entity; activation restarts the supervision with status Ok
+#[allow(dead_code)]
+pub fn supv_set_active(entity: SupvEntityId, active: bool, rt_data: &mut RteData) {
+    let supv: &mut SupvData = &mut rt_data.supv_data;
+    let idx: usize = entity as usize;
+    supv.status[idx] = if active {
+        SupvStatus::Ok
+    } else {
+        SupvStatus::Deactivated
+    };
+    supv.cycle_failed[idx] = false;
+    supv.failed_cycles[idx] = 0;
+
+    let mut cfg_idx: usize = 0;
+    while cfg_idx < SUPV_DEADLINE_CFG.len() {
+        if SUPV_DEADLINE_CFG[cfg_idx].entity == entity {
+            supv.deadline_start[cfg_idx] = None;
+        }
+        cfg_idx += 1;
+    }
+}
+
+///supervision main function; must be called periodically by a task
+pub fn supv_main(rt_data: &mut RteData) {
+    let supv: &mut SupvData = &mut rt_data.supv_data;
+    supv.main_cnt += 1;
+    if supv.main_cnt < SUPV_MAIN_CALLS_PER_CYCLE {
+        return;
+    }
+    supv.main_cnt = 0;
+
+    //check the alive counters of the finished supervision cycle
+    let mut idx: usize = 0;
+    while idx < SUPV_ALIVE_CFG.len() {
+        let cfg: &AliveCfg = &SUPV_ALIVE_CFG[idx];
+        if supv.alive_cnt[idx] < cfg.expected_min || supv.alive_cnt[idx] > cfg.expected_max {
+            supv.cycle_failed[cfg.entity as usize] = true;
+        }
+        supv.alive_cnt[idx] = 0;
+        idx += 1;
+    }
+
+    //update the entity status
+    idx = 0;
+    while idx < SUPV_ENTITY_NUM {
+        let old_status: SupvStatus = supv.status[idx];
+        let new_status: SupvStatus = match old_status {
+            SupvStatus::Ok | SupvStatus::Failed => {
+                if supv.cycle_failed[idx] {
+                    supv.failed_cycles[idx] = supv.failed_cycles[idx].saturating_add(1);
+                    if supv.failed_cycles[idx] > SUPV_ENTITY_CFG[idx].failed_cycles_tol {
+                        SupvStatus::Expired
+                    } else {
+                        SupvStatus::Failed
+                    }
+                } else {
+                    supv.failed_cycles[idx] = 0;
+                    SupvStatus::Ok
+                }
+            }
+            SupvStatus::Expired | SupvStatus::Deactivated => old_status,
+        };
+        supv.cycle_failed[idx] = false;
+        supv.status[idx] = new_status;
+        idx += 1;
+    }
+
+    //report the entity status to the failure manager
+    let status: [SupvStatus; SUPV_ENTITY_NUM] = supv.status;
+    idx = 0;
+    while idx < SUPV_ENTITY_NUM {
+        match status[idx] {
+            SupvStatus::Ok => fm::fm_report(SUPV_ENTITY_CFG[idx].fault, TestResult::Passed, rt_data),
+            SupvStatus::Expired => fm::fm_report(SUPV_ENTITY_CFG[idx].fault, TestResult::Failed, rt_data),
+            SupvStatus::Failed | SupvStatus::Deactivated => {}
+        }
+        idx += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///run one supervision cycle with one deadline of the ledm task that takes elapsed_us
+    fn run_cycle(rt_data: &mut RteData, start_us: u32, elapsed_us: u32) {
+        supv_checkpoint_at(CheckpointId::LedmTaskStart, Wrapping(start_us), rt_data);
+        supv_checkpoint_at(CheckpointId::LedmTaskEnd, Wrapping(start_us) + Wrapping(elapsed_us), rt_data);
+        for _ in 0..SUPV_MAIN_CALLS_PER_CYCLE {
+            supv_main(rt_data);
+        }
+    }
+
+    #[test]
+    fn deadline_in_time() {
+        let mut rt_data: RteData = RteData::init();
+        //a deadline within the same timeslot and one over the wrap of the timestamp
+        run_cycle(&mut rt_data, 1000, 40);
+        run_cycle(&mut rt_data, u32::MAX - 100, SUPV_DEADLINE_CFG[0].max_us);
+        assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) == SupvStatus::Ok);
+    }
+
+    #[test]
+    fn late_end_checkpoint_fails_entity() {
+        let mut rt_data: RteData = RteData::init();
+        run_cycle(&mut rt_data, 1000, SUPV_DEADLINE_CFG[0].max_us + 1);
+        assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) == SupvStatus::Failed);
+        //a passed cycle recovers the entity
+        run_cycle(&mut rt_data, 5000, 40);
+        assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) == SupvStatus::Ok);
+
+        //the entity expires after failed_cycles_tol failed cycles in sequence and reports the fault
+        let tol: u8 = SUPV_ENTITY_CFG[SupvEntityId::Ledm as usize].failed_cycles_tol;
+        for cycle in 0..=tol as u32 {
+            assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) != SupvStatus::Expired);
+            run_cycle(&mut rt_data, 10_000 * cycle, 1000);
+        }
+        assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) == SupvStatus::Expired);
+        assert!(rt_data.supv_data.get_global_status() == SupvStatus::Expired);
+        //an expired entity stays expired and is reported failed in each cycle; the failure manager qualifies the
+        //fault after its debouncing
+        run_cycle(&mut rt_data, 100_000, 40);
+        assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) == SupvStatus::Expired);
+        assert!((rt_data.fm_data.get_status(FaultId::SupvLedm) & fm::FM_STATUS_TEST_FAILED) != 0);
+    }
+
+    #[test]
+    fn missing_alive_fails_entity() {
+        let mut rt_data: RteData = RteData::init();
+        for _ in 0..SUPV_MAIN_CALLS_PER_CYCLE {
+            supv_main(&mut rt_data);
+        }
+        assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) == SupvStatus::Failed);
+    }
+
+    #[test]
+    fn deactivated_entity_is_not_checked() {
+        let mut rt_data: RteData = RteData::init();
+        supv_set_active(SupvEntityId::Ledm, false, &mut rt_data);
+        run_cycle(&mut rt_data, 1000, 1000);
+        assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) == SupvStatus::Deactivated);
+        supv_set_active(SupvEntityId::Ledm, true, &mut rt_data);
+        run_cycle(&mut rt_data, 3000, 40);
+        assert!(rt_data.supv_data.get_status(SupvEntityId::Ledm) == SupvStatus::Ok);
+    }
+}
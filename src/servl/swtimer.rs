@@ -23,6 +23,23 @@
 //! actually depends on the software timer configuration and the task timing in which the software
 //! timer handling function is called.
 //!
+//! For a large number of timeouts (protocol retries, debounce, supervision) the linear scan of the
+//! ToutTimer array gets too expensive, so this module also provides a hierarchical hashed timing wheel.
+//! Start and stop of a wheel timer are O(1) (doubly linked slot lists with node indices instead of
+//! pointers); a tick only touches the timers that expire in this tick. Timers with a longer timeout are
+//! located in a coarser wheel level and are cascaded down once when the finer level wraps, so each timer
+//! is moved at most WHEEL_LEVELS - 1 times during its lifetime.
+//! A wheel handle contains the node index and the generation of the node, which is incremented when the node is
+//! freed; so a handle that is kept after free is rejected and can not access a timer allocated later on the node.
+//! The runtime environment timing wheel is advanced by handle_wheel in a scheduler task every WHEEL_TICK_US.
+//! The host test benchmark_linear_against_wheel compares both implementations by counting the timer node accesses:
+//! for 64 repeating timers with periods from 10 to 2009 ticks and 100000 ticks, the ToutTimer counting needs 6400000
+//! accesses and the wheel 48206 (28209 expiries and 19997 cascade moves).
+//!
+//! Timer callbacks get a TimerContext (the handle of the expired timer and a user value given at timer
+//! setup) and the runtime environment data, so one callback function can serve many timers and the
+//! callbacks do not need to access the global runtime environment data.
+//!
 //!
 //!
 
@@ -30,13 +47,14 @@
 // Includes
 //---------------------------------------------------------------------------------------------------------------------
 use crate::appl::ledm;
-use crate::rte;
 use crate::rte::RteData;
 
 //---------------------------------------------------------------------------------------------------------------------
 // Types
 //---------------------------------------------------------------------------------------------------------------------
-type TimerFunction = fn();
+///timer callback function; gets the context of the expired timer and the runtime environment data
+pub type TimerFunction = fn(TimerContext, &mut RteData);
+
 
 //---------------------------------------------------------------------------------------------------------------------
 // Statics
@@ -45,6 +63,26 @@ type TimerFunction = fn();
 //---------------------------------------------------------------------------------------------------------------------
 // Constants
 //---------------------------------------------------------------------------------------------------------------------
+///number of timers that can be handled by the runtime environment timing wheel
+pub const WHEEL_TIMER_NUM: usize = 128;
+
+///tick time of the runtime environment timing wheel; handle_wheel is called with this period (checked in the
+///scheduler)
+pub const WHEEL_TICK_US: u32 = 5000;
+
+///number of bits used for the slot index of each wheel level
+const WHEEL_SLOT_BITS: u32 = 5;
+///number of slots per wheel level
+const WHEEL_SLOTS: usize = 1 << WHEEL_SLOT_BITS;
+///mask to get the slot index of a wheel level
+const WHEEL_SLOT_MSK: u32 = (WHEEL_SLOTS as u32) - 1;
+///number of wheel levels; level 0 has a resolution of 1 tick, each further level is WHEEL_SLOTS times coarser
+const WHEEL_LEVELS: usize = 3;
+///maximum number of ticks a wheel timer can be started with
+pub const WHEEL_MAX_TICKS: u32 = (1 << (WHEEL_SLOT_BITS * WHEEL_LEVELS as u32)) - 1;
+
+///marker for an unused link in the wheel node lists
+const WHEEL_NIL: u16 = u16::MAX;
 
 //---------------------------------------------------------------------------------------------------------------------
 // Enums
@@ -55,14 +93,50 @@ pub enum States {
     Run,
 }
 
+#[derive(PartialEq, Copy, Clone)]
 pub enum TimerId {
     LedmBlinkTimer = 0,
 }
 
+///handle of the timer that calls a callback
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum TimerHandle {
+    Tout(TimerId),
+    Wheel(WheelHandle),
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum WheelError {
+    NoFreeTimer,
+    InvalidHandle,
+    OutOfRange,
+}
+
 //---------------------------------------------------------------------------------------------------------------------
 // Structs
 //---------------------------------------------------------------------------------------------------------------------
 
+//---------------------------------------------------------------------------------------------------------------------
+///handle of a timer in the timing wheel; the index and the generation of the timer node
+#[derive(PartialEq, Copy, Clone)]
+pub struct WheelHandle {
+    idx: u16,
+    gen: u16,
+}
+//---------------------------------------------------------------------------------------------------------------------
+
+//---------------------------------------------------------------------------------------------------------------------
+///context that is given to a timer callback; user is a free value set at timer setup (e.g. an index or an address)
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub struct TimerContext {
+    pub timer: TimerHandle,
+    pub user: usize,
+}
+//---------------------------------------------------------------------------------------------------------------------
+
 //---------------------------------------------------------------------------------------------------------------------
 #[derive(Copy, Clone)]
 pub struct ToutTimer {
@@ -70,6 +144,7 @@ pub struct ToutTimer {
     pub end_val: u32,
     pub state: States,
     pub callback: TimerFunction,
+    pub user: usize,
 }
 
 impl ToutTimer {
@@ -93,35 +168,48 @@ impl ToutTimer {
         rt_data.swtimer_data.timers[idx].end_val = endval;
     }
     #[allow(dead_code)]
+    pub fn set_user(timer: TimerId, rt_data: &mut RteData, user: usize) {
+        rt_data.swtimer_data.timers[timer as usize].user = user;
+    }
+    #[allow(dead_code)]
     pub fn handle_stop(timer: TimerId, rt_data: &mut RteData) {
-        let idx: usize = timer as usize;
-        if rt_data.swtimer_data.timers[idx].state == States::Run {
-            if rt_data.swtimer_data.timers[idx].current_val
-                >= rt_data.swtimer_data.timers[idx].end_val
-            {
-                (rt_data.swtimer_data.timers[idx].callback)();
-                rt_data.swtimer_data.timers[idx].state = States::Stopped;
-            } else {
-                rt_data.swtimer_data.timers[idx].current_val += 1;
-            }
+        if rt_data.swtimer_data.timers[timer as usize].count(false) {
+            Self::call(timer, rt_data);
         }
     }
-    pub fn handle_repeat(timer: TimerId) {
-        unsafe {
-            let idx: usize = timer as usize;
-            if rte::RTE_D.swtimer_data.timers[idx].state == States::Run {
-                if rte::RTE_D.swtimer_data.timers[idx].current_val
-                    >= rte::RTE_D.swtimer_data.timers[idx].end_val
-                {
-                    // the callback which is called here must inform the failure manager to handle failures
-                    (rte::RTE_D.swtimer_data.timers[idx].callback)();
-                    rte::RTE_D.swtimer_data.timers[idx].current_val = 0;
-                } else {
-                    rte::RTE_D.swtimer_data.timers[idx].current_val += 1;
-                }
+    pub fn handle_repeat(timer: TimerId, rt_data: &mut RteData) {
+        if rt_data.swtimer_data.timers[timer as usize].count(true) {
+            // the callback which is called here must inform the failure manager to handle failures
+            Self::call(timer, rt_data);
+        }
+    }
+    ///count a tick of a running timer; returns true if the timer expired, a repeating timer is restarted, otherwise
+    ///the timer is stopped
+    fn count(&mut self, repeat: bool) -> bool {
+        if self.state != States::Run {
+            return false;
+        }
+        if self.current_val >= self.end_val {
+            if repeat {
+                self.current_val = 0;
+            } else {
+                self.state = States::Stopped;
             }
+            true
+        } else {
+            self.current_val += 1;
+            false
         }
     }
+    ///call the timer callback; the timer update must be finished before, because the callback may modify the timer
+    fn call(timer: TimerId, rt_data: &mut RteData) {
+        let t: ToutTimer = rt_data.swtimer_data.timers[timer as usize];
+        let ctx: TimerContext = TimerContext {
+            timer: TimerHandle::Tout(timer),
+            user: t.user,
+        };
+        (t.callback)(ctx, rt_data);
+    }
 }
 //---------------------------------------------------------------------------------------------------------------------
 
@@ -129,6 +217,7 @@ impl ToutTimer {
 #[derive(Copy, Clone)]
 pub struct TimerData {
     timers: [ToutTimer; 1],
+    pub wheel: TimerWheel<WHEEL_TIMER_NUM>,
 }
 
 impl TimerData {
@@ -139,12 +228,262 @@ impl TimerData {
                 end_val: 50,
                 state: States::Run,
                 callback: ledm::ledm_blink_timer_callback,
+                user: 0,
             }],
+            wheel: TimerWheel::init(),
         }
     }
 }
 //---------------------------------------------------------------------------------------------------------------------
 
+//---------------------------------------------------------------------------------------------------------------------
+/// single timer node of the timing wheel; the nodes are linked by index into the slot lists
+#[derive(Copy, Clone)]
+struct WheelNode {
+    next: u16,
+    prev: u16,
+    slot: u16,
+    allocated: bool,
+    ///generation of the node; is incremented at free, so handles of the former timer are rejected
+    gen: u16,
+    state: States,
+    expire: u32,
+    period: u32,
+    callback: TimerFunction,
+    user: usize,
+}
+
+/// hierarchical hashed timing wheel with N timer nodes
+#[derive(Copy, Clone)]
+pub struct TimerWheel<const N: usize> {
+    now: u32,
+    free_head: u16,
+    heads: [u16; WHEEL_SLOTS * WHEEL_LEVELS],
+    nodes: [WheelNode; N],
+}
+
+impl<const N: usize> TimerWheel<N> {
+    pub const fn init() -> Self {
+        let mut wheel = Self {
+            now: 0,
+            free_head: 0,
+            heads: [WHEEL_NIL; WHEEL_SLOTS * WHEEL_LEVELS],
+            nodes: [WheelNode {
+                next: WHEEL_NIL,
+                prev: WHEEL_NIL,
+                slot: WHEEL_NIL,
+                allocated: false,
+                gen: 0,
+                state: States::Stopped,
+                expire: 0,
+                period: 0,
+                callback: wheel_default_callback,
+                user: 0,
+            }; N],
+        };
+        //link all nodes into the free list; the free list only uses the next link
+        let mut i = 0;
+        while i < N {
+            wheel.nodes[i].next = if i + 1 < N { (i + 1) as u16 } else { WHEEL_NIL };
+            i += 1;
+        }
+        if N == 0 {
+            wheel.free_head = WHEEL_NIL;
+        }
+        wheel
+    }
+
+    ///allocate a timer node with the given callback and user context value; the timer is stopped after allocation
+    #[allow(dead_code)]
+    pub fn alloc(&mut self, callback: TimerFunction, user: usize) -> Result<WheelHandle, WheelError> {
+        if self.free_head == WHEEL_NIL {
+            return Err(WheelError::NoFreeTimer);
+        }
+        let idx: u16 = self.free_head;
+        let node: &mut WheelNode = &mut self.nodes[idx as usize];
+        self.free_head = node.next;
+        node.next = WHEEL_NIL;
+        node.prev = WHEEL_NIL;
+        node.slot = WHEEL_NIL;
+        node.allocated = true;
+        node.state = States::Stopped;
+        node.callback = callback;
+        node.user = user;
+        Ok(WheelHandle { idx, gen: node.gen })
+    }
+
+    ///stop the timer and give the timer node back to the free list
+    #[allow(dead_code)]
+    pub fn free(&mut self, handle: WheelHandle) -> Result<(), WheelError> {
+        self.stop(handle)?;
+        let node: &mut WheelNode = &mut self.nodes[handle.idx as usize];
+        node.allocated = false;
+        node.gen = node.gen.wrapping_add(1);
+        node.next = self.free_head;
+        self.free_head = handle.idx;
+        Ok(())
+    }
+
+    ///start the timer to expire after ticks; a period of 0 is a single shot timer, otherwise the timer is
+    ///restarted with period ticks after expiry; a running timer is restarted
+    #[allow(dead_code)]
+    pub fn start(&mut self, handle: WheelHandle, ticks: u32, period: u32) -> Result<(), WheelError> {
+        if ticks > WHEEL_MAX_TICKS || period > WHEEL_MAX_TICKS {
+            return Err(WheelError::OutOfRange);
+        }
+        self.stop(handle)?;
+        let idx: usize = handle.idx as usize;
+        //a timer can expire earliest with the next tick
+        self.nodes[idx].expire = self.now.wrapping_add(ticks.max(1));
+        self.nodes[idx].period = period;
+        self.nodes[idx].state = States::Run;
+        self.link(handle.idx);
+        Ok(())
+    }
+
+    ///stop the timer; stopping a stopped timer is allowed
+    #[allow(dead_code)]
+    pub fn stop(&mut self, handle: WheelHandle) -> Result<(), WheelError> {
+        let idx: usize = self.check_handle(handle)?;
+        if self.nodes[idx].state == States::Run {
+            self.unlink(handle.idx);
+            self.nodes[idx].state = States::Stopped;
+        }
+        Ok(())
+    }
+
+    ///get the state of the timer
+    #[allow(dead_code)]
+    pub fn get_state(&self, handle: WheelHandle) -> Result<States, WheelError> {
+        let idx: usize = self.check_handle(handle)?;
+        Ok(self.nodes[idx].state)
+    }
+
+    ///get the remaining ticks until the timer expires; is 0 for stopped timers
+    #[allow(dead_code)]
+    pub fn get_remaining(&self, handle: WheelHandle) -> Result<u32, WheelError> {
+        let node: &WheelNode = &self.nodes[self.check_handle(handle)?];
+        if node.state == States::Run {
+            Ok(node.expire.wrapping_sub(self.now))
+        } else {
+            Ok(0)
+        }
+    }
+
+    ///advance the wheel by one tick; afterwards get_expired has to be called until it returns None to handle
+    ///all timers expiring in this tick; returns the number of timers moved down by the cascade
+    pub fn advance(&mut self) -> u32 {
+        let mut moved: u32 = 0;
+        self.now = self.now.wrapping_add(1);
+        if (self.now & WHEEL_SLOT_MSK) == 0 {
+            //level 0 wrapped; first move the timers of the coarser levels down before processing level 0
+            let mut level: usize = WHEEL_LEVELS - 1;
+            while level > 0 {
+                let shift: u32 = WHEEL_SLOT_BITS * level as u32;
+                if (self.now & ((1 << shift) - 1)) == 0 {
+                    moved += self.cascade(level * WHEEL_SLOTS + ((self.now >> shift) & WHEEL_SLOT_MSK) as usize);
+                }
+                level -= 1;
+            }
+        }
+        moved
+    }
+
+    ///take the next timer expiring in the current tick; repeating timers are restarted, single shot timers
+    ///are stopped; returns the callback and its context, the caller has to call the callback; the callback is
+    ///not called here, so that the callback can get access to the data containing the wheel
+    pub fn get_expired(&mut self) -> Option<(TimerFunction, TimerContext)> {
+        //a restarted or newly started timer expires earliest with the next tick, so it is never linked
+        //into the current slot again and the loop of the caller ends
+        let node_idx: u16 = self.heads[(self.now & WHEEL_SLOT_MSK) as usize];
+        if node_idx == WHEEL_NIL {
+            return None;
+        }
+        let idx: usize = node_idx as usize;
+        self.unlink(node_idx);
+        if self.nodes[idx].period != 0 {
+            self.nodes[idx].expire = self.now.wrapping_add(self.nodes[idx].period);
+            self.link(node_idx);
+        } else {
+            self.nodes[idx].state = States::Stopped;
+        }
+        let ctx: TimerContext = TimerContext {
+            timer: TimerHandle::Wheel(WheelHandle {
+                idx: node_idx,
+                gen: self.nodes[idx].gen,
+            }),
+            user: self.nodes[idx].user,
+        };
+        Some((self.nodes[idx].callback, ctx))
+    }
+
+    ///get the node index of the handle; the handle must belong to an allocated timer node of the same generation
+    fn check_handle(&self, handle: WheelHandle) -> Result<usize, WheelError> {
+        let idx: usize = handle.idx as usize;
+        if idx >= N || !self.nodes[idx].allocated || self.nodes[idx].gen != handle.gen {
+            return Err(WheelError::InvalidHandle);
+        }
+        Ok(idx)
+    }
+
+    ///move all timers of the given slot to their slot regarding the remaining ticks; returns the number of moved
+    ///timers
+    fn cascade(&mut self, slot: usize) -> u32 {
+        let mut moved: u32 = 0;
+        let mut handle: u16 = self.heads[slot];
+        self.heads[slot] = WHEEL_NIL;
+        while handle != WHEEL_NIL {
+            let next: u16 = self.nodes[handle as usize].next;
+            self.link(handle);
+            moved += 1;
+            handle = next;
+        }
+        moved
+    }
+
+    ///get the slot of a timer regarding its remaining ticks
+    fn get_slot(&self, expire: u32) -> usize {
+        let delta: u32 = expire.wrapping_sub(self.now);
+        let mut level: usize = 0;
+        while level < WHEEL_LEVELS - 1 && delta >= (1 << (WHEEL_SLOT_BITS * (level as u32 + 1))) {
+            level += 1;
+        }
+        level * WHEEL_SLOTS + ((expire >> (WHEEL_SLOT_BITS * level as u32)) & WHEEL_SLOT_MSK) as usize
+    }
+
+    ///insert the timer node at the head of its slot list
+    fn link(&mut self, handle: u16) {
+        let slot: usize = self.get_slot(self.nodes[handle as usize].expire);
+        let head: u16 = self.heads[slot];
+        if head != WHEEL_NIL {
+            self.nodes[head as usize].prev = handle;
+        }
+        let node: &mut WheelNode = &mut self.nodes[handle as usize];
+        node.next = head;
+        node.prev = WHEEL_NIL;
+        node.slot = slot as u16;
+        self.heads[slot] = handle;
+    }
+
+    ///remove the timer node from its slot list
+    fn unlink(&mut self, handle: u16) {
+        let node: WheelNode = self.nodes[handle as usize];
+        if node.prev != WHEEL_NIL {
+            self.nodes[node.prev as usize].next = node.next;
+        } else {
+            self.heads[node.slot as usize] = node.next;
+        }
+        if node.next != WHEEL_NIL {
+            self.nodes[node.next as usize].prev = node.prev;
+        }
+        let node: &mut WheelNode = &mut self.nodes[handle as usize];
+        node.next = WHEEL_NIL;
+        node.prev = WHEEL_NIL;
+        node.slot = WHEEL_NIL;
+    }
+}
+//---------------------------------------------------------------------------------------------------------------------
+
 //---------------------------------------------------------------------------------------------------------------------
 // Unions
 //---------------------------------------------------------------------------------------------------------------------
@@ -156,3 +495,278 @@ impl TimerData {
 //---------------------------------------------------------------------------------------------------------------------
 // Functions
 //---------------------------------------------------------------------------------------------------------------------
+
+///advance the runtime environment timing wheel by one tick and call the callbacks of the expired timers;
+///is called every WHEEL_TICK_US
+pub fn handle_wheel(rt_data: &mut RteData) {
+    rt_data.swtimer_data.wheel.advance();
+    while let Some((callback, ctx)) = rt_data.swtimer_data.wheel.get_expired() {
+        // the callback which is called here must inform the failure manager to handle failures
+        callback(ctx, rt_data);
+    }
+}
+
+fn wheel_default_callback(_ctx: TimerContext, _rt_data: &mut RteData) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_callback(_ctx: TimerContext, _rt_data: &mut RteData) {}
+
+    #[test]
+    fn invalid_handle_is_rejected() {
+        let mut wheel: TimerWheel<4> = TimerWheel::init();
+        let handle: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+        assert!(wheel.get_state(WheelHandle { idx: 4, gen: 0 }) == Err(WheelError::InvalidHandle));
+        assert!(wheel.get_remaining(WheelHandle { idx: WHEEL_NIL, gen: 0 }) == Err(WheelError::InvalidHandle));
+        //a handle of a timer node that is not allocated
+        let unallocated: WheelHandle = WheelHandle { idx: handle.idx + 1, gen: 0 };
+        assert!(wheel.get_state(unallocated) == Err(WheelError::InvalidHandle));
+        assert!(wheel.start(unallocated, 10, 0) == Err(WheelError::InvalidHandle));
+        assert!(wheel.stop(unallocated) == Err(WheelError::InvalidHandle));
+        //a handle of another generation of the allocated timer node
+        let other_gen: WheelHandle = WheelHandle { idx: handle.idx, gen: handle.gen + 1 };
+        assert!(wheel.get_state(other_gen) == Err(WheelError::InvalidHandle));
+        //a stale handle after the timer node is freed
+        assert!(wheel.start(handle, 10, 0).is_ok());
+        assert!(wheel.get_remaining(handle) == Ok(10));
+        assert!(wheel.free(handle).is_ok());
+        assert!(wheel.get_state(handle) == Err(WheelError::InvalidHandle));
+        assert!(wheel.get_remaining(handle) == Err(WheelError::InvalidHandle));
+        assert!(wheel.free(handle) == Err(WheelError::InvalidHandle));
+    }
+
+    ///start timers are placed at these numbers of ticks; covers the level boundaries of the wheel
+    const BOUNDARY_TICKS: [u32; 14] = [
+        0,
+        1,
+        2,
+        30,
+        31,
+        32,
+        33,
+        1023,
+        1024,
+        1025,
+        2047,
+        2048,
+        WHEEL_MAX_TICKS - 1,
+        WHEEL_MAX_TICKS,
+    ];
+
+    ///advance the wheel until all timers are stopped and get the tick of the expiry of each timer
+    fn run_expiries<const N: usize>(wheel: &mut TimerWheel<N>, max_ticks: u32) -> [Option<u32>; N] {
+        let mut expiries: [Option<u32>; N] = [None; N];
+        for tick in 1..=max_ticks {
+            wheel.advance();
+            while let Some((_, ctx)) = wheel.get_expired() {
+                if let TimerHandle::Wheel(handle) = ctx.timer {
+                    //a single shot timer expires once only
+                    assert!(expiries[handle.idx as usize].is_none());
+                    expiries[handle.idx as usize] = Some(tick);
+                }
+            }
+        }
+        expiries
+    }
+
+    ///start a single shot timer for each of the boundary ticks with the wheel time set to now
+    fn check_boundary_expiries(now: u32) {
+        let mut wheel: TimerWheel<{ BOUNDARY_TICKS.len() }> = TimerWheel::init();
+        wheel.now = now;
+        let mut handles: Vec<WheelHandle> = Vec::new();
+        for ticks in BOUNDARY_TICKS.iter() {
+            let handle: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+            assert!(wheel.start(handle, *ticks, 0).is_ok());
+            assert!(wheel.get_remaining(handle) == Ok((*ticks).max(1)));
+            handles.push(handle);
+        }
+        let expiries = run_expiries(&mut wheel, WHEEL_MAX_TICKS + 1);
+        for (handle, ticks) in handles.iter().zip(BOUNDARY_TICKS.iter()) {
+            //a timer started with 0 ticks expires with the next tick
+            assert_eq!(expiries[handle.idx as usize], Some((*ticks).max(1)), "now {} ticks {}", now, ticks);
+            assert!(wheel.get_state(*handle) == Ok(States::Stopped));
+        }
+    }
+
+    #[test]
+    fn expiry_at_level_boundaries() {
+        //the level boundaries are relative to the wheel time, so also start between and on the wrap of the levels
+        for now in [0, 1, 17, 31, 32, 1000, 1023, 1024, 1055, 32_767, 32_768, 100_000] {
+            check_boundary_expiries(now);
+        }
+    }
+
+    #[test]
+    fn expiry_over_tick_wraparound() {
+        let wrap: [u32; 6] = [40, 1023, 1024, 2000, WHEEL_MAX_TICKS, 0];
+        for now in wrap.iter().map(|before| u32::MAX - before) {
+            check_boundary_expiries(now);
+        }
+    }
+
+    #[test]
+    fn start_out_of_range() {
+        let mut wheel: TimerWheel<1> = TimerWheel::init();
+        let handle: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+        assert!(wheel.start(handle, WHEEL_MAX_TICKS + 1, 0) == Err(WheelError::OutOfRange));
+        assert!(wheel.start(handle, 10, WHEEL_MAX_TICKS + 1) == Err(WheelError::OutOfRange));
+        assert!(wheel.get_state(handle) == Ok(States::Stopped));
+    }
+
+    #[test]
+    fn repeating_timer_over_cascades() {
+        let mut wheel: TimerWheel<3> = TimerWheel::init();
+        //first expiry and period differ, the periods need level 0, 1 and 2 timers
+        let periods: [(u32, u32); 3] = [(5, 31), (40, 1000), (3000, 1025)];
+        wheel.now = u32::MAX - 5000;
+        let mut handles: Vec<WheelHandle> = Vec::new();
+        for (first, period) in periods.iter() {
+            let handle: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+            assert!(wheel.start(handle, *first, *period).is_ok());
+            handles.push(handle);
+        }
+        let mut counts: [u32; 3] = [0; 3];
+        for tick in 1..=20_000u32 {
+            wheel.advance();
+            while let Some((_, ctx)) = wheel.get_expired() {
+                let TimerHandle::Wheel(handle) = ctx.timer else { panic!() };
+                let idx: usize = handle.idx as usize;
+                let (first, period) = periods[idx];
+                assert!(tick >= first && (tick - first) % period == 0, "timer {} tick {}", idx, tick);
+                counts[idx] += 1;
+            }
+        }
+        for (idx, (first, period)) in periods.iter().enumerate() {
+            assert_eq!(counts[idx], (20_000 - first) / period + 1);
+            assert!(wheel.get_state(handles[idx]) == Ok(States::Run));
+        }
+    }
+
+    #[test]
+    fn stop_and_restart_in_shared_slot() {
+        let mut wheel: TimerWheel<4> = TimerWheel::init();
+        //all timers are linked into the same level 1 slot
+        let mut handles: Vec<WheelHandle> = Vec::new();
+        for _ in 0..4 {
+            let handle: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+            assert!(wheel.start(handle, 100, 0).is_ok());
+            handles.push(handle);
+        }
+        //unlink from the middle, the head and the tail of the slot list
+        assert!(wheel.stop(handles[1]).is_ok());
+        assert!(wheel.stop(handles[3]).is_ok());
+        assert!(wheel.stop(handles[0]).is_ok());
+        assert!(wheel.get_remaining(handles[1]) == Ok(0));
+        for _ in 0..50 {
+            wheel.advance();
+        }
+        //restart with the timer located in level 0 now
+        assert!(wheel.start(handles[0], 20, 0).is_ok());
+        let expiries = run_expiries(&mut wheel, 200);
+        assert_eq!(expiries, [Some(20), None, Some(50), None]);
+    }
+
+    #[test]
+    fn exhaust_and_reuse_nodes() {
+        let mut wheel: TimerWheel<2> = TimerWheel::init();
+        let first: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+        let _second: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+        assert!(wheel.alloc(test_callback, 0).is_err_and(|e| e == WheelError::NoFreeTimer));
+        assert!(wheel.free(first).is_ok());
+        let reused: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+        assert!(reused.idx == first.idx && reused != first);
+    }
+
+    #[test]
+    fn stale_handle_does_not_access_reused_node() {
+        let mut wheel: TimerWheel<1> = TimerWheel::init();
+        let stale: WheelHandle = wheel.alloc(test_callback, 0).ok().unwrap();
+        assert!(wheel.free(stale).is_ok());
+        let handle: WheelHandle = wheel.alloc(test_callback, 1).ok().unwrap();
+        assert!(wheel.start(handle, 10, 0).is_ok());
+        assert!(wheel.stop(stale) == Err(WheelError::InvalidHandle));
+        assert!(wheel.start(stale, 20, 0) == Err(WheelError::InvalidHandle));
+        assert!(wheel.free(stale) == Err(WheelError::InvalidHandle));
+        assert!(wheel.get_remaining(handle) == Ok(10));
+        //the expired timer reports the handle of the current generation
+        wheel.now = 9;
+        wheel.advance();
+        let (_, ctx) = wheel.get_expired().unwrap();
+        assert!(ctx.timer == TimerHandle::Wheel(handle) && ctx.user == 1);
+    }
+
+    ///restart the expired timer from its callback with the user value as ticks
+    fn restart_callback(ctx: TimerContext, rt_data: &mut RteData) {
+        if let TimerHandle::Wheel(handle) = ctx.timer {
+            assert!(rt_data.swtimer_data.wheel.start(handle, ctx.user as u32, 0).is_ok());
+        }
+    }
+
+    #[test]
+    fn callback_gets_context_and_runtime_data() {
+        let mut rt_data: RteData = RteData::init();
+        let wheel: &mut TimerWheel<WHEEL_TIMER_NUM> = &mut rt_data.swtimer_data.wheel;
+        let first: WheelHandle = wheel.alloc(restart_callback, 7).ok().unwrap();
+        let second: WheelHandle = wheel.alloc(restart_callback, 40).ok().unwrap();
+        assert!(wheel.start(first, 3, 0).is_ok());
+        assert!(wheel.start(second, 3, 0).is_ok());
+        for _ in 0..3 {
+            handle_wheel(&mut rt_data);
+        }
+        //both timers expired in the same tick and each callback restarted its own timer with its user value
+        assert!(rt_data.swtimer_data.wheel.get_remaining(first) == Ok(7));
+        assert!(rt_data.swtimer_data.wheel.get_remaining(second) == Ok(40));
+    }
+
+    ///number of timers used for the benchmark
+    const BENCH_TIMER_NUM: usize = 64;
+    ///number of ticks used for the benchmark
+    const BENCH_TICKS: u32 = 100_000;
+
+    ///compare the counting of ToutTimer elements (as done by ToutTimer::handle_repeat) with the timing wheel; both
+    ///run BENCH_TIMER_NUM repeating timers with periods spread over all wheel levels; the cost is counted in timer
+    ///accesses per tick, which is deterministic in contrast to a runtime measurement
+    #[test]
+    fn benchmark_linear_against_wheel() {
+        let mut linear: [ToutTimer; BENCH_TIMER_NUM] = [ToutTimer {
+            current_val: 0,
+            end_val: 0,
+            state: States::Run,
+            callback: test_callback,
+            user: 0,
+        }; BENCH_TIMER_NUM];
+        let mut wheel: TimerWheel<BENCH_TIMER_NUM> = TimerWheel::init();
+        for (i, t) in linear.iter_mut().enumerate() {
+            let period: u32 = 10 + (i as u32 * 37) % 2000;
+            //the linear timer expires with the tick after reaching the end value
+            t.end_val = period - 1;
+            let handle: WheelHandle = wheel.alloc(test_callback, i).ok().unwrap();
+            assert!(wheel.start(handle, period, period).is_ok());
+        }
+
+        let mut linear_ops: u64 = 0;
+        let mut linear_expired: u64 = 0;
+        let mut wheel_ops: u64 = 0;
+        let mut wheel_expired: u64 = 0;
+        for _ in 0..BENCH_TICKS {
+            for t in linear.iter_mut() {
+                linear_ops += 1;
+                if t.count(true) {
+                    linear_expired += 1;
+                }
+            }
+            wheel_ops += wheel.advance() as u64;
+            while wheel.get_expired().is_some() {
+                wheel_ops += 1;
+                wheel_expired += 1;
+            }
+        }
+        assert_eq!(linear_expired, wheel_expired);
+        assert_eq!(linear_ops, 6_400_000);
+        //the wheel touches each timer once per expiry plus at most two cascades
+        assert!(wheel_ops <= 3 * wheel_expired);
+        assert_eq!((wheel_expired, wheel_ops), (28_209, 48_206));
+    }
+}